@@ -1,44 +1,209 @@
+// How many results to show for a search
+var MAX_SEARCH_RESULTS = 20;
+
+// Whether the preview server searches for us, so that the index doesn't
+// have to be downloaded
+var SEARCH_API = false;
+
 function search() {
     box = document.getElementById('search-box');
     list = document.getElementById('search-results');
-    list.innerHTML = '';
 
     if (box.value == "") {
+        list.innerHTML = '';
         return
     }
 
-    config = {
-        fields: {
-            title: {
-                boost: 2,
-            },
-            body: {
-                boost: 1
+    if (SEARCH_API) {
+        searchWithApi(box.value);
+        return;
+    }
+
+    list.innerHTML = '';
+
+    var terms = searchTerms(box.value);
+
+    // Sharded indices are only loaded as far as the search needs. Searching
+    // again once a file has loaded adds the results from it.
+    neededSearchFiles(terms).forEach(loadSearchFile);
+
+    searchResults(terms).slice(0, MAX_SEARCH_RESULTS).forEach(function(result) {
+        var doc = searchDoc(result.doc);
+        if (doc) {
+            showSearchResult(doc, escapeHtml(doc.title), escapeHtml(doc.excerpt));
+        }
+    });
+}
+
+function searchWithApi(query) {
+    var url = DOCTAVE_URI_PREFIX + '/api/search?q=' + encodeURIComponent(query) +
+        '&limit=' + MAX_SEARCH_RESULTS;
+
+    fetch(url)
+        .then(function(response) {
+            if (!response.ok) {
+                throw new Error("HTTP error " + response.status);
+            }
+            return response.json();
+        })
+        .then(function(json) {
+            // Results for an older query arrived after newer ones
+            if (json.query !== document.getElementById('search-box').value) {
+                return;
             }
-        },
-        bool: "OR",
-        expand: true
+
+            document.getElementById('search-results').innerHTML = '';
+            json.results.forEach(function(result) {
+                showSearchResult(result, result.highlights.title, result.highlights.excerpt);
+            });
+        });
+}
+
+// Adds a result to the list. The title and excerpt are HTML.
+function showSearchResult(doc, title, excerpt) {
+    // Sites built with relative links have URIs relative to their root
+    var uri = doc.uri;
+    if (uri.charAt(0) !== '/') {
+        uri = DOCTAVE_URI_PREFIX + '/' + uri;
+    }
+
+    // Sections of a page show which page they are on
+    var page = "";
+    if (doc.page !== doc.title) {
+        page = "<span class='search-result-item-page'>" + escapeHtml(doc.page) + "</span>";
+    }
+
+    listItem = document.createElement("li");
+    listItem.className = "search-result-item";
+    listItem.innerHTML =
+        "<a href='" + escapeHtml(uri) + "'>" + page + title +
+        "<p class='search-result-item-preview'>" + excerpt + "</p>" +
+        "</a>";
+
+    document.getElementById('search-results').appendChild(listItem);
+}
+
+// Splits text into terms the same way the index was built, in
+// src/search.rs
+function searchTerms(text) {
+    return text.toLowerCase()
+        .split(/[^\p{Alphabetic}\p{N}]+/u)
+        .filter(function(word) {
+            return word !== "" && !SEARCH_STOP_WORDS.hasOwnProperty(word);
+        })
+        .map(function(word) {
+            return SEARCH_INDEX.stemming ? porterStemmer(word) : word;
+        });
+}
+
+// The files of a sharded index that can have matches for the terms
+function neededSearchFiles(terms) {
+    var shards = SEARCH_INDEX.shards;
+    if (!shards) {
+        return [];
+    }
+
+    if (SEARCH_INDEX.sharding === 'prefix') {
+        return terms
+            .map(function(term) { return shards[Array.from(term)[0]]; })
+            .filter(function(file) { return file; });
     }
 
-    INDEX.search(box.value, config).forEach(function(result) {
-        listItem = document.createElement("li");
-        listItem.className = "search-result-item";
-        listItem.innerHTML =
-            "<a href='" + result.doc.uri + "'>" + result.doc.title +
-            "<p class='search-result-item-preview'>" + searchPreview(result.doc.body) + "</p>" +
-            "</a>";
+    // Every section can have matches, but the one being read comes first
+    var current = window.location.pathname.slice(DOCTAVE_URI_PREFIX.length).split('/')[1] || '';
+
+    return Object.keys(shards)
+        .sort(function(a, b) { return (b === current) - (a === current); })
+        .map(function(section) { return shards[section]; });
+}
+
+// Scores the sections that have words starting with the terms, the way
+// elasticlunr does. Words that only start with a term count for less.
+function searchResults(terms) {
+    var sources = [];
+    if (SEARCH_INDEX.terms) {
+        sources.push(SEARCH_INDEX.terms);
+    }
+    Object.keys(SEARCH_FILES).forEach(function(file) {
+        if (SEARCH_FILES[file] && SEARCH_FILES[file].terms) {
+            sources.push(SEARCH_FILES[file].terms);
+        }
+    });
+
+    var scores = {};
+    var matches = {};
+
+    terms.forEach(function(term, i) {
+        sources.forEach(function(source) {
+            Object.keys(source).forEach(function(word) {
+                if (word.indexOf(term) !== 0) {
+                    return;
+                }
+
+                var similarity = term.length / word.length;
 
-        list.appendChild(listItem);
+                source[word].forEach(function(posting) {
+                    var doc = posting[0];
+                    var score = posting[1] * SEARCH_INDEX.boosts.title +
+                        posting[2] * SEARCH_INDEX.boosts.body;
+
+                    scores[doc] = (scores[doc] || 0) + score * similarity;
+                    matches[doc] = matches[doc] || {};
+                    matches[doc][i] = true;
+                });
+            });
+        });
     });
+
+    return Object.keys(scores)
+        .map(function(doc) {
+            // Sections that match more of the terms come first
+            var matched = Object.keys(matches[doc]).length;
+
+            return { doc: Number(doc), score: scores[doc] * matched / terms.length };
+        })
+        .sort(function(a, b) { return b.score - a.score; });
 }
 
-function searchPreview(body) {
-    return body.substring(0, 100)
-        .replace(/=+/g, "")
-        .replace(/#+/g, "")
-        .replace(/\*+/g, "")
-        .replace(/_+/g, "") +
-        "...";
+// The title, URI and excerpt of a search result, if they have loaded
+function searchDoc(id) {
+    if (SEARCH_INDEX.docs) {
+        return SEARCH_INDEX.docs[id];
+    }
+
+    var file = SEARCH_INDEX.doc_files[Math.floor(id / SEARCH_INDEX.docs_per_file)];
+    loadSearchFile(file);
+
+    var docs = SEARCH_FILES[file];
+    return docs && docs[id % SEARCH_INDEX.docs_per_file];
+}
+
+function loadSearchFile(file) {
+    if (SEARCH_FILES.hasOwnProperty(file)) {
+        return;
+    }
+    SEARCH_FILES[file] = null;
+
+    fetch(DOCTAVE_URI_PREFIX + '/' + file + '?v=' + DOCTAVE_TIMESTAMP)
+        .then(function(response) {
+            if (!response.ok) {
+                throw new Error("HTTP error " + response.status);
+            }
+            return response.json();
+        })
+        .then(function(json) {
+            SEARCH_FILES[file] = json;
+            search();
+        });
+}
+
+function escapeHtml(text) {
+    return text
+        .replace(/&/g, "&amp;")
+        .replace(/</g, "&lt;")
+        .replace(/>/g, "&gt;")
+        .replace(/"/g, "&quot;")
+        .replace(/'/g, "&#39;");
 }
 
 function disableScrollifMenuOpen() {
@@ -83,6 +248,11 @@ function scrolledUp() {
 }
 
 function dragRightMenu() {
+    // Layouts without the right sidebar have nothing to drag
+    if (!document.getElementById('page-nav')) {
+        return;
+    }
+
     if (atTop()) {
         document.getElementById('page-nav').classList.remove('fixed');
         document.getElementsByClassName('sidebar-right')[0].classList.remove('bottom');
@@ -123,17 +293,20 @@ function setColor() {
     var color = localStorage.getItem('doctave-color')
 
     if (color === 'dark') {
-        document.querySelector("link[rel='stylesheet'][href*='prism-']").href = "/assets/prism-atom-dark.css?v=" + DOCTAVE_TIMESTAMP;
+        document.querySelector("link[rel='stylesheet'][href*='prism-']").href = DOCTAVE_URI_PREFIX + "/assets/prism-atom-dark.css?v=" + DOCTAVE_TIMESTAMP;
         document.getElementsByTagName('html')[0].classList.remove('light');
         document.getElementsByTagName('html')[0].classList.add('dark');
     } else {
-        document.querySelector("link[rel='stylesheet'][href*='prism-']").href = "/assets/prism-ghcolors.css?" + DOCTAVE_TIMESTAMP;
+        document.querySelector("link[rel='stylesheet'][href*='prism-']").href = DOCTAVE_URI_PREFIX + "/assets/prism-ghcolors.css?" + DOCTAVE_TIMESTAMP;
         document.getElementsByTagName('html')[0].classList.remove('dark');
         document.getElementsByTagName('html')[0].classList.add('light');
     }
 }
 
-document.getElementById("light-dark-mode-switch").addEventListener("click", toggleColor);
+var colorSwitch = document.getElementById("light-dark-mode-switch");
+if (colorSwitch) {
+    colorSwitch.addEventListener("click", toggleColor);
+}
 
 
 // Initialize mermaid.js based on color theme
@@ -145,29 +318,78 @@ if (color === 'dark') {
     mermaid.initialize({'theme': 'default'});
 }
 
-var INDEX;
+var SEARCH_INDEX;
+var SEARCH_STOP_WORDS = {};
+// The files of a sharded index that have been loaded, or are loading
+var SEARCH_FILES = {};
 
-// Load search index
-fetch('/search_index.json')
-    .then(function(response) {
-        if (!response.ok) {
-            throw new Error("HTTP error " + response.status);
-        }
-        return response.json();
-    })
-    .then(function(json) {
-        INDEX = elasticlunr.Index.load(json)
+function loadSearchIndex(json) {
+    SEARCH_INDEX = json;
+    SEARCH_INDEX.stop_words.forEach(function(word) {
+        SEARCH_STOP_WORDS[word] = true;
+    });
+
+    enableSearch();
+}
+
+function enableSearch() {
+    // Not every layout has a search box
+    if (document.getElementById('search-box')) {
         document.getElementById('search-box').oninput = search;
         search();
-    });
+    }
+}
+
+function fetchSearchIndex() {
+    fetch(DOCTAVE_URI_PREFIX + '/search_index.json?v=' + DOCTAVE_TIMESTAMP)
+        .then(function(response) {
+            if (!response.ok) {
+                throw new Error("HTTP error " + response.status);
+            }
+            return response.json();
+        })
+        .then(loadSearchIndex);
+}
+
+// Load search index. Sites built with relative links include it as a
+// script, since pages opened from disk can't fetch it. Sites without
+// search don't include the stemmer or the index at all.
+//
+// The preview server can search the site itself, so pages it renders ask it
+// first, and only download the index if it doesn't answer.
+if (typeof DOCTAVE_SEARCH === 'undefined' || !DOCTAVE_SEARCH) {
+    // Search is turned off
+} else if (typeof DOCTAVE_SEARCH_INDEX !== 'undefined') {
+    loadSearchIndex(DOCTAVE_SEARCH_INDEX);
+} else if (typeof DOCTAVE_SEARCH_API !== 'undefined' && DOCTAVE_SEARCH_API) {
+    fetch(DOCTAVE_URI_PREFIX + '/api/search?q=')
+        .then(function(response) {
+            var type = response.headers.get('Content-Type') || '';
+
+            if (!response.ok || type.indexOf('application/json') !== 0) {
+                throw new Error("No search API");
+            }
+
+            SEARCH_API = true;
+            enableSearch();
+        })
+        .catch(fetchSearchIndex);
+} else {
+    fetchSearchIndex();
+}
 
 // Setup keyboard shortcuts
 
 document.onkeydown = function(e) {
     var searchResults = document.getElementById('search-results');
-    var first = searchResults.firstChild;
     var searchBox = document.getElementById('search-box');
 
+    if (!searchBox || !searchResults) {
+        return;
+    }
+
+    var first = searchResults.firstChild;
+
     switch (e.keyCode) {
         case 83: // The S key
             if (document.activeElement == searchBox) {
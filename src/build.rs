@@ -1,7 +1,9 @@
+use std::io::Write;
 use std::time::Instant;
 
 use bunt::termcolor::{ColorChoice, StandardStream};
 
+use crate::check;
 use crate::config::Config;
 use crate::site::{BuildMode, Site};
 use crate::Result;
@@ -11,8 +13,14 @@ pub struct BuildCommand {
     site: Site,
 }
 
+#[derive(Default)]
+pub struct BuildOptions {
+    /// Fail the build if any documents contain broken links
+    pub strict: bool,
+}
+
 impl BuildCommand {
-    pub fn run(config: Config) -> Result<()> {
+    pub fn run(options: BuildOptions, config: Config) -> Result<()> {
         let mut stdout = if config.color_enabled() {
             StandardStream::stdout(ColorChoice::Auto)
         } else {
@@ -45,9 +53,63 @@ impl BuildCommand {
         let duration = start.elapsed();
 
         if result.is_ok() {
-            bunt::writeln!(stdout, "Site built in {$bold}{:?}{/$}\n", duration)?;
+            bunt::writeln!(stdout, "Site built in {$bold}{:?}{/$}", duration)?;
+
+            // Sites without search have no index
+            let index = cmd.site.search_index_size();
+            if index.files > 0 {
+                bunt::writeln!(
+                    stdout,
+                    "Search index is {$bold}{}{/$} in {} {}",
+                    human_size(index.bytes),
+                    index.files,
+                    if index.files == 1 { "file" } else { "files" }
+                )?;
+            }
+
+            writeln!(stdout)?;
+        }
+
+        result?;
+
+        check::report_drafts(&mut stdout, &cmd.site.draft_links()?)?;
+
+        if options.strict {
+            let broken_links = cmd.site.check()?;
+
+            check::report(&mut stdout, &broken_links)?;
         }
 
-        result
+        Ok(())
+    }
+}
+
+/// Formats a number of bytes the way file managers do, like `12.3 KB`.
+fn human_size(bytes: u64) -> String {
+    let units = ["KB", "MB", "GB"];
+
+    if bytes < 1000 {
+        return format!("{} B", bytes);
+    }
+
+    let mut size = bytes as f64 / 1000.0;
+    let mut unit = 0;
+    while size >= 1000.0 && unit < units.len() - 1 {
+        size /= 1000.0;
+        unit += 1;
+    }
+
+    format!("{:.1} {}", size, units[unit])
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn human_sizes() {
+        assert_eq!(human_size(999), "999 B");
+        assert_eq!(human_size(12_345), "12.3 KB");
+        assert_eq!(human_size(4_200_000), "4.2 MB");
     }
 }
@@ -0,0 +1,444 @@
+use std::collections::HashMap;
+use std::ffi::OsStr;
+use std::fmt;
+use std::path::{Component, Path, PathBuf};
+
+use bunt::termcolor::{ColorChoice, StandardStream};
+use pulldown_cmark::{Event, Options, Parser, Tag};
+
+use crate::config::Config;
+use crate::site::Site;
+use crate::{frontmatter, Directory, Document};
+use crate::{Error, Result};
+
+static INCLUDE_DIR: &str = "_include";
+
+pub struct CheckCommand {
+    config: Config,
+    site: Site,
+}
+
+impl CheckCommand {
+    pub fn run(config: Config) -> Result<()> {
+        let mut stdout = if config.color_enabled() {
+            StandardStream::stdout(ColorChoice::Auto)
+        } else {
+            StandardStream::stdout(ColorChoice::Never)
+        };
+
+        let site = Site::new(config.clone());
+        let cmd = CheckCommand { config, site };
+
+        bunt::writeln!(stdout, "{$bold}{$blue}Doctave | Check{/$}{/$}")?;
+        bunt::writeln!(
+            stdout,
+            "Checking links in {$bold}{}{/$}\n",
+            cmd.config.docs_dir().display()
+        )?;
+
+        let broken_links = cmd.site.check()?;
+
+        report_drafts(&mut stdout, &cmd.site.draft_links()?)?;
+        report(&mut stdout, &broken_links)
+    }
+}
+
+/// Prints out a warning for each link to a draft page.
+pub fn report_drafts(stdout: &mut StandardStream, draft_links: &[BrokenLink]) -> Result<()> {
+    for draft_link in draft_links {
+        bunt::writeln!(stdout, "{$yellow}Warning:{/$} {}", draft_link)?;
+    }
+
+    if !draft_links.is_empty() {
+        bunt::writeln!(stdout, "")?;
+    }
+
+    Ok(())
+}
+
+/// Prints out any broken links, and returns an error if there were any.
+pub fn report(stdout: &mut StandardStream, broken_links: &[BrokenLink]) -> Result<()> {
+    for broken_link in broken_links {
+        bunt::writeln!(stdout, "{$red}Broken link{/$} {}", broken_link)?;
+    }
+
+    if broken_links.is_empty() {
+        bunt::writeln!(stdout, "{$green}No broken links found{/$}\n")?;
+
+        Ok(())
+    } else {
+        Err(Error::new(format!(
+            "Found {} broken link(s)",
+            broken_links.len()
+        )))
+    }
+}
+
+/// A link or image in a Markdown document that does not point to anything.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BrokenLink {
+    /// Path to the Markdown file, relative to the project root
+    pub file: PathBuf,
+    pub line: usize,
+    pub column: usize,
+    pub destination: String,
+    pub reason: String,
+}
+
+impl fmt::Display for BrokenLink {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}:{}:{}: \"{}\" {}",
+            self.file.display(),
+            self.line,
+            self.column,
+            self.destination,
+            self.reason
+        )
+    }
+}
+
+/// Validates that the links and images in every document point to an
+/// existing page, a heading on that page, or a file in the _include
+/// directory.
+///
+/// Relative links are resolved against the URI of the page they appear on,
+/// the same way the browser resolves them.
+pub struct LinkChecker<'a> {
+    config: &'a Config,
+    pages: HashMap<String, &'a Document>,
+    docs: Vec<&'a Document>,
+}
+
+impl<'a> LinkChecker<'a> {
+    pub fn new(config: &'a Config, root: &'a Directory) -> Self {
+        let docs = root.docs_recursive();
+        let pages = docs.iter().map(|d| (d.uri_path(), *d)).collect();
+
+        LinkChecker {
+            config,
+            pages,
+            docs,
+        }
+    }
+
+    pub fn run(&self) -> Vec<BrokenLink> {
+        let mut broken_links = vec![];
+
+        for doc in &self.docs {
+            broken_links.append(&mut self.check_document(doc));
+        }
+
+        broken_links.sort_by(|a, b| (&a.file, a.line, a.column).cmp(&(&b.file, b.line, b.column)));
+        broken_links
+    }
+
+    /// Finds links in documents that will be published, which point to
+    /// drafts. They work while previewing the site, but are broken in
+    /// release builds, where drafts are left out.
+    pub fn draft_links(&self) -> Vec<BrokenLink> {
+        let mut draft_links = vec![];
+
+        for doc in self
+            .docs
+            .iter()
+            .copied()
+            .filter(|doc| !doc.frontmatter.draft)
+        {
+            draft_links.append(&mut self.find_links(doc, |event| match event {
+                Event::Start(Tag::Link(_, destination, _)) => {
+                    let (path, _) = split_destination(destination);
+
+                    match self.target(doc, path) {
+                        Some(Some(target)) if target.frontmatter.draft => Some(String::from(
+                            "points to a draft page, which is left out of release builds",
+                        )),
+                        _ => None,
+                    }
+                }
+                _ => None,
+            }));
+        }
+
+        draft_links.sort_by(|a, b| (&a.file, a.line, a.column).cmp(&(&b.file, b.line, b.column)));
+        draft_links
+    }
+
+    fn check_document(&self, doc: &Document) -> Vec<BrokenLink> {
+        self.find_links(doc, |event| match event {
+            Event::Start(Tag::Link(_, destination, _)) => self.check_link(doc, destination),
+            Event::Start(Tag::Image(_, destination, _)) => self.check_image(doc, destination),
+            _ => None,
+        })
+    }
+
+    /// Goes through the links and images in a document, reporting the ones
+    /// the given function returns a reason for.
+    fn find_links<F>(&self, doc: &Document, reason: F) -> Vec<BrokenLink>
+    where
+        F: Fn(&Event) -> Option<String>,
+    {
+        let offset = frontmatter::end_pos(&doc.raw);
+        let mut broken_links = vec![];
+
+        for (event, range) in
+            Parser::new_ext(doc.markdown_section(), Options::all()).into_offset_iter()
+        {
+            let destination = match &event {
+                Event::Start(Tag::Link(_, destination, _))
+                | Event::Start(Tag::Image(_, destination, _)) => destination,
+                _ => continue,
+            };
+
+            if let Some(reason) = reason(&event) {
+                let (line, column) = line_and_column(&doc.raw, offset + range.start);
+
+                broken_links.push(BrokenLink {
+                    file: self.relative_docs_dir().join(&doc.source),
+                    line,
+                    column,
+                    destination: destination.to_string(),
+                    reason,
+                });
+            }
+        }
+
+        broken_links
+    }
+
+    /// Returns the reason the link is broken, if it is.
+    fn check_link(&self, doc: &Document, destination: &str) -> Option<String> {
+        if is_external(destination) {
+            return None;
+        }
+
+        let (path, anchor) = split_destination(destination);
+
+        let target = match self.target(doc, path) {
+            Some(target) => target,
+            None => return Some(String::from("points outside the docs directory")),
+        };
+
+        match (target, anchor) {
+            (Some(target), Some(anchor)) => {
+                if target.headings().iter().any(|h| h.anchor == anchor) {
+                    None
+                } else {
+                    Some(format!(
+                        "points to a heading that does not exist on {}",
+                        target.uri_path()
+                    ))
+                }
+            }
+            (Some(_), None) => None,
+            (None, _) => {
+                if self.include_exists(doc, path) {
+                    None
+                } else {
+                    Some(String::from("points to a page that does not exist"))
+                }
+            }
+        }
+    }
+
+    /// The page a link path in a document points to, if any. Returns `None`
+    /// if the path points outside the docs directory.
+    fn target(&self, doc: &'a Document, path: &str) -> Option<Option<&'a Document>> {
+        if path.is_empty() {
+            Some(Some(doc))
+        } else {
+            resolve(&link_base(doc), path)
+                .map(|resolved| self.pages.get(&page_uri(&resolved)).copied())
+        }
+    }
+
+    /// Returns the reason the image is broken, if it is.
+    fn check_image(&self, doc: &Document, destination: &str) -> Option<String> {
+        if is_external(destination) {
+            return None;
+        }
+
+        let (path, _) = split_destination(destination);
+
+        if self.include_exists(doc, path) {
+            None
+        } else {
+            Some(format!(
+                "points to an image that does not exist in {}",
+                self.relative_docs_dir().join(INCLUDE_DIR).display()
+            ))
+        }
+    }
+
+    fn relative_docs_dir(&self) -> &Path {
+        self.config
+            .docs_dir()
+            .strip_prefix(self.config.project_root())
+            .unwrap_or_else(|_| self.config.docs_dir())
+    }
+
+    fn include_exists(&self, doc: &Document, destination: &str) -> bool {
+        resolve(&link_base(doc), destination)
+            .map(|resolved| {
+                self.config
+                    .include_dirs()
+                    .iter()
+                    .any(|dir| dir.join(&resolved).is_file())
+            })
+            .unwrap_or(false)
+    }
+}
+
+/// Anything with a scheme, like `https:` or `mailto:`, is not ours to check.
+fn is_external(destination: &str) -> bool {
+    destination.starts_with("//")
+        || destination
+            .find(':')
+            .map(|colon| !destination[..colon].contains('/'))
+            .unwrap_or(false)
+}
+
+/// Splits a link destination into its path and anchor, dropping any query
+/// string.
+fn split_destination(destination: &str) -> (&str, Option<&str>) {
+    let (path, anchor) = match destination.find('#') {
+        Some(hash) => (&destination[..hash], Some(&destination[hash + 1..])),
+        None => (destination, None),
+    };
+
+    let path = match path.find('?') {
+        Some(question_mark) => &path[..question_mark],
+        None => path,
+    };
+
+    (path, anchor.filter(|a| !a.is_empty()))
+}
+
+/// The directory relative links on a page are resolved against, relative
+/// to the docs directory. This is the parent of the page's URI, except for
+/// directory indices, which are served as the directory itself.
+fn link_base(doc: &Document) -> PathBuf {
+    let uri = doc.uri_path();
+    let uri = Path::new(uri.trim_start_matches('/'));
+
+    if doc.html_path().file_name() == Some(OsStr::new("index.html")) {
+        uri.to_path_buf()
+    } else {
+        uri.parent().map(|p| p.to_path_buf()).unwrap_or_default()
+    }
+}
+
+/// Resolves a link destination into a path relative to the docs directory.
+///
+/// Absolute destinations are relative to the root of the docs directory,
+/// while relative ones are relative to the given base directory.
+/// Returns None if the destination climbs out of the docs directory.
+pub fn resolve(base: &Path, destination: &str) -> Option<PathBuf> {
+    let base = if destination.starts_with('/') {
+        PathBuf::new()
+    } else {
+        base.to_path_buf()
+    };
+
+    let mut resolved = PathBuf::new();
+
+    for component in base.join(destination.trim_start_matches('/')).components() {
+        match component {
+            Component::Normal(c) => resolved.push(c),
+            Component::ParentDir => {
+                if !resolved.pop() {
+                    return None;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    Some(resolved)
+}
+
+/// Converts a resolved link path into the URI of the page it would point to.
+/// Links may point to the Markdown file, the HTML file, or the bare URI.
+pub fn page_uri(resolved: &Path) -> String {
+    let mut path = resolved.to_path_buf();
+
+    let extension = path.extension().and_then(|e| e.to_str());
+    if extension == Some("md") || extension == Some("html") {
+        path.set_extension("");
+    }
+
+    if path.file_name().and_then(|f| f.to_str()) == Some("README") {
+        path.set_file_name("index");
+    }
+
+    crate::navigation::Link::path_to_uri(&path)
+}
+
+/// Converts a byte offset into a 1-based line and column.
+fn line_and_column(input: &str, offset: usize) -> (usize, usize) {
+    let before = &input[..offset];
+    let line = before.matches('\n').count() + 1;
+    let column = before[before.rfind('\n').map(|n| n + 1).unwrap_or(0)..]
+        .chars()
+        .count()
+        + 1;
+
+    (line, column)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn external_links() {
+        assert!(is_external("https://doctave.com"));
+        assert!(is_external("mailto:nik@doctave.com"));
+        assert!(is_external("//cdn.example.com/image.png"));
+        assert!(!is_external("/features/markdown"));
+        assert!(!is_external("../tutorial.md#step-1"));
+    }
+
+    #[test]
+    fn resolving_relative_links() {
+        assert_eq!(
+            resolve(Path::new("features"), "../tutorial.md"),
+            Some(PathBuf::from("tutorial.md"))
+        );
+        assert_eq!(
+            resolve(Path::new("features"), "mermaid-js.md"),
+            Some(PathBuf::from("features").join("mermaid-js.md"))
+        );
+        assert_eq!(
+            resolve(Path::new("features"), "/installing"),
+            Some(PathBuf::from("installing"))
+        );
+        assert_eq!(resolve(Path::new(""), "../../secrets"), None);
+    }
+
+    #[test]
+    fn page_uris() {
+        assert_eq!(page_uri(Path::new("features/README.md")), "/features");
+        assert_eq!(
+            page_uri(Path::new("features/markdown.md")),
+            "/features/markdown"
+        );
+        assert_eq!(
+            page_uri(Path::new("features/markdown.html")),
+            "/features/markdown"
+        );
+        assert_eq!(
+            page_uri(Path::new("features/markdown")),
+            "/features/markdown"
+        );
+        assert_eq!(page_uri(Path::new("")), "/");
+    }
+
+    #[test]
+    fn line_and_column_of_offset() {
+        let input = "---\ntitle: Hi\n---\n\nSee [here](/nowhere)";
+
+        assert_eq!(line_and_column(input, input.find('[').unwrap()), (5, 5));
+    }
+}
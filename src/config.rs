@@ -1,21 +1,38 @@
+use std::collections::{BTreeMap, HashMap};
+use std::ffi::OsStr;
 use std::fs;
-use std::path::{Path, PathBuf};
+use std::path::{Component, Path, PathBuf};
 
 use colorsys::prelude::*;
 use colorsys::Rgb;
+use glob::{MatchOptions, Pattern};
 use serde::Deserialize;
+use walkdir::WalkDir;
 
-use crate::{Error, Result};
-use crate::site::BuildMode;
+use crate::languages;
 use crate::navigation::Link;
+use crate::search::{Boosts, SearchSettings, Sharding};
+use crate::site::BuildMode;
+use crate::toc::{Toc, TocYaml};
+use crate::{Error, Result};
 
 #[derive(Debug, Clone, Deserialize)]
 struct DoctaveYaml {
     title: String,
+    docs_dir: Option<PathBuf>,
+    base_path: Option<String>,
+    url: Option<String>,
+    robots: Option<String>,
     port: Option<u32>,
     colors: Option<ColorsYaml>,
     logo: Option<PathBuf>,
+    og_image: Option<String>,
+    templates: Option<PathBuf>,
+    toc: Option<TocYaml>,
+    search: Option<SearchYaml>,
     navigation: Option<Vec<Navigation>>,
+    versions: Option<Vec<VersionYaml>>,
+    languages: Option<Vec<LanguageYaml>>,
 }
 
 impl DoctaveYaml {
@@ -29,8 +46,15 @@ impl DoctaveYaml {
         }
     }
 
+    /// The docs directory, relative to the project root
+    fn docs_path(&self) -> PathBuf {
+        self.docs_dir
+            .clone()
+            .unwrap_or_else(|| PathBuf::from(DEFAULT_DOCS_DIR))
+    }
+
     /// Runs checks that validate the values of provided in the Yaml file
-    fn validate(&self, project_root: &Path) -> Result<()> {
+    fn validate(&self, project_root: &Path, locations: &NavigationLocations) -> Result<()> {
         // Validate color
         if let Some(color) = &self.colors.as_ref().and_then(|c| c.main.as_ref()) {
             Rgb::from_hex_str(color).map_err(|_e| {
@@ -42,9 +66,31 @@ impl DoctaveYaml {
             })?;
         }
 
+        // Validate docs directory exists
+        if let Some(p) = &self.docs_dir {
+            let location = project_root.join(p);
+            if !location.is_dir() {
+                return Err(Error::new(format!(
+                    "Could not find docs directory specified in doctave.yaml at {}.\n\
+                     The docs path should be relative to the project root.",
+                    location.display()
+                )));
+            }
+        }
+
+        // Validate base path
+        if let Some(base_path) = &self.base_path {
+            normalize_base_path(base_path)?;
+        }
+
+        // Validate url
+        if let Some(url) = &self.url {
+            normalize_url(url)?;
+        }
+
         // Validate logo exists
         if let Some(p) = &self.logo {
-            let location = project_root.join("docs").join("_include").join(p);
+            let location = project_root.join(self.docs_path()).join("_include").join(p);
             if !location.exists() {
                 return Err(Error::new(format!(
                     "Could not find logo specified in doctave.yaml at {}.\n\
@@ -54,55 +100,316 @@ impl DoctaveYaml {
             }
         }
 
-        // Validate navigation paths exist
-        // Validate navigation wildcards recursively
+        // Validate Open Graph image exists, unless it's hosted elsewhere
+        if let Some(p) = self.og_image.as_ref().filter(|p| !is_url(p)) {
+            let location = project_root.join(self.docs_path()).join("_include").join(p);
+            if !location.exists() {
+                return Err(Error::new(format!(
+                    "Could not find og_image specified in doctave.yaml at {}.\n\
+                     The image path should be relative to the _include directory, or a full URL.",
+                    location.display()
+                )));
+            }
+        }
+
+        // Validate templates directory exists
+        if let Some(p) = &self.templates {
+            let location = project_root.join(p);
+            if !location.is_dir() {
+                return Err(Error::new(format!(
+                    "Could not find templates directory specified in doctave.yaml at {}.\n\
+                     The templates path should be relative to the project root.",
+                    location.display()
+                )));
+            }
+        }
+
+        // Validate search settings
+        if let Some(SearchYaml::Settings(search)) = &self.search {
+            for pattern in &search.exclude {
+                Pattern::new(pattern).map_err(|e| {
+                    Error::new(format!(
+                        "Invalid pattern '{}' in search.exclude in doctave.yaml.\n{}",
+                        pattern, e.msg
+                    ))
+                })?;
+            }
+
+            if let Some(boosts) = &search.boosts {
+                for (field, boost) in &[("title", boosts.title), ("body", boosts.body)] {
+                    match boost {
+                        Some(boost) if !(boost.is_finite() && *boost >= 0.0) => {
+                            return Err(Error::new(format!(
+                                "Invalid search.boosts.{} in doctave.yaml.\n\
+                                 Boosts have to be zero or a positive number. Found {}",
+                                field, boost
+                            )));
+                        }
+                        _ => {}
+                    }
+                }
+            }
+        }
+
+        // Validate table of contents levels
+        if let Some(toc) = &self.toc {
+            toc.validate()
+                .map_err(|e| Error::new(format!("Invalid toc in doctave.yaml.\n{}", e)))?;
+        }
+
+        // Validate versions
+        if let Some(versions) = &self.versions {
+            let mut names = std::collections::HashSet::new();
+
+            for version in versions {
+                if version.name.is_empty()
+                    || version.name == LATEST_VERSION_ALIAS
+                    || version.name.contains(&['/', '\\'][..])
+                {
+                    return Err(Error::new(format!(
+                        "Invalid version name '{}' in doctave.yaml.\n\
+                         Version names can't be empty, contain slashes, or be \"{}\".",
+                        version.name, LATEST_VERSION_ALIAS
+                    )));
+                }
+
+                if !names.insert(&version.name) {
+                    return Err(Error::new(format!(
+                        "Version '{}' is listed more than once in doctave.yaml",
+                        version.name
+                    )));
+                }
+
+                if let (None, Some(path)) = (&version.git, &version.path) {
+                    let location = project_root.join(path);
+                    if !location.is_dir() {
+                        return Err(Error::new(format!(
+                            "Could not find directory for version '{}' specified in \
+                             doctave.yaml at {}.\n\
+                             The path should be relative to the project root.",
+                            version.name,
+                            location.display()
+                        )));
+                    }
+                }
+            }
+        }
+
+        // Validate languages
+        if let Some(langs) = &self.languages {
+            let mut codes = std::collections::HashSet::new();
+
+            for lang in langs {
+                if lang.code.is_empty()
+                    || !lang
+                        .code
+                        .chars()
+                        .all(|c| c.is_ascii_alphanumeric() || c == '-')
+                {
+                    return Err(Error::new(format!(
+                        "Invalid language code '{}' in doctave.yaml.\n\
+                         Language codes can only contain letters, numbers, and dashes.",
+                        lang.code
+                    )));
+                }
+
+                if !codes.insert(&lang.code) {
+                    return Err(Error::new(format!(
+                        "Language '{}' is listed more than once in doctave.yaml",
+                        lang.code
+                    )));
+                }
+
+                let stemming = lang.search.stemming.or(match &self.search {
+                    Some(SearchYaml::Settings(search)) => search.language.stemming,
+                    _ => None,
+                });
+                if stemming == Some(true) && !languages::has_stemmer(&lang.code) {
+                    return Err(Error::new(format!(
+                        "Invalid search.stemming for language '{}' in doctave.yaml.\n\
+                         Only English can be stemmed. Set search.stemming to false for this \
+                         language.",
+                        lang.code
+                    )));
+                }
+
+                for name in lang.strings.keys() {
+                    if !languages::string_names().any(|known| known == name) {
+                        return Err(Error::new(format!(
+                            "Unknown string '{}' for language '{}' in doctave.yaml.\n\
+                             Expected one of: {}",
+                            name,
+                            lang.code,
+                            languages::string_names().collect::<Vec<_>>().join(", ")
+                        )));
+                    }
+                }
+            }
+        }
+
+        // Validate that a pattern for the children of a directory in the
+        // navigation matches at least one page in it, collecting the ones
+        // that don't
+        fn validate_pattern(
+            pattern: &str,
+            kind: &str,
+            path: &Path,
+            project_root: &Path,
+            unmatched: &mut Vec<(PathBuf, String)>,
+        ) -> Result<()> {
+            let glob = Pattern::new(pattern).map_err(|e| {
+                Error::new(format!(
+                    "Invalid pattern '{}' for the {} of {} in navigation.\n{}",
+                    pattern,
+                    kind,
+                    path.display(),
+                    e.msg
+                ))
+            })?;
+
+            let dir = project_root.join(path);
+            let matches = WalkDir::new(&dir)
+                .into_iter()
+                .filter_map(|e| e.ok())
+                .filter(|e| {
+                    e.file_type().is_file() && e.path().extension() == Some(OsStr::new("md"))
+                })
+                .any(|e| {
+                    e.path()
+                        .strip_prefix(&dir)
+                        .map(|relative| glob_matches(&glob, relative))
+                        .unwrap_or(false)
+                });
+
+            if !matches {
+                unmatched.push((
+                    path.to_path_buf(),
+                    format!(
+                        "{}: the pattern '{}' for the {} does not match any pages",
+                        path.display(),
+                        pattern,
+                        kind
+                    ),
+                ));
+            }
+
+            Ok(())
+        }
+
+        // Validate navigation entries recursively, collecting the paths and
+        // patterns that don't match any files
         fn validate_level(
             nav: &Navigation,
             config: &DoctaveYaml,
             project_root: &Path,
+            unmatched: &mut Vec<(PathBuf, String)>,
         ) -> Result<()> {
-            if !project_root.join(&nav.path).exists() {
-                return Err(Error::new(format!(
-                    "Could not find file specified in navigation at {}",
-                    nav.path.display()
-                )));
-            }
-
-            if let Some(children) = &nav.children {
-                match children {
-                    NavChildren::WildCard(pattern) => {
+            match (&nav.path, &nav.url, &nav.label) {
+                (Some(path), None, None) if !project_root.join(path).exists() => {
+                    unmatched.push((path.clone(), path.display().to_string()));
+                }
+                (Some(path), None, None) => {
+                    if let Some(NavChildren::WildCard(pattern)) = &nav.children {
                         if pattern != "*" {
+                            validate_pattern(pattern, "children", path, project_root, unmatched)?;
+                        }
+                    }
+
+                    if let Some(exclude) = &nav.exclude {
+                        if !matches!(nav.children, Some(NavChildren::WildCard(_))) {
                             return Err(Error::new(format!(
-                                "Invalid pattern for navigation children. \
-                                 Found '{}', expected \"*\" or a list of child pages",
-                                pattern
+                                "Invalid exclude for {} in navigation.\n\
+                                 Only directories with a pattern for their children, \
+                                 like \"*\", can exclude pages.",
+                                path.display()
                             )));
                         }
-                    }
-                    NavChildren::List(navs) => {
-                        for nav in navs {
-                            validate_level(&nav, config, project_root)?;
+
+                        for pattern in exclude {
+                            validate_pattern(pattern, "exclude", path, project_root, unmatched)?;
                         }
                     }
                 }
+                (None, Some(url), None) => {
+                    if !is_url(url) {
+                        return Err(Error::new(format!(
+                            "Invalid url '{}' in navigation.\n\
+                             Links to other sites need a full URL, like https://example.com.",
+                            url
+                        )));
+                    }
+                    if nav.title.is_none() {
+                        return Err(Error::new(format!(
+                            "Missing title for the link to {} in navigation",
+                            url
+                        )));
+                    }
+                    if nav.children.is_some() || nav.exclude.is_some() {
+                        return Err(Error::new(format!(
+                            "The link to {} in navigation can't have children",
+                            url
+                        )));
+                    }
+                }
+                (None, None, Some(label)) => {
+                    if matches!(nav.children, Some(NavChildren::WildCard(_)))
+                        || nav.exclude.is_some()
+                    {
+                        return Err(Error::new(format!(
+                            "Invalid children for '{}' in navigation.\n\
+                             The children of a label have to be a list of entries.",
+                            label
+                        )));
+                    }
+                }
+                _ => {
+                    return Err(Error::new(
+                        "Invalid entry in navigation.\n\
+                         Each entry needs exactly one of path, url, or label.",
+                    ))
+                }
+            }
+
+            if let Some(NavChildren::List(navs)) = &nav.children {
+                for nav in navs {
+                    validate_level(&nav, config, project_root, unmatched)?;
+                }
             }
 
             Ok(())
         }
 
         if let Some(navs) = &self.navigation {
+            let mut unmatched = vec![];
+
             for nav in navs {
-                validate_level(nav, &self, &project_root)?;
+                validate_level(nav, &self, &project_root, &mut unmatched)?;
+            }
+
+            if !unmatched.is_empty() {
+                return Err(navigation_error(
+                    "Could not find the files for these entries in navigation:",
+                    &unmatched,
+                    locations,
+                ));
             }
         }
 
         Ok(())
     }
 }
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Default, Deserialize)]
 pub struct Navigation {
-    pub path: PathBuf,
+    pub path: Option<PathBuf>,
+    /// A link to another site
+    pub url: Option<String>,
+    /// A heading for the entries in `children`
+    pub label: Option<String>,
+    /// Replaces the title of the page the entry points to
+    pub title: Option<String>,
     pub children: Option<NavChildren>,
+    /// Patterns for children of a directory to leave out
+    pub exclude: Option<Vec<String>>,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -112,7 +419,142 @@ pub enum NavChildren {
     List(Vec<Navigation>),
 }
 
+#[derive(Debug, Clone, Deserialize)]
+struct VersionYaml {
+    name: String,
+    path: Option<PathBuf>,
+    git: Option<String>,
+}
+
+/// The `search` setting in doctave.yaml: `false` to leave search out of
+/// the site, or how to build the search index.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+enum SearchYaml {
+    Enabled(bool),
+    Settings(SearchSettingsYaml),
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+struct SearchSettingsYaml {
+    shards: Option<Sharding>,
+    boosts: Option<BoostsYaml>,
+    /// Patterns for pages to leave out, relative to the docs directory
+    #[serde(default)]
+    exclude: Vec<String>,
+    #[serde(flatten)]
+    language: SearchLanguageYaml,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+struct BoostsYaml {
+    title: Option<f64>,
+    body: Option<f64>,
+}
+
+/// Search settings that can differ between languages.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+struct SearchLanguageYaml {
+    stemming: Option<bool>,
+    stop_words: Option<Vec<String>>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct LanguageYaml {
+    code: String,
+    name: Option<String>,
+    #[serde(default)]
+    strings: BTreeMap<String, String>,
+    #[serde(default)]
+    search: SearchLanguageYaml,
+}
+
+/// A language the documentation is written in, built into its own
+/// directory in the site.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Language {
+    pub code: String,
+    /// Shown in the language switcher
+    pub name: String,
+    /// Translations for the text in the built-in templates
+    pub strings: BTreeMap<String, String>,
+    /// Search settings for this language, over the ones for the whole site
+    search: SearchLanguageYaml,
+}
+
+impl From<LanguageYaml> for Language {
+    fn from(other: LanguageYaml) -> Self {
+        let LanguageYaml {
+            code,
+            name,
+            strings: overrides,
+            search,
+        } = other;
+
+        let mut strings = languages::default_strings(&code);
+        strings.extend(overrides);
+
+        Language {
+            name: name
+                .or_else(|| languages::default_name(&code).map(|n| n.to_string()))
+                .unwrap_or_else(|| code.clone()),
+            code,
+            strings,
+            search,
+        }
+    }
+}
+
+/// The name under which the newest version is also available.
+pub static LATEST_VERSION_ALIAS: &str = "latest";
+
+/// A file in the output directory that marks it as built by Doctave, so
+/// that it can be cleared on the next build.
+pub static SITE_MARKER: &str = ".doctave";
+
+/// One version of the documentation, built into its own directory in the
+/// site.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Version {
+    pub name: String,
+    pub source: VersionSource,
+}
+
+impl Version {
+    /// The URI path the version is served under.
+    pub fn uri_prefix(&self) -> String {
+        format!("/{}", self.name)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum VersionSource {
+    /// A directory of Markdown files in the project
+    Dir(PathBuf),
+    /// A directory of Markdown files, as it was at a git tag, branch or
+    /// commit. The path is relative to the project root.
+    Git(String, PathBuf),
+}
+
+impl Version {
+    /// Versions without a path read the project's docs directory.
+    fn from_yaml(other: VersionYaml, docs_path: &Path) -> Self {
+        let path = other.path.unwrap_or_else(|| docs_path.to_path_buf());
+
+        Version {
+            name: other.name,
+            source: match other.git {
+                Some(reference) => VersionSource::Git(reference, path),
+                None => VersionSource::Dir(path),
+            },
+        }
+    }
+}
+
 static DEFAULT_THEME_COLOR: &str = "#445282";
+static DEFAULT_DOCS_DIR: &str = "docs";
+static DEFAULT_OUT_DIR: &str = "site";
+static DEFAULT_ROBOTS: &str = "User-agent: *\nAllow: /\n";
 
 #[derive(Debug, Clone)]
 struct Colors {
@@ -140,56 +582,171 @@ impl Default for Colors {
     }
 }
 
+/// An entry in the navigation, and the title to show for it if it should
+/// not be the title of the page.
 #[derive(Debug, Clone, PartialEq)]
 pub enum NavRule {
-    File(PathBuf),
-    Dir(PathBuf, Option<DirIncludeRule>),
+    File(PathBuf, Option<String>),
+    Dir(PathBuf, Option<DirIncludeRule>, Option<String>),
+    /// A link to another site, and its title
+    External(String, String),
+    /// A heading that groups the entries under it, without linking anywhere
+    Label(String, Vec<NavRule>),
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum DirIncludeRule {
     WildCard,
     Explicit(Vec<NavRule>),
+    /// The children whose paths, relative to the directory, match the
+    /// pattern, or all of them if there is none. Children that match any of
+    /// the `exclude` patterns are left out, along with everything under them.
+    Matching {
+        pattern: Option<Pattern>,
+        exclude: Vec<Pattern>,
+    },
+}
+
+/// Where the paths in the navigation are in doctave.yaml, as a line and a
+/// column.
+type NavigationLocations = HashMap<PathBuf, (usize, usize)>;
+
+/// Finds where each path in the navigation is in doctave.yaml. If the same
+/// path is in the navigation more than once, the first one is used.
+fn navigation_locations(yaml: &str) -> NavigationLocations {
+    let mut locations = HashMap::new();
+    let mut in_navigation = false;
+
+    for (index, line) in yaml.lines().enumerate() {
+        // Top level keys start at the beginning of the line
+        if line.starts_with(|c: char| c.is_alphanumeric() || c == '_') {
+            in_navigation = line.starts_with("navigation:");
+        }
+
+        if !in_navigation {
+            continue;
+        }
+
+        let entry = line.trim_start().trim_start_matches("- ").trim_start();
+
+        if let Some(value) = entry.strip_prefix("path:") {
+            // The value is the end of the line, so its column follows from
+            // its length
+            let value = value.trim_start();
+            let column = line.len() - value.len() + 1;
+            let path = value.trim_end().trim_matches(|c| c == '"' || c == '\'');
+
+            locations
+                .entry(PathBuf::from(path))
+                .or_insert((index + 1, column));
+        }
+    }
+
+    locations
+}
+
+/// Lists entries in the navigation that something is wrong with, along
+/// with where they are in doctave.yaml. Each entry is the path it is found
+/// by, and what to show for it.
+fn navigation_error(
+    message: &str,
+    entries: &[(PathBuf, String)],
+    locations: &NavigationLocations,
+) -> Error {
+    let mut error = format!("{}\n", message);
+
+    for (path, entry) in entries {
+        match locations.get(path) {
+            Some((line, column)) => error.push_str(&format!(
+                "\n    doctave.yaml:{}:{}  {}",
+                line, column, entry
+            )),
+            None => error.push_str(&format!("\n    {}", entry)),
+        }
+    }
+
+    Error::new(error)
+}
+
+/// Matches a path against a pattern from the navigation. Wildcards don't
+/// match across directories, unless they are a `**`.
+pub fn glob_matches(pattern: &Pattern, path: &Path) -> bool {
+    pattern.matches_path_with(
+        path,
+        MatchOptions {
+            case_sensitive: true,
+            require_literal_separator: true,
+            require_literal_leading_dot: false,
+        },
+    )
 }
 
 impl NavRule {
-    fn from_yaml_input(input: Vec<Navigation>) -> Vec<NavRule> {
-        let mut rules = vec![];
-
-        for item in input {
-            if item.path.is_file() {
-                rules.push(NavRule::File(item.path.clone()));
-            } else if item.path.is_dir() {
-                let dir_rules = Self::build_directory_rules(&item);
-                rules.push(dir_rules);
-            }
+    /// Turns the navigation in doctave.yaml into rules. Paths are relative
+    /// to the project root.
+    fn from_yaml_input(input: Vec<Navigation>, project_root: &Path) -> Vec<NavRule> {
+        input
+            .iter()
+            .filter_map(|item| Self::from_yaml_item(item, project_root))
+            .collect()
+    }
+
+    fn from_yaml_item(item: &Navigation, project_root: &Path) -> Option<NavRule> {
+        if let Some(url) = &item.url {
+            let title = item.title.clone().unwrap_or_else(|| url.clone());
+
+            return Some(NavRule::External(url.clone(), title));
+        }
+
+        if let Some(label) = &item.label {
+            let children = match &item.children {
+                Some(NavChildren::List(items)) => {
+                    Self::from_yaml_input(items.clone(), project_root)
+                }
+                _ => vec![],
+            };
+
+            return Some(NavRule::Label(label.clone(), children));
         }
 
-        rules
+        let path = item.path.as_ref()?;
+
+        if project_root.join(path).is_file() {
+            Some(NavRule::File(path.clone(), item.title.clone()))
+        } else if project_root.join(path).is_dir() {
+            Some(Self::build_directory_rules(item, path, project_root))
+        } else {
+            None
+        }
     }
 
-    fn build_directory_rules(dir: &Navigation) -> NavRule {
-        match &dir.children {
-            None => NavRule::Dir(dir.path.clone(), None),
-            Some(NavChildren::WildCard(_)) => {
-                NavRule::Dir(dir.path.clone(), Some(DirIncludeRule::WildCard))
+    fn build_directory_rules(dir: &Navigation, path: &Path, project_root: &Path) -> NavRule {
+        // Patterns have been validated by the time rules are built
+        let exclude = dir
+            .exclude
+            .iter()
+            .flatten()
+            .filter_map(|p| Pattern::new(p).ok())
+            .collect::<Vec<_>>();
+
+        let include_rule = match &dir.children {
+            None => None,
+            Some(NavChildren::WildCard(pattern)) if pattern == "*" && exclude.is_empty() => {
+                Some(DirIncludeRule::WildCard)
             }
-            Some(NavChildren::List(paths)) => NavRule::Dir(
-                dir.path.clone(),
-                Some(DirIncludeRule::Explicit(
-                    paths
-                        .iter()
-                        .map(|p| {
-                            if p.path.is_file() {
-                                NavRule::File(p.path.clone())
-                            } else {
-                                Self::build_directory_rules(p)
-                            }
-                        })
-                        .collect::<Vec<_>>(),
-                )),
-            ),
-        }
+            Some(NavChildren::WildCard(pattern)) => Some(DirIncludeRule::Matching {
+                pattern: match pattern.as_str() {
+                    "*" => None,
+                    pattern => Pattern::new(pattern).ok(),
+                },
+                exclude,
+            }),
+            Some(NavChildren::List(items)) => Some(DirIncludeRule::Explicit(
+                Self::from_yaml_input(items.clone(), project_root),
+            )),
+        };
+
+        NavRule::Dir(path.to_path_buf(), include_rule, dir.title.clone())
     }
 }
 
@@ -199,12 +756,34 @@ pub struct Config {
     project_root: PathBuf,
     out_dir: PathBuf,
     docs_dir: PathBuf,
+    /// The docs directory set in doctave.yaml, relative to the project root.
+    /// Navigation paths start with it, even when building a version of the
+    /// docs that lives elsewhere.
+    docs_path: PathBuf,
+    templates_dir: PathBuf,
     title: String,
     colors: Colors,
     logo: Option<String>,
+    og_image: Option<String>,
+    toc: Toc,
+    search_enabled: bool,
+    search: SearchSettingsYaml,
     navigation: Option<Vec<NavRule>>,
+    navigation_locations: NavigationLocations,
     port: u32,
     build_mode: BuildMode,
+    versions: Vec<Version>,
+    version: Option<Version>,
+    languages: Vec<Language>,
+    language: Option<Language>,
+    /// The docs directory of all languages, if this config is for a
+    /// language with its own directory inside it
+    shared_docs_dir: Option<PathBuf>,
+    site_uri_prefix: String,
+    /// The origin the site is hosted at, like `https://docs.example.com`
+    url: Option<String>,
+    robots: Option<String>,
+    relative_links: bool,
 }
 
 impl Config {
@@ -222,22 +801,63 @@ impl Config {
         let doctave_yaml: DoctaveYaml = serde_yaml::from_str(yaml)
             .map_err(|e| Error::yaml(e, "Could not parse doctave.yaml"))?;
 
-        doctave_yaml.validate(project_root)?;
+        let navigation_locations = navigation_locations(yaml);
+
+        doctave_yaml.validate(project_root, &navigation_locations)?;
+
+        let docs_path = doctave_yaml.docs_path();
 
         let config = Config {
             color: true,
             project_root: project_root.to_path_buf(),
-            out_dir: project_root.join("site"),
-            docs_dir: project_root.join("docs"),
+            out_dir: project_root.join(DEFAULT_OUT_DIR),
+            docs_dir: project_root.join(&docs_path),
+            templates_dir: doctave_yaml
+                .templates
+                .map(|p| project_root.join(p))
+                .unwrap_or_else(|| project_root.join(&docs_path).join("_templates")),
             title: doctave_yaml.title,
             colors: doctave_yaml
                 .colors
                 .map(|c| c.into())
                 .unwrap_or(Colors::default()),
             logo: doctave_yaml.logo.map(|p| Link::path_to_uri_with_extension(&p)),
-            navigation: doctave_yaml.navigation.map(|n| NavRule::from_yaml_input(n)),
+            og_image: doctave_yaml.og_image,
+            toc: Toc::default().with(doctave_yaml.toc.as_ref()),
+            search_enabled: !matches!(doctave_yaml.search, Some(SearchYaml::Enabled(false))),
+            search: match doctave_yaml.search {
+                Some(SearchYaml::Settings(settings)) => settings,
+                _ => SearchSettingsYaml::default(),
+            },
+            navigation: doctave_yaml
+                .navigation
+                .map(|n| NavRule::from_yaml_input(n, project_root)),
+            navigation_locations,
             port: doctave_yaml.port.unwrap_or_else(|| 4001),
             build_mode: BuildMode::Dev,
+            versions: doctave_yaml
+                .versions
+                .map(|v| {
+                    v.into_iter()
+                        .map(|v| Version::from_yaml(v, &docs_path))
+                        .collect()
+                })
+                .unwrap_or_default(),
+            version: None,
+            languages: doctave_yaml
+                .languages
+                .map(|l| l.into_iter().map(|l| l.into()).collect())
+                .unwrap_or_default(),
+            language: None,
+            shared_docs_dir: None,
+            site_uri_prefix: match &doctave_yaml.base_path {
+                Some(base_path) => normalize_base_path(base_path)?,
+                None => String::new(),
+            },
+            url: doctave_yaml.url.as_deref().map(normalize_url).transpose()?,
+            robots: doctave_yaml.robots,
+            docs_path,
+            relative_links: false,
         };
 
         Ok(config)
@@ -258,16 +878,139 @@ impl Config {
         &self.out_dir
     }
 
+    /// Builds the HTML into the given directory instead of `site` in the
+    /// project root.
+    ///
+    /// The directory is cleared before every build, so it can't be one that
+    /// the project, its docs or its templates are in, or one that already
+    /// has files Doctave didn't build.
+    pub fn set_out_dir(&mut self, out_dir: PathBuf) -> Result<()> {
+        let dirs = [
+            ("the project", &self.project_root),
+            ("the docs directory", &self.docs_dir),
+            ("the templates directory", &self.templates_dir),
+        ];
+
+        for (name, dir) in dirs.iter() {
+            if is_inside(dir, &out_dir) {
+                return Err(Error::new(format!(
+                    "Invalid output directory {}.\n\
+                     It contains {}, which would be deleted when building the site.",
+                    out_dir.display(),
+                    name
+                )));
+            }
+        }
+
+        let is_empty = fs::read_dir(&out_dir)
+            .map(|mut entries| entries.next().is_none())
+            .unwrap_or(true);
+        if !is_empty && !out_dir.join(SITE_MARKER).exists() {
+            return Err(Error::new(format!(
+                "Invalid output directory {}.\n\
+                 It has files that Doctave didn't build, which would be deleted when building \
+                 the site.",
+                out_dir.display()
+            )));
+        }
+
+        self.out_dir = out_dir;
+
+        Ok(())
+    }
+
     /// The directory that contains all the Markdown documentation
     pub fn docs_dir(&self) -> &Path {
         &self.docs_dir
     }
 
+    /// The docs directory as set in doctave.yaml, relative to the project
+    /// root. Paths in the navigation start with this.
+    pub fn docs_path(&self) -> &Path {
+        &self.docs_path
+    }
+
+    /// The directory that contains any templates overriding the built-in ones
+    pub fn templates_dir(&self) -> &Path {
+        &self.templates_dir
+    }
+
+    /// Which headings pages list under "On this page", unless they set
+    /// their own
+    pub fn toc(&self) -> Toc {
+        self.toc
+    }
+
+    /// How to build the search index for the language being built.
+    ///
+    /// Settings for the language win over the ones for the whole site.
+    /// Without either, languages Doctave knows get their own stop words,
+    /// and English is stemmed.
+    pub fn search(&self) -> SearchSettings {
+        let code = self.language.as_ref().map_or("en", |l| l.code.as_str());
+        let language = self.language.as_ref().map(|l| &l.search);
+        let boosts = self.search.boosts.clone().unwrap_or_default();
+
+        SearchSettings {
+            enabled: self.search_enabled,
+            sharding: self.search.shards,
+            boosts: Boosts {
+                title: boosts.title.unwrap_or(Boosts::default().title),
+                body: boosts.body.unwrap_or(Boosts::default().body),
+            },
+            // Patterns have been validated when the config was loaded
+            exclude: self
+                .search
+                .exclude
+                .iter()
+                .filter_map(|p| Pattern::new(p).ok())
+                .collect(),
+            stemming: language
+                .and_then(|l| l.stemming)
+                .or(self.search.language.stemming)
+                .unwrap_or_else(|| languages::has_stemmer(code)),
+            stop_words: language
+                .and_then(|l| l.stop_words.clone())
+                .or_else(|| self.search.language.stop_words.clone())
+                .unwrap_or_else(|| languages::default_stop_words(code)),
+        }
+    }
+
     /// Rules that set the site navigation structure
     pub fn navigation(&self) -> Option<&[NavRule]> {
         self.navigation.as_deref()
     }
 
+    /// An error listing entries in the navigation, with where they are in
+    /// doctave.yaml.
+    pub fn navigation_error(&self, message: &str, paths: &[PathBuf]) -> Error {
+        let entries = paths
+            .iter()
+            .map(|path| (path.clone(), path.display().to_string()))
+            .collect::<Vec<_>>();
+
+        navigation_error(message, &entries, &self.navigation_locations)
+    }
+
+    /// Whether this config builds the pages the paths in the navigation
+    /// point to. Other versions and languages of the docs may not have all
+    /// of them.
+    pub fn builds_navigation_pages(&self) -> bool {
+        let version = match &self.version {
+            None => true,
+            Some(version) => version.source == VersionSource::Dir(self.docs_path.clone()),
+        };
+
+        let language = match &self.language {
+            None => true,
+            Some(language) => {
+                !self.language_directories() || Some(language) == self.default_language()
+            }
+        };
+
+        version && language
+    }
+
     /// Port to serve the development server on
     pub fn port(&self) -> u32 {
         self.port
@@ -289,65 +1032,325 @@ impl Config {
         self.build_mode = mode;
     }
 
-    /// The main theme color. Other shades are computed based off of this
-    /// color.
-    ///
-    /// Must be a valid HEX color.
-    pub fn main_color(&self) -> Rgb {
-        let color = &self.colors.main;
+    /// Whether links in the site are relative to each page, so it can be
+    /// opened without a server.
+    pub fn relative_links(&self) -> bool {
+        self.relative_links
+    }
 
-        // This was already validated
-        Rgb::from_hex_str(color).unwrap()
+    pub fn enable_relative_links(&mut self) {
+        self.relative_links = true;
     }
 
-    /// A lighter version of the main color, meant to be used in _dark_ mode.
-    pub fn main_color_dark(&self) -> Rgb {
-        let mut color = self.main_color();
-        color.lighten(25.0);
-        color
+    /// All versions of the documentation, starting with the latest one.
+    /// Empty if the project is not versioned.
+    pub fn versions(&self) -> &[Version] {
+        &self.versions
     }
 
-    /// URI path to a logo that will show up at the top left next to the title
-    pub fn logo(&self) -> Option<&str> {
-        self.logo.as_deref()
+    pub fn latest_version(&self) -> Option<&Version> {
+        self.versions.first()
+    }
+
+    /// The version being built, if this config was made for one with
+    /// `for_version`.
+    pub fn version(&self) -> Option<&Version> {
+        self.version.as_ref()
     }
-}
 
-pub fn project_root() -> Option<PathBuf> {
-    let mut current_dir = std::env::current_dir().expect("Unable to determine current directory");
+    /// All languages of the documentation, starting with the default one.
+    /// Empty if the project is not translated.
+    pub fn languages(&self) -> &[Language] {
+        &self.languages
+    }
 
-    loop {
-        // If we are in the root dir, just return it
-        if current_dir.join("doctave.yaml").exists() || current_dir.join("doctave.yml").exists() {
-            return Some(current_dir);
+    pub fn default_language(&self) -> Option<&Language> {
+        self.languages.first()
+    }
+
+    /// The language being built, if this config was made for one with
+    /// `for_language`.
+    pub fn language(&self) -> Option<&Language> {
+        self.language.as_ref()
+    }
+
+    /// Whether each language has its own directory inside the docs
+    /// directory, like `docs/de`. Otherwise translated pages sit next to
+    /// the originals, with the language code in their name, like
+    /// `tutorial.de.md`.
+    pub fn language_directories(&self) -> bool {
+        match (&self.shared_docs_dir, self.default_language()) {
+            (Some(_), _) => true,
+            (None, Some(lang)) if self.language.is_none() => {
+                self.docs_dir.join(&lang.code).is_dir()
+            }
+            _ => false,
         }
+    }
 
-        if let Some(parent) = current_dir.parent() {
-            current_dir = parent.to_path_buf();
-        } else {
-            return None;
+    /// The text to use in the built-in templates.
+    pub fn strings(&self) -> BTreeMap<String, String> {
+        match &self.language {
+            Some(lang) => lang.strings.clone(),
+            None => languages::default_strings("en"),
         }
     }
-}
 
-#[cfg(test)]
-mod test {
-    use super::*;
+    /// The directories files are included into the site from, as they are.
+    /// Files in later directories replace ones in earlier directories.
+    pub fn include_dirs(&self) -> Vec<PathBuf> {
+        let mut dirs = vec![];
 
-    extern crate indoc;
+        if let Some(shared) = &self.shared_docs_dir {
+            dirs.push(shared.join("_include"));
+        }
+        dirs.push(self.docs_dir.join("_include"));
 
-    #[test]
-    fn validate_colors() {
-        let yaml = indoc! {"
-            ---
-            title: The Title
-            colors:
-               main: not-a-color
-        "};
+        dirs
+    }
 
-        let error = Config::from_yaml_str(Path::new(""), yaml).unwrap_err();
+    /// Prefix of the URI of every page and asset in the part of the site
+    /// this config builds.
+    pub fn uri_prefix(&self) -> String {
+        let mut prefix = self.site_uri_prefix.clone();
 
-        assert!(
+        if let Some(version) = &self.version {
+            prefix.push_str(&version.uri_prefix());
+        }
+        if let Some(lang) = &self.language {
+            prefix.push('/');
+            prefix.push_str(&lang.code);
+        }
+
+        prefix
+    }
+
+    /// Prefix of every URI in the whole site, across versions and languages.
+    /// Set with `base_path`, for sites that are not hosted at the root of
+    /// their domain.
+    pub fn site_uri_prefix(&self) -> &str {
+        &self.site_uri_prefix
+    }
+
+    /// The origin the site is hosted at, without a trailing slash. Needed
+    /// to generate a sitemap, which has to list full URLs.
+    pub fn url(&self) -> Option<&str> {
+        self.url.as_deref()
+    }
+
+    /// The rules for crawlers to put into robots.txt, before the link to
+    /// the sitemap.
+    pub fn robots(&self) -> &str {
+        self.robots.as_deref().unwrap_or(DEFAULT_ROBOTS)
+    }
+
+    /// Overrides the `base_path` from doctave.yaml.
+    pub fn set_base_path(&mut self, base_path: &str) -> Result<()> {
+        self.site_uri_prefix = normalize_base_path(base_path)?;
+
+        Ok(())
+    }
+
+    /// The prefix of the pages the root of the site points to. This is the
+    /// default language of the latest version, if there are any.
+    pub fn default_uri_prefix(&self) -> String {
+        let mut prefix = self.site_uri_prefix.clone();
+
+        if let Some(version) = self.latest_version() {
+            prefix.push_str(&version.uri_prefix());
+        }
+        if let Some(lang) = self.default_language() {
+            prefix.push('/');
+            prefix.push_str(&lang.code);
+        }
+
+        prefix
+    }
+
+    /// A copy of this config for building a single version of the
+    /// documentation, whose Markdown files are in `docs_dir`.
+    pub fn for_version(&self, version: &Version, docs_dir: PathBuf) -> Config {
+        Config {
+            out_dir: self.out_dir.join(&version.name),
+            docs_dir,
+            version: Some(version.clone()),
+            ..self.clone()
+        }
+    }
+
+    /// A copy of this config for building the documentation in a single
+    /// language.
+    pub fn for_language(&self, language: &Language) -> Config {
+        let (docs_dir, shared_docs_dir) = if self.language_directories() {
+            (
+                self.docs_dir.join(&language.code),
+                Some(self.docs_dir.clone()),
+            )
+        } else {
+            (self.docs_dir.clone(), None)
+        };
+
+        Config {
+            out_dir: self.out_dir.join(&language.code),
+            docs_dir,
+            shared_docs_dir,
+            language: Some(language.clone()),
+            ..self.clone()
+        }
+    }
+
+    /// The main theme color. Other shades are computed based off of this
+    /// color.
+    ///
+    /// Must be a valid HEX color.
+    pub fn main_color(&self) -> Rgb {
+        let color = &self.colors.main;
+
+        // This was already validated
+        Rgb::from_hex_str(color).unwrap()
+    }
+
+    /// A lighter version of the main color, meant to be used in _dark_ mode.
+    pub fn main_color_dark(&self) -> Rgb {
+        let mut color = self.main_color();
+        color.lighten(25.0);
+        color
+    }
+
+    /// URI path to a logo that will show up at the top left next to the title
+    pub fn logo(&self) -> Option<&str> {
+        self.logo.as_deref()
+    }
+
+    /// The image to show in link previews of every page that doesn't set
+    /// its own. Either a path in the _include directory, or a full URL.
+    pub fn og_image(&self) -> Option<&str> {
+        self.og_image.as_deref()
+    }
+}
+
+/// Turns a base path like `project/` into a URI prefix like `/project`.
+/// The root path turns into an empty prefix.
+fn normalize_base_path(base_path: &str) -> Result<String> {
+    if base_path.contains("://")
+        || base_path.contains(&['?', '#', '\\'][..])
+        || base_path.chars().any(char::is_whitespace)
+        || base_path.split('/').any(|segment| segment == "..")
+    {
+        return Err(Error::new(format!(
+            "Invalid base path '{}'.\n\
+             The base path should be the path the site is hosted under, like /project.",
+            base_path
+        )));
+    }
+
+    let trimmed = base_path.trim_matches('/');
+
+    if trimmed.is_empty() {
+        Ok(String::new())
+    } else {
+        Ok(format!("/{}", trimmed))
+    }
+}
+
+/// Checks that a url is just the origin of a site, like
+/// `https://docs.example.com/`, and strips the trailing slash.
+fn normalize_url(url: &str) -> Result<String> {
+    let trimmed = url.trim_end_matches('/');
+
+    let host = trimmed
+        .strip_prefix("https://")
+        .or_else(|| trimmed.strip_prefix("http://"));
+
+    match host {
+        Some(host)
+            if !host.is_empty()
+                && !host.contains(&['/', '?', '#', '\\'][..])
+                && !host.chars().any(char::is_whitespace) =>
+        {
+            Ok(trimmed.to_string())
+        }
+        _ => Err(Error::new(format!(
+            "Invalid url '{}'.\n\
+             The url should be the address the site is hosted at, like https://docs.example.com.\n\
+             Use base_path if the site is not hosted at the root of its domain.",
+            url
+        ))),
+    }
+}
+
+/// Whether a path is the given directory or inside it. Only looks at the
+/// paths themselves, so neither has to exist.
+pub fn is_inside(path: &Path, dir: &Path) -> bool {
+    normalize_path(path).starts_with(normalize_path(dir))
+}
+
+/// Resolves `.` and `..` in a path without touching the file system.
+fn normalize_path(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                if !normalized.pop() {
+                    normalized.push(component);
+                }
+            }
+            _ => normalized.push(component),
+        }
+    }
+
+    normalized
+}
+
+/// Whether a link points to another site, rather than a file in this one.
+pub fn is_url(link: &str) -> bool {
+    link.starts_with("http://") || link.starts_with("https://")
+}
+
+/// Whether the directory contains a doctave.yaml file.
+pub fn is_project_root(dir: &Path) -> bool {
+    DoctaveYaml::find(dir).is_some()
+}
+
+/// Finds the project root by looking for a doctave.yaml file in the given
+/// directory and its parents.
+pub fn project_root(start: &Path) -> Option<PathBuf> {
+    let mut current_dir = start.to_path_buf();
+
+    loop {
+        // If we are in the root dir, just return it
+        if is_project_root(&current_dir) {
+            return Some(current_dir);
+        }
+
+        if let Some(parent) = current_dir.parent() {
+            current_dir = parent.to_path_buf();
+        } else {
+            return None;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    extern crate indoc;
+
+    #[test]
+    fn validate_colors() {
+        let yaml = indoc! {"
+            ---
+            title: The Title
+            colors:
+               main: not-a-color
+        "};
+
+        let error = Config::from_yaml_str(Path::new(""), yaml).unwrap_err();
+
+        assert!(
             format!("{}", error)
                 .contains("Invalid HEX color provided for colors.main in doctave.yaml"),
             format!("Error message was: {}", error)
@@ -376,50 +1379,673 @@ mod test {
     }
 
     #[test]
-    fn validate_navigation_wildcard() {
+    fn validate_og_image() {
         let yaml = indoc! {"
             ---
             title: The Title
-            navigation:
-              - path: docs/tutorial.md
-                children: not-wildcard
+            og_image: i-do-not-exist.png
+        "};
+
+        let error = Config::from_yaml_str(Path::new(""), yaml).unwrap_err();
+
+        assert!(
+            format!("{}", error).contains("Could not find og_image specified in doctave.yaml"),
+            format!("Error message was: {}", error)
+        );
+
+        let yaml = indoc! {"
+            ---
+            title: The Title
+            og_image: https://example.com/preview.png
+        "};
+
+        let config = Config::from_yaml_str(Path::new(""), yaml).unwrap();
+
+        assert_eq!(config.og_image(), Some("https://example.com/preview.png"));
+    }
+
+    #[test]
+    fn validate_toc() {
+        let yaml = indoc! {"
+            ---
+            title: The Title
+            toc:
+              min_level: 3
+              max_level: 2
         "};
 
         let error = Config::from_yaml_str(Path::new(""), yaml).unwrap_err();
 
         assert!(
             format!("{}", error).contains(
-                "Invalid pattern for navigation children. \
-                Found 'not-wildcard', expected \"*\" or a list of child pages"
+                "Invalid toc in doctave.yaml.\n\
+                 toc.min_level can't be larger than toc.max_level. Found 3 and 2"
             ),
             format!("Error message was: {}", error)
         );
+
+        let yaml = indoc! {"
+            ---
+            title: The Title
+            toc: false
+        "};
+
+        let config = Config::from_yaml_str(Path::new(""), yaml).unwrap();
+
+        assert!(!config.toc().enabled);
+    }
+
+    #[test]
+    fn validate_templates() {
+        let yaml = indoc! {"
+            ---
+            title: The Title
+            templates: i-do-not-exist
+        "};
+
+        let error = Config::from_yaml_str(Path::new(""), yaml).unwrap_err();
+
+        assert!(
+            format!("{}", error)
+                .contains("Could not find templates directory specified in doctave.yaml"),
+            format!("Error message was: {}", error)
+        );
+    }
+
+    #[test]
+    fn validate_docs_dir() {
+        let yaml = indoc! {"
+            ---
+            title: The Title
+            docs_dir: i-do-not-exist
+        "};
+
+        let error = Config::from_yaml_str(Path::new(""), yaml).unwrap_err();
+
+        assert!(
+            format!("{}", error)
+                .contains("Could not find docs directory specified in doctave.yaml"),
+            format!("Error message was: {}", error)
+        );
+    }
+
+    #[test]
+    fn validate_base_path() {
+        let yaml = indoc! {"
+            ---
+            title: The Title
+            base_path: https://example.com/docs
+        "};
+
+        let error = Config::from_yaml_str(Path::new(""), yaml).unwrap_err();
+
+        assert!(
+            format!("{}", error).contains("Invalid base path 'https://example.com/docs'"),
+            format!("Error message was: {}", error)
+        );
+    }
+
+    #[test]
+    fn base_path() {
+        for (base_path, prefix) in &[("/", ""), ("project", "/project"), ("/a/b/", "/a/b")] {
+            let yaml = format!("---\ntitle: The Title\nbase_path: {}\n", base_path);
+            let config = Config::from_yaml_str(Path::new(""), &yaml).unwrap();
+
+            assert_eq!(config.site_uri_prefix(), *prefix);
+            assert_eq!(config.uri_prefix(), *prefix);
+        }
+
+        let mut config = Config::from_yaml_str(Path::new(""), "---\ntitle: The Title\n").unwrap();
+        config.set_base_path("/override/").unwrap();
+
+        assert_eq!(config.site_uri_prefix(), "/override");
+    }
+
+    #[test]
+    fn validate_url() {
+        for url in &[
+            "docs.example.com",
+            "https://",
+            "https://example.com/docs",
+            "ftp://example.com",
+        ] {
+            let yaml = format!("---\ntitle: The Title\nurl: {}\n", url);
+            let error = Config::from_yaml_str(Path::new(""), &yaml).unwrap_err();
+
+            assert!(
+                format!("{}", error).contains(&format!("Invalid url '{}'", url)),
+                format!("Error message was: {}", error)
+            );
+        }
+    }
+
+    #[test]
+    fn url() {
+        let yaml = "---\ntitle: The Title\nurl: https://docs.example.com/\n";
+        let config = Config::from_yaml_str(Path::new(""), yaml).unwrap();
+
+        assert_eq!(config.url(), Some("https://docs.example.com"));
+
+        let yaml = "---\ntitle: The Title\nurl: http://localhost:8080\n";
+        let config = Config::from_yaml_str(Path::new(""), yaml).unwrap();
+
+        assert_eq!(config.url(), Some("http://localhost:8080"));
+    }
+
+    #[test]
+    fn out_dir() {
+        let yaml = "---\ntitle: The Title\n";
+        let root = Path::new("project");
+        let mut config = Config::from_yaml_str(root, yaml).unwrap();
+
+        config.set_out_dir(root.join("public")).unwrap();
+        assert_eq!(config.out_dir(), root.join("public"));
+
+        config
+            .set_out_dir(root.join("docs").join("..").join("site"))
+            .unwrap();
+
+        for dir in &[
+            root.to_path_buf(),
+            root.join("."),
+            root.join("docs"),
+            root.join("docs").join("_templates"),
+            root.join("site").join(".."),
+            PathBuf::from(""),
+        ] {
+            let error = config.set_out_dir(dir.clone()).unwrap_err();
+
+            assert!(
+                format!("{}", error).contains("Invalid output directory"),
+                format!("Error message was: {}", error)
+            );
+        }
+    }
+
+    #[test]
+    fn validate_version_names() {
+        let yaml = indoc! {"
+            ---
+            title: The Title
+            versions:
+              - name: latest
+        "};
+
+        let error = Config::from_yaml_str(Path::new(""), yaml).unwrap_err();
+
+        assert!(
+            format!("{}", error).contains("Invalid version name 'latest' in doctave.yaml"),
+            format!("Error message was: {}", error)
+        );
+
+        let yaml = indoc! {"
+            ---
+            title: The Title
+            versions:
+              - name: v1
+                git: v1.0.0
+              - name: v1
+                git: v1.0.1
+        "};
+
+        let error = Config::from_yaml_str(Path::new(""), yaml).unwrap_err();
+
+        assert!(
+            format!("{}", error).contains("Version 'v1' is listed more than once in doctave.yaml"),
+            format!("Error message was: {}", error)
+        );
+    }
+
+    #[test]
+    fn validate_version_directory() {
+        let yaml = indoc! {"
+            ---
+            title: The Title
+            versions:
+              - name: v1
+                path: i-do-not-exist
+        "};
+
+        let error = Config::from_yaml_str(Path::new(""), yaml).unwrap_err();
+
+        assert!(
+            format!("{}", error)
+                .contains("Could not find directory for version 'v1' specified in doctave.yaml"),
+            format!("Error message was: {}", error)
+        );
+    }
+
+    #[test]
+    fn versions() {
+        let yaml = indoc! {"
+            ---
+            title: The Title
+            versions:
+              - name: v2
+              - name: v1
+                git: v1.0.0
+        "};
+
+        let config = Config::from_yaml_str(Path::new("project"), yaml).unwrap();
+
+        assert_eq!(
+            config.versions(),
+            &[
+                Version {
+                    name: String::from("v2"),
+                    source: VersionSource::Dir(PathBuf::from("docs")),
+                },
+                Version {
+                    name: String::from("v1"),
+                    source: VersionSource::Git(String::from("v1.0.0"), PathBuf::from("docs")),
+                },
+            ]
+        );
+
+        let v1 = config.for_version(&config.versions()[1], PathBuf::from("checkout"));
+
+        assert_eq!(v1.docs_dir(), Path::new("checkout"));
+        assert_eq!(v1.out_dir(), Path::new("project").join("site").join("v1"));
+        assert_eq!(v1.uri_prefix(), "/v1");
+    }
+
+    #[test]
+    fn validate_language_codes() {
+        let yaml = indoc! {"
+            ---
+            title: The Title
+            languages:
+              - code: en/us
+        "};
+
+        let error = Config::from_yaml_str(Path::new(""), yaml).unwrap_err();
+
+        assert!(
+            format!("{}", error).contains("Invalid language code 'en/us' in doctave.yaml"),
+            format!("Error message was: {}", error)
+        );
+
+        let yaml = indoc! {"
+            ---
+            title: The Title
+            languages:
+              - code: en
+              - code: en
+        "};
+
+        let error = Config::from_yaml_str(Path::new(""), yaml).unwrap_err();
+
+        assert!(
+            format!("{}", error).contains("Language 'en' is listed more than once in doctave.yaml"),
+            format!("Error message was: {}", error)
+        );
+    }
+
+    #[test]
+    fn validate_language_strings() {
+        let yaml = indoc! {"
+            ---
+            title: The Title
+            languages:
+              - code: de
+                strings:
+                  not_a_string: Hallo
+        "};
+
+        let error = Config::from_yaml_str(Path::new(""), yaml).unwrap_err();
+
+        assert!(
+            format!("{}", error)
+                .contains("Unknown string 'not_a_string' for language 'de' in doctave.yaml"),
+            format!("Error message was: {}", error)
+        );
+    }
+
+    #[test]
+    fn languages() {
+        let yaml = indoc! {"
+            ---
+            title: The Title
+            languages:
+              - code: en
+              - code: de
+                strings:
+                  search: Finden...
+              - code: fi
+        "};
+
+        let config = Config::from_yaml_str(Path::new("project"), yaml).unwrap();
+        let languages = config.languages();
+
+        assert_eq!(config.default_language(), Some(&languages[0]));
+        assert_eq!(languages[0].name, "English");
+        assert_eq!(languages[1].strings["search"], "Finden...");
+        assert_eq!(languages[1].strings["on_this_page"], "Auf dieser Seite");
+        assert_eq!(languages[2].name, "fi");
+
+        let de = config.for_language(&languages[1]);
+
+        assert_eq!(de.docs_dir(), Path::new("project").join("docs"));
+        assert_eq!(de.out_dir(), Path::new("project").join("site").join("de"));
+        assert_eq!(de.uri_prefix(), "/de");
+    }
+
+    #[test]
+    fn search_settings() {
+        let yaml = indoc! {"
+            ---
+            title: The Title
+        "};
+
+        let config = Config::from_yaml_str(Path::new(""), yaml).unwrap();
+        let search = config.search();
+
+        assert!(search.enabled);
+        assert_eq!(search.sharding, None);
+        assert_eq!(search.boosts, Boosts::default());
+        assert!(search.stemming);
+        assert!(search.stop_words.contains(&"the".to_string()));
+
+        let yaml = indoc! {"
+            ---
+            title: The Title
+            search:
+              shards: prefix
+              stemming: false
+              stop_words: [doctave]
+            languages:
+              - code: en
+                search:
+                  stemming: true
+              - code: de
+              - code: fi
+                search:
+                  stop_words: [ja]
+        "};
+
+        let config = Config::from_yaml_str(Path::new(""), yaml).unwrap();
+        let languages = config.languages();
+
+        let en = config.for_language(&languages[0]).search();
+        assert_eq!(en.sharding, Some(Sharding::Prefix));
+        assert!(en.stemming);
+        assert_eq!(en.stop_words, vec!["doctave"]);
+
+        let de = config.for_language(&languages[1]).search();
+        assert!(!de.stemming);
+        assert_eq!(de.stop_words, vec!["doctave"]);
+
+        let fi = config.for_language(&languages[2]).search();
+        assert_eq!(fi.sharding, Some(Sharding::Prefix));
+        assert!(!fi.stemming);
+        assert_eq!(fi.stop_words, vec!["ja"]);
+    }
+
+    #[test]
+    fn validate_search() {
+        let cases = [
+            (
+                "exclude:\n    - \"[internal\"",
+                "Invalid pattern '[internal' in search.exclude in doctave.yaml.",
+            ),
+            (
+                "boosts:\n    body: -1",
+                "Invalid search.boosts.body in doctave.yaml.\n\
+                 Boosts have to be zero or a positive number. Found -1",
+            ),
+        ];
+
+        for (search, message) in &cases {
+            let yaml = format!("---\ntitle: The Title\nsearch:\n  {}\n", search);
+            let error = Config::from_yaml_str(Path::new(""), &yaml).unwrap_err();
+
+            assert!(
+                format!("{}", error).contains(message),
+                format!("Error message was: {}", error)
+            );
+        }
+
+        for yaml in &[
+            "search:\n  stemming: true\nlanguages:\n  - code: en\n  - code: fi\n",
+            "languages:\n  - code: fi\n    search:\n      stemming: true\n",
+        ] {
+            let yaml = format!("---\ntitle: The Title\n{}", yaml);
+            let error = Config::from_yaml_str(Path::new(""), &yaml).unwrap_err();
+
+            assert!(
+                format!("{}", error)
+                    .contains("Invalid search.stemming for language 'fi' in doctave.yaml."),
+                format!("Error message was: {}", error)
+            );
+        }
+
+        let yaml = indoc! {"
+            ---
+            title: The Title
+            search:
+              stemming: true
+            languages:
+              - code: en
+              - code: fi
+                search:
+                  stemming: false
+        "};
+
+        assert!(Config::from_yaml_str(Path::new(""), yaml).is_ok());
+
+        let yaml = indoc! {"
+            ---
+            title: The Title
+            search: false
+        "};
+
+        let config = Config::from_yaml_str(Path::new(""), yaml).unwrap();
+
+        assert!(!config.search().enabled);
+    }
+
+    #[test]
+    fn validate_navigation_patterns() {
+        let cases = [
+            (
+                "- path: docs/tutorial.md\n  children: not-wildcard",
+                "docs/tutorial.md: the pattern 'not-wildcard' for the children does not match \
+                 any pages",
+            ),
+            (
+                "- path: docs/features\n  children: \"custom-*.html\"",
+                "docs/features: the pattern 'custom-*.html' for the children does not match \
+                 any pages",
+            ),
+            (
+                "- path: docs/features\n  children: \"[custom\"",
+                "Invalid pattern '[custom' for the children of docs/features in navigation",
+            ),
+            (
+                "- path: docs/features\n  children: \"*\"\n  exclude:\n    - missing.md",
+                "docs/features: the pattern 'missing.md' for the exclude does not match any \
+                 pages",
+            ),
+            (
+                "- path: docs/features\n  exclude:\n    - markdown.md",
+                "Invalid exclude for docs/features in navigation",
+            ),
+        ];
+
+        for (navigation, message) in &cases {
+            let yaml = format!("---\ntitle: The Title\nnavigation:\n{}\n", navigation);
+            let error = Config::from_yaml_str(Path::new(""), &yaml).unwrap_err();
+
+            assert!(
+                format!("{}", error).contains(message),
+                format!("Error message was: {}", error)
+            );
+        }
+
+        let yaml = indoc! {"
+            ---
+            title: The Title
+            navigation:
+              - path: docs/features
+                children: \"custom-*.md\"
+                exclude:
+                  - custom-head-tag.md
+        "};
+
+        assert!(Config::from_yaml_str(Path::new(""), yaml).is_ok());
+    }
+
+    #[test]
+    fn validate_navigation_paths() {
+        let yaml = indoc! {"
+            ---
+            title: The Title
+            navigation:
+              - path: docs/missing.md
+              - path: docs/features
+                children:
+                  - path: \"docs/features/missing.md\"
+                  - path: docs/features/markdown.md
+        "};
+
+        let error = Config::from_yaml_str(Path::new(""), yaml).unwrap_err();
+
+        assert_eq!(
+            format!("{}", error),
+            "Could not find the files for these entries in navigation:\n\n    \
+             doctave.yaml:4:11  docs/missing.md\n    \
+             doctave.yaml:7:15  docs/features/missing.md"
+        );
+    }
+
+    #[test]
+    fn validate_navigation_paths_and_patterns() {
+        let yaml = indoc! {"
+            ---
+            title: The Title
+            navigation:
+              - path: docs/features
+                children: \"missing-*.md\"
+              - path: docs
+                children: \"*\"
+                exclude:
+                  - missing.md
+              - path: docs/missing.md
+        "};
+
+        let error = Config::from_yaml_str(Path::new(""), yaml).unwrap_err();
+
+        assert_eq!(
+            format!("{}", error),
+            "Could not find the files for these entries in navigation:\n\n    \
+             doctave.yaml:4:11  docs/features: the pattern 'missing-*.md' for the children \
+             does not match any pages\n    \
+             doctave.yaml:6:11  docs: the pattern 'missing.md' for the exclude does not match \
+             any pages\n    \
+             doctave.yaml:10:11  docs/missing.md"
+        );
+    }
+
+    #[test]
+    fn navigation_paths_are_found_in_yaml() {
+        let yaml = indoc! {"
+            ---
+            title: The Title
+            versions:
+              - name: v1
+                path: docs/tutorial.md
+            navigation:
+              - path: docs/README.md
+              - label: Guides
+                children:
+                  -   path: 'docs/tutorial.md'
+        "};
+
+        let locations = navigation_locations(yaml);
+
+        assert_eq!(locations.len(), 2);
+        assert_eq!(locations[Path::new("docs/README.md")], (7, 11));
+        assert_eq!(locations[Path::new("docs/tutorial.md")], (10, 17));
+    }
+
+    #[test]
+    fn validate_navigation_entries() {
+        let cases = [
+            (
+                "- path: docs/README.md\n  url: https://example.com",
+                "Each entry needs exactly one of path, url, or label",
+            ),
+            (
+                "- title: Nothing",
+                "Each entry needs exactly one of path, url, or label",
+            ),
+            (
+                "- url: example.com\n  title: Example",
+                "Invalid url 'example.com' in navigation",
+            ),
+            (
+                "- url: https://example.com",
+                "Missing title for the link to https://example.com in navigation",
+            ),
+            (
+                "- label: Guides\n  children: \"*\"",
+                "Invalid children for 'Guides' in navigation",
+            ),
+        ];
+
+        for (navigation, message) in &cases {
+            let yaml = format!("---\ntitle: The Title\nnavigation:\n{}\n", navigation);
+            let error = Config::from_yaml_str(Path::new(""), &yaml).unwrap_err();
+
+            assert!(
+                format!("{}", error).contains(message),
+                format!("Error message was: {}", error)
+            );
+        }
     }
 
     #[test]
     fn convert_navigation_input_to_rules_file() {
         let input = vec![Navigation {
-            path: PathBuf::from("docs").join("README.md"),
-            children: None,
+            path: Some(PathBuf::from("docs").join("README.md")),
+            ..Navigation::default()
         }];
 
         assert_eq!(
-            NavRule::from_yaml_input(input),
-            vec![NavRule::File(PathBuf::from("docs").join("README.md"))]
+            NavRule::from_yaml_input(input, Path::new("")),
+            vec![NavRule::File(PathBuf::from("docs").join("README.md"), None)]
+        );
+    }
+
+    #[test]
+    fn convert_navigation_input_to_rules_file_with_title() {
+        let input = vec![Navigation {
+            path: Some(PathBuf::from("docs").join("README.md")),
+            title: Some(String::from("Home")),
+            ..Navigation::default()
+        }];
+
+        assert_eq!(
+            NavRule::from_yaml_input(input, Path::new("")),
+            vec![NavRule::File(
+                PathBuf::from("docs").join("README.md"),
+                Some(String::from("Home"))
+            )]
         );
     }
 
     #[test]
     fn convert_navigation_input_to_rules_directory_no_children() {
         let input = vec![Navigation {
-            path: PathBuf::from("docs").join("features"), // TODO: Make not rely on our docs
-            children: None,
+            path: Some(PathBuf::from("docs").join("features")), // TODO: Make not rely on our docs
+            ..Navigation::default()
         }];
 
         assert_eq!(
-            NavRule::from_yaml_input(input),
+            NavRule::from_yaml_input(input, Path::new("")),
             vec![NavRule::Dir(
                 PathBuf::from("docs").join("features"),
+                None,
                 None
             )]
         );
@@ -428,15 +2054,17 @@ mod test {
     #[test]
     fn convert_navigation_input_to_rules_directory_wildcard_children() {
         let input = vec![Navigation {
-            path: PathBuf::from("docs").join("features"), // TODO: Make not rely on our docs
+            path: Some(PathBuf::from("docs").join("features")), // TODO: Make not rely on our docs
             children: Some(NavChildren::WildCard(String::from("*"))),
+            ..Navigation::default()
         }];
 
         assert_eq!(
-            NavRule::from_yaml_input(input),
+            NavRule::from_yaml_input(input, Path::new("")),
             vec![NavRule::Dir(
                 PathBuf::from("docs").join("features"),
-                Some(DirIncludeRule::WildCard)
+                Some(DirIncludeRule::WildCard),
+                None
             )]
         );
     }
@@ -444,25 +2072,99 @@ mod test {
     #[test]
     fn convert_navigation_input_to_rules_directory_explicit_children() {
         let input = vec![Navigation {
-            path: PathBuf::from("docs").join("features"), // TODO: Make not rely on our docs
+            path: Some(PathBuf::from("docs").join("features")), // TODO: Make not rely on our docs
             children: Some(NavChildren::List(vec![Navigation {
-                path: PathBuf::from("docs")
-                    .join("features")
-                    .join("markdown.md"),
-                children: None,
+                path: Some(PathBuf::from("docs").join("features").join("markdown.md")),
+                ..Navigation::default()
             }])),
+            ..Navigation::default()
         }];
 
         assert_eq!(
-            NavRule::from_yaml_input(input),
+            NavRule::from_yaml_input(input, Path::new("")),
             vec![NavRule::Dir(
                 PathBuf::from("docs").join("features"),
                 Some(DirIncludeRule::Explicit(vec![NavRule::File(
-                    PathBuf::from("docs")
-                        .join("features")
-                        .join("markdown.md")
-                )]))
+                    PathBuf::from("docs").join("features").join("markdown.md"),
+                    None
+                )])),
+                None
             )]
         );
     }
+
+    #[test]
+    fn convert_navigation_input_to_rules_external_and_label() {
+        let input = vec![Navigation {
+            label: Some(String::from("Elsewhere")),
+            children: Some(NavChildren::List(vec![Navigation {
+                url: Some(String::from("https://github.com/Doctave/doctave")),
+                title: Some(String::from("GitHub")),
+                ..Navigation::default()
+            }])),
+            ..Navigation::default()
+        }];
+
+        assert_eq!(
+            NavRule::from_yaml_input(input, Path::new("")),
+            vec![NavRule::Label(
+                String::from("Elsewhere"),
+                vec![NavRule::External(
+                    String::from("https://github.com/Doctave/doctave"),
+                    String::from("GitHub")
+                )]
+            )]
+        );
+    }
+
+    #[test]
+    fn convert_navigation_input_to_rules_directory_patterns() {
+        let input = vec![
+            Navigation {
+                path: Some(PathBuf::from("docs").join("features")),
+                children: Some(NavChildren::WildCard(String::from("custom-*.md"))),
+                ..Navigation::default()
+            },
+            Navigation {
+                path: Some(PathBuf::from("docs").join("features")),
+                children: Some(NavChildren::WildCard(String::from("*"))),
+                exclude: Some(vec![String::from("markdown.md")]),
+                ..Navigation::default()
+            },
+        ];
+
+        assert_eq!(
+            NavRule::from_yaml_input(input, Path::new("")),
+            vec![
+                NavRule::Dir(
+                    PathBuf::from("docs").join("features"),
+                    Some(DirIncludeRule::Matching {
+                        pattern: Some(Pattern::new("custom-*.md").unwrap()),
+                        exclude: vec![],
+                    }),
+                    None
+                ),
+                NavRule::Dir(
+                    PathBuf::from("docs").join("features"),
+                    Some(DirIncludeRule::Matching {
+                        pattern: None,
+                        exclude: vec![Pattern::new("markdown.md").unwrap()],
+                    }),
+                    None
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn patterns_do_not_match_across_directories() {
+        let pattern = Pattern::new("*.md").unwrap();
+
+        assert!(glob_matches(&pattern, Path::new("one.md")));
+        assert!(!glob_matches(&pattern, &Path::new("nested").join("two.md")));
+        assert!(glob_matches(
+            &Pattern::new("**/*.md").unwrap(),
+            &Path::new("nested").join("two.md")
+        ));
+    }
 }
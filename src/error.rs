@@ -28,6 +28,13 @@ impl Error {
         }
     }
 
+    pub fn template<S: Into<String>>(err: handlebars::TemplateError, msg: S) -> Self {
+        Error {
+            kind: ErrorKind::Template(err),
+            message: msg.into(),
+        }
+    }
+
     pub fn io<S: Into<String>>(err: std::io::Error, msg: S) -> Self {
         Error {
             kind: ErrorKind::IO(err),
@@ -40,6 +47,7 @@ impl Error {
 pub enum ErrorKind {
     IO(std::io::Error),
     Handlebars(handlebars::RenderError),
+    Template(handlebars::TemplateError),
     Yaml(serde_yaml::Error),
     Generic,
 }
@@ -49,6 +57,7 @@ impl fmt::Display for Error {
         match &self.kind {
             ErrorKind::IO(io_err) => write!(f, "{}:\n{}", self.message, io_err),
             ErrorKind::Handlebars(err) => write!(f, "{}:\n{}", self.message, err),
+            ErrorKind::Template(err) => write!(f, "{}:\n{}", self.message, err),
             ErrorKind::Yaml(err) => write!(f, "{}:\n{}", self.message, err),
             ErrorKind::Generic => write!(f, "{}", self.message),
         }
@@ -1,21 +1,130 @@
 use std::collections::BTreeMap;
 
-pub fn parse(input: &str) -> std::io::Result<BTreeMap<String, String>> {
+use serde::{Deserialize, Serialize};
+
+use crate::toc::TocYaml;
+
+/// The parsed frontmatter of a document.
+///
+/// Keys Doctave knows about are parsed into their own fields, while `meta`
+/// holds every key exactly as it was written, including unknown ones, so
+/// that custom templates can use them.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Frontmatter {
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub tags: Vec<String>,
+    pub draft: bool,
+    /// Keeps search engines from indexing the page
+    pub noindex: bool,
+    /// Where the page goes in the navigation, relative to the others in
+    /// its directory. A directory's `README.md` sets where the directory
+    /// goes.
+    #[serde(alias = "order", alias = "nav_order")]
+    pub weight: Option<i64>,
+    pub layout: Option<String>,
+    /// The image to show in link previews of the page
+    pub og_image: Option<String>,
+    /// Whether to link to the previous and next pages at the bottom of
+    /// the page. On by default.
+    pub pagination: Option<bool>,
+    /// Which headings to list under "On this page", or `false` to hide it
+    pub toc: Option<TocYaml>,
+    /// Whether to include the page in the search index. On by default.
+    pub search: Option<bool>,
+    #[serde(skip_deserializing)]
+    pub meta: BTreeMap<String, serde_yaml::Value>,
+}
+
+impl Frontmatter {
+    /// Frontmatter with nothing but a title, for documents that don't
+    /// come from a file.
+    pub fn with_title<S: Into<String>>(title: S) -> Self {
+        let title = title.into();
+
+        let mut meta = BTreeMap::new();
+        meta.insert(
+            String::from("title"),
+            serde_yaml::Value::String(title.clone()),
+        );
+
+        Frontmatter {
+            title: Some(title),
+            meta,
+            ..Frontmatter::default()
+        }
+    }
+}
+
+pub fn parse(input: &str) -> Result<Frontmatter, serde_yaml::Error> {
     if input.starts_with("---\n") {
         let after_starter_mark = &input[4..];
         let end_mark = after_starter_mark.find("---\n");
 
         if end_mark.is_none() {
-            return Ok(BTreeMap::new());
+            return Ok(Frontmatter::default());
         };
 
-        serde_yaml::from_str(&input[4..end_mark.unwrap() + 4])
-            .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e))
+        let yaml = &input[4..end_mark.unwrap() + 4];
+
+        if yaml.trim().is_empty() {
+            return Ok(Frontmatter::default());
+        }
+
+        let mut frontmatter: Frontmatter = serde_yaml::from_str(yaml)?;
+        frontmatter.meta = serde_yaml::from_str(yaml)?;
+
+        Ok(frontmatter)
     } else {
-        Ok(BTreeMap::new())
+        Ok(Frontmatter::default())
+    }
+}
+
+/// Finds the line and column in the original input where parsing the
+/// frontmatter failed, if the YAML parser reported a location.
+///
+/// Lines and columns start at 1, and take into account the opening `---`.
+pub fn error_location(error: &serde_yaml::Error) -> Option<(usize, usize)> {
+    error.location().map(|l| (l.line() + 1, l.column()))
+}
+
+/// Describes what went wrong when parsing the frontmatter, without the
+/// location the YAML parser adds, since that is relative to the start of
+/// the frontmatter rather than the file.
+pub fn error_description(error: &serde_yaml::Error) -> String {
+    let description = error.to_string();
+
+    match description.find(" at line ") {
+        Some(position) => description[..position].to_string(),
+        None => description,
     }
 }
 
+/// Renders the lines leading up to the given line, with a marker pointing
+/// at the column, for showing where an error in the input is.
+pub fn snippet(input: &str, line: usize, column: usize) -> String {
+    let first = line.saturating_sub(2).max(1);
+    let gutter = format!("{}", line).len();
+
+    let mut snippet = input
+        .lines()
+        .enumerate()
+        .map(|(i, l)| (i + 1, l))
+        .filter(|(n, _)| *n >= first && *n <= line)
+        .map(|(n, l)| format!("{:>width$} | {}", n, l, width = gutter))
+        .collect::<Vec<_>>();
+
+    snippet.push(format!(
+        "{:>width$} | {}^",
+        "",
+        " ".repeat(column.saturating_sub(1)),
+        width = gutter
+    ));
+
+    snippet.join("\n")
+}
+
 pub fn end_pos(input: &str) -> usize {
     if input.starts_with("---\n") {
         let after_starter_mark = &input[4..];
@@ -49,12 +158,123 @@ mod test {
             # Runbooks
         "};
 
-        let values = parse(input).unwrap();
+        let frontmatter = parse(input).unwrap();
+
+        assert_eq!(frontmatter.title, Some("Runbooks".to_owned()));
+        assert_eq!(
+            frontmatter.meta.get("title"),
+            Some(&serde_yaml::Value::String("Runbooks".to_owned()))
+        );
+    }
+
+    #[test]
+    fn typed_values() {
+        let input = indoc! {"
+            ---
+            title: Runbooks
+            description: How to keep things running
+            tags:
+              - ops
+              - oncall
+            draft: true
+            noindex: true
+            weight: 10
+            slug: runbooks
+            layout: landing
+            og_image: previews/runbooks.png
+            pagination: false
+            toc:
+              max_level: 4
+            ---
+
+            # Runbooks
+        "};
+
+        let frontmatter = parse(input).unwrap();
+
+        assert_eq!(
+            frontmatter.description,
+            Some("How to keep things running".to_owned())
+        );
+        assert_eq!(
+            frontmatter.tags,
+            vec!["ops".to_owned(), "oncall".to_owned()]
+        );
+        assert_eq!(frontmatter.draft, true);
+        assert_eq!(frontmatter.noindex, true);
+        assert_eq!(frontmatter.weight, Some(10));
+        assert_eq!(
+            frontmatter.meta.get("slug"),
+            Some(&serde_yaml::Value::String("runbooks".to_owned()))
+        );
+        assert_eq!(frontmatter.layout, Some("landing".to_owned()));
+        assert_eq!(
+            frontmatter.og_image,
+            Some("previews/runbooks.png".to_owned())
+        );
+        assert_eq!(frontmatter.pagination, Some(false));
+        assert_eq!(
+            frontmatter.toc,
+            Some(TocYaml::Levels {
+                min_level: None,
+                max_level: Some(4)
+            })
+        );
+    }
+
+    #[test]
+    fn weight_aliases() {
+        for key in &["weight", "order", "nav_order"] {
+            let input = format!("---\n{}: 3\n---\n", key);
+
+            assert_eq!(parse(&input).unwrap().weight, Some(3));
+        }
+    }
+
+    #[test]
+    fn unknown_keys_are_kept() {
+        let input = indoc! {"
+            ---
+            title: Runbooks
+            owner:
+              team: platform
+            ---
+
+            # Runbooks
+        "};
+
+        let frontmatter = parse(input).unwrap();
+
+        let owner = frontmatter.meta.get("owner").unwrap();
+        assert_eq!(
+            owner.get("team"),
+            Some(&serde_yaml::Value::String("platform".to_owned()))
+        );
+    }
+
+    #[test]
+    fn wrong_type_for_known_key() {
+        let input = indoc! {"
+            ---
+            weight: heavy
+            ---
+
+            # Runbooks
+        "};
+
+        assert!(parse(input).is_err());
+    }
+
+    #[test]
+    fn empty_frontmatter() {
+        let input = indoc! {"
+            ---
+            ---
 
-        let expected = Some("Runbooks".to_owned());
-        let actual = values.get("title");
+            # Some content
+        "};
 
-        assert_eq!(actual, expected.as_ref());
+        assert_eq!(parse(input).unwrap(), Frontmatter::default());
     }
 
     #[test]
@@ -63,9 +283,9 @@ mod test {
             # Some content
         "};
 
-        let values = parse(input).unwrap();
+        let frontmatter = parse(input).unwrap();
 
-        assert_eq!(values, BTreeMap::new());
+        assert_eq!(frontmatter, Frontmatter::default());
     }
 
     #[test]
@@ -81,6 +301,45 @@ mod test {
         assert!(parse(input).is_err());
     }
 
+    #[test]
+    fn invalid_yaml_location() {
+        let input = indoc! {"
+            ---
+            title: Runbooks
+            tags: [one, two
+            ---
+
+            # Some content
+        "};
+
+        let error = parse(input).unwrap_err();
+
+        assert_eq!(error_location(&error), Some((4, 1)));
+        assert_eq!(
+            error_description(&error),
+            "while parsing a flow sequence, expected ',' or ']'"
+        );
+    }
+
+    #[test]
+    fn error_snippet() {
+        let input = indoc! {"
+            ---
+            title: Runbooks
+            :::blarg: @@!~
+            ---
+        "};
+
+        assert_eq!(
+            snippet(input, 3, 11),
+            indoc! {"
+                1 | ---
+                2 | title: Runbooks
+                3 | :::blarg: @@!~
+                  |           ^"}
+        );
+    }
+
     #[test]
     fn never_ending_frontmatter() {
         let input = indoc! {"
@@ -90,7 +349,7 @@ mod test {
             # Runbooks
         "};
 
-        assert_eq!(parse(input).unwrap(), BTreeMap::new());
+        assert_eq!(parse(input).unwrap(), Frontmatter::default());
     }
 
     #[test]
@@ -33,6 +33,16 @@ impl InitCommand {
 
         cmd.check_for_existing_project()?;
 
+        fs::create_dir_all(&cmd.project_root).map_err(|e| {
+            Error::io(
+                e,
+                format!(
+                    "Could not create project directory {}",
+                    cmd.project_root.display()
+                ),
+            )
+        })?;
+
         cmd.create_doctave_yaml()?;
 
         if cmd.no_existing_docs_dir() {
@@ -65,7 +75,7 @@ impl InitCommand {
     }
 
     fn no_existing_docs_dir(&self) -> bool {
-        !self.project_root.join("docs").exists()
+        !self.docs_root.exists()
     }
 
     fn create_doctave_yaml(&mut self) -> Result<()> {
@@ -81,7 +91,7 @@ impl InitCommand {
     }
 
     fn create_docs_dir(&mut self) -> Result<()> {
-        if !self.project_root.join("docs").exists() {
+        if !self.docs_root.exists() {
             fs::create_dir(&self.docs_root).map_err(|e| {
                 Error::io(
                     e,
@@ -99,16 +109,13 @@ impl InitCommand {
     }
 
     fn create_docs_index(&mut self) -> Result<()> {
-        let path = self.project_root.join("docs").join("README.md");
+        let path = self.docs_root.join("README.md");
 
         if !path.exists() {
             let mut file = File::create(path).map_err(|e| {
                 Error::io(
                     e,
-                    format!(
-                        "Could not create README.md in {}",
-                        self.project_root.join("docs").display()
-                    ),
+                    format!("Could not create README.md in {}", self.docs_root.display()),
                 )
             })?;
 
@@ -127,7 +134,7 @@ impl InitCommand {
     }
 
     fn create_doc_examples(&mut self) -> Result<()> {
-        let path = self.project_root.join("docs").join("examples.md");
+        let path = self.docs_root.join("examples.md");
 
         if !path.exists() {
             let mut file = File::create(path).map_err(|e| {
@@ -135,7 +142,7 @@ impl InitCommand {
                     e,
                     format!(
                         "Could not create examles.md in {}",
-                        self.project_root.join("docs").display()
+                        self.docs_root.display()
                     ),
                 )
             })?;
@@ -0,0 +1,218 @@
+use std::collections::BTreeMap;
+
+/// The UI strings that can be translated, and their English defaults.
+static ENGLISH: &[(&str, &str)] = &[
+    ("on_this_page", "On this page"),
+    ("search", "Search..."),
+    ("version", "Version"),
+    ("language", "Language"),
+    (
+        "outdated_version",
+        "You are reading the documentation for version",
+    ),
+    ("latest_version", "Go to the latest version"),
+    (
+        "draft",
+        "This page is a draft. It is not included in release builds.",
+    ),
+    ("previous", "Previous"),
+    ("next", "Next"),
+    ("breadcrumbs", "Breadcrumbs"),
+];
+
+static GERMAN: &[(&str, &str)] = &[
+    ("on_this_page", "Auf dieser Seite"),
+    ("search", "Suchen..."),
+    ("version", "Version"),
+    ("language", "Sprache"),
+    (
+        "outdated_version",
+        "Sie lesen die Dokumentation für Version",
+    ),
+    ("latest_version", "Zur neuesten Version"),
+    (
+        "draft",
+        "Diese Seite ist ein Entwurf. Sie ist in Release-Builds nicht enthalten.",
+    ),
+    ("previous", "Zurück"),
+    ("next", "Weiter"),
+    ("breadcrumbs", "Brotkrümelnavigation"),
+];
+
+static FRENCH: &[(&str, &str)] = &[
+    ("on_this_page", "Sur cette page"),
+    ("search", "Rechercher..."),
+    ("version", "Version"),
+    ("language", "Langue"),
+    (
+        "outdated_version",
+        "Vous lisez la documentation de la version",
+    ),
+    ("latest_version", "Aller à la dernière version"),
+    (
+        "draft",
+        "Cette page est un brouillon. Elle n'est pas incluse dans les builds de production.",
+    ),
+    ("previous", "Précédent"),
+    ("next", "Suivant"),
+    ("breadcrumbs", "Fil d'Ariane"),
+];
+
+static SPANISH: &[(&str, &str)] = &[
+    ("on_this_page", "En esta página"),
+    ("search", "Buscar..."),
+    ("version", "Versión"),
+    ("language", "Idioma"),
+    (
+        "outdated_version",
+        "Está leyendo la documentación de la versión",
+    ),
+    ("latest_version", "Ir a la última versión"),
+    (
+        "draft",
+        "Esta página es un borrador. No se incluye en las versiones de producción.",
+    ),
+    ("previous", "Anterior"),
+    ("next", "Siguiente"),
+    ("breadcrumbs", "Ruta de navegación"),
+];
+
+static JAPANESE: &[(&str, &str)] = &[
+    ("on_this_page", "このページの内容"),
+    ("search", "検索..."),
+    ("version", "バージョン"),
+    ("language", "言語"),
+    ("outdated_version", "古いバージョンのドキュメントです:"),
+    ("latest_version", "最新バージョンを見る"),
+    (
+        "draft",
+        "このページは下書きです。リリースビルドには含まれません。",
+    ),
+    ("previous", "前へ"),
+    ("next", "次へ"),
+    ("breadcrumbs", "パンくずリスト"),
+];
+
+/// Words too common to search for, which are left out of the search index.
+/// The same words elasticlunr.js leaves out.
+static ENGLISH_STOP_WORDS: &[&str] = &[
+    "a", "able", "about", "across", "after", "all", "almost", "also", "am", "among", "an", "and",
+    "any", "are", "as", "at", "be", "because", "been", "but", "by", "can", "cannot", "could",
+    "dear", "did", "do", "does", "either", "else", "ever", "every", "for", "from", "get", "got",
+    "had", "has", "have", "he", "her", "hers", "him", "his", "how", "however", "i", "if", "in",
+    "into", "is", "it", "its", "just", "least", "let", "like", "likely", "may", "me", "might",
+    "most", "must", "my", "neither", "no", "nor", "not", "of", "off", "often", "on", "only", "or",
+    "other", "our", "own", "rather", "said", "say", "says", "she", "should", "since", "so", "some",
+    "than", "that", "the", "their", "them", "then", "there", "these", "they", "this", "tis", "to",
+    "too", "twas", "us", "wants", "was", "we", "were", "what", "when", "where", "which", "while",
+    "who", "whom", "why", "will", "with", "would", "yet", "you", "your",
+];
+
+static GERMAN_STOP_WORDS: &[&str] = &[
+    "aber", "als", "am", "an", "auch", "auf", "aus", "bei", "bin", "bis", "da", "das", "dass",
+    "dem", "den", "der", "des", "die", "doch", "du", "ein", "eine", "einem", "einen", "einer",
+    "eines", "er", "es", "für", "hat", "ich", "ihr", "im", "in", "ist", "ja", "kann", "man", "mit",
+    "nach", "nicht", "noch", "nur", "oder", "sich", "sie", "sind", "so", "um", "und", "uns", "vom",
+    "von", "vor", "war", "was", "wenn", "wie", "wir", "wird", "zu", "zum", "zur",
+];
+
+static FRENCH_STOP_WORDS: &[&str] = &[
+    "à", "au", "aux", "avec", "ce", "ces", "dans", "de", "des", "du", "elle", "en", "est", "et",
+    "il", "ils", "je", "la", "le", "les", "leur", "lui", "mais", "me", "même", "mes", "ne", "nous",
+    "on", "ou", "où", "par", "pas", "pour", "qu", "que", "qui", "sa", "se", "ses", "son", "sur",
+    "ta", "te", "tes", "ton", "tu", "un", "une", "vos", "votre", "vous",
+];
+
+static SPANISH_STOP_WORDS: &[&str] = &[
+    "a", "al", "algo", "como", "con", "de", "del", "el", "ella", "en", "es", "esta", "este", "ha",
+    "la", "las", "le", "lo", "los", "más", "me", "mi", "no", "nos", "o", "para", "pero", "por",
+    "que", "se", "si", "sin", "sobre", "su", "sus", "te", "tu", "un", "una", "y", "ya", "yo",
+];
+
+/// Names of the UI strings that can be translated.
+pub fn string_names() -> impl Iterator<Item = &'static str> {
+    ENGLISH.iter().map(|(name, _)| *name)
+}
+
+/// The built-in UI strings for a language, falling back to English for
+/// languages Doctave does not have translations for.
+pub fn default_strings(code: &str) -> BTreeMap<String, String> {
+    let translations = match code.split('-').next().unwrap_or(code) {
+        "de" => GERMAN,
+        "fr" => FRENCH,
+        "es" => SPANISH,
+        "ja" => JAPANESE,
+        _ => ENGLISH,
+    };
+
+    translations
+        .iter()
+        .map(|(name, value)| (name.to_string(), value.to_string()))
+        .collect()
+}
+
+/// The name of a language in that language, for the language switcher.
+pub fn default_name(code: &str) -> Option<&'static str> {
+    match code.split('-').next().unwrap_or(code) {
+        "en" => Some("English"),
+        "de" => Some("Deutsch"),
+        "fr" => Some("Français"),
+        "es" => Some("Español"),
+        "ja" => Some("日本語"),
+        _ => None,
+    }
+}
+
+/// The words to leave out of the search index for a language. Languages
+/// Doctave doesn't know get none, rather than the English ones.
+pub fn default_stop_words(code: &str) -> Vec<String> {
+    let words = match code.split('-').next().unwrap_or(code) {
+        "en" => ENGLISH_STOP_WORDS,
+        "de" => GERMAN_STOP_WORDS,
+        "fr" => FRENCH_STOP_WORDS,
+        "es" => SPANISH_STOP_WORDS,
+        _ => &[],
+    };
+
+    words.iter().map(|w| w.to_string()).collect()
+}
+
+/// Whether Doctave can reduce words of a language to their stem for
+/// searching, so that "installing" also finds "installation". Only
+/// English is supported.
+pub fn has_stemmer(code: &str) -> bool {
+    code.split('-').next() == Some("en")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn every_language_has_every_string() {
+        for code in &["en", "de", "fr", "es", "ja"] {
+            let strings = default_strings(code);
+
+            for name in string_names() {
+                assert!(strings.contains_key(name), "{} is missing {}", code, name);
+            }
+            assert_eq!(strings.len(), string_names().count());
+        }
+    }
+
+    #[test]
+    fn regional_variants_use_the_base_language() {
+        assert_eq!(default_strings("de-AT")["on_this_page"], "Auf dieser Seite");
+        assert_eq!(default_name("ja-JP"), Some("日本語"));
+        assert!(default_stop_words("de-AT").contains(&"und".to_string()));
+        assert!(has_stemmer("en-GB"));
+    }
+
+    #[test]
+    fn unknown_languages_fall_back_to_english() {
+        assert_eq!(default_strings("fi")["on_this_page"], "On this page");
+        assert_eq!(default_name("fi"), None);
+        assert!(default_stop_words("fi").is_empty());
+        assert!(!has_stemmer("fi"));
+    }
+}
@@ -4,42 +4,48 @@
 #[macro_use]
 extern crate indoc;
 
-#[macro_use]
-extern crate lazy_static;
-
 mod build;
+mod check;
 pub mod config;
 mod error;
 mod frontmatter;
 mod init;
+mod languages;
 mod livereload_server;
 mod navigation;
 mod preview_server;
+mod relative_links;
+mod search;
 #[allow(dead_code, unused_variables)]
 mod serve;
 mod site;
 mod site_generator;
+mod sitemap;
+mod stemmer;
+mod templates;
+mod toc;
+mod versions;
 mod watcher;
 
-use std::collections::BTreeMap;
 use std::ffi::OsStr;
 use std::fs;
 use std::path::{Path, PathBuf};
 
+pub use build::{BuildCommand, BuildOptions};
+pub use check::CheckCommand;
 pub use config::Config;
-pub use build::BuildCommand;
 pub use error::Error;
 pub use init::InitCommand;
 pub use serve::{ServeCommand, ServeOptions};
 pub use site::BuildMode;
 
 pub use doctave_markdown::{Heading, Markdown};
-use handlebars::Handlebars;
+use frontmatter::Frontmatter;
 use navigation::Link;
 
 static APP_JS: &str = include_str!("assets/app.js");
 static MERMAID_JS: &str = include_str!("assets/mermaid.min.js");
-static ELASTIC_LUNR: &str = include_str!("assets/elasticlunr.min.js");
+static STEMMER_JS: &str = include_str!("assets/stemmer.min.js");
 static LIVERELOAD_JS: &str = include_str!("assets/livereload.min.js");
 static PRISM_JS: &str = include_str!("assets/prism.min.js");
 
@@ -47,33 +53,6 @@ static NORMALIZE_CSS: &str = include_str!("assets/normalize.css");
 static ATOM_DARK_CSS: &str = include_str!("assets/prism-atom-dark.css");
 static GH_COLORS_CSS: &str = include_str!("assets/prism-ghcolors.css");
 
-lazy_static! {
-    pub static ref HANDLEBARS: Handlebars<'static> = {
-        let mut handlebars = Handlebars::new();
-
-        handlebars
-            .register_template_string("page", include_str!("../templates/page.html"))
-            .unwrap();
-        handlebars
-            .register_template_string("navigation", include_str!("../templates/navigation.html"))
-            .unwrap();
-        handlebars
-            .register_template_string("search", include_str!("../templates/search.html"))
-            .unwrap();
-        handlebars
-            .register_template_string(
-                "nested_navigation",
-                include_str!("../templates/nested_navigation.html"),
-            )
-            .unwrap();
-        handlebars
-            .register_template_string("style.css", include_str!("../templates/style.css"))
-            .unwrap();
-
-        handlebars
-    };
-}
-
 pub type Result<T> = std::result::Result<T, error::Error>;
 
 #[derive(Debug, Clone)]
@@ -88,14 +67,87 @@ impl Directory {
         &self.path
     }
 
-    fn destination(&self, out: &Path) -> PathBuf {
-        self.docs
-            .get(0)
-            .unwrap()
-            .destination(out)
-            .parent()
-            .unwrap()
-            .to_path_buf()
+    /// All documents in this directory and its subdirectories.
+    fn docs_recursive(&self) -> Vec<&Document> {
+        let mut docs = self.docs.iter().collect::<Vec<_>>();
+
+        for dir in &self.dirs {
+            docs.append(&mut dir.docs_recursive());
+        }
+
+        docs
+    }
+
+    /// Inserts a document into the tree, replacing any existing document
+    /// with the same path. Creates any missing directories along the way.
+    ///
+    /// Must be called on the root directory, since document paths are
+    /// relative to the docs folder.
+    fn upsert(&mut self, doc: Document) {
+        let mut dir = &mut *self;
+
+        if let Some(parent) = doc.path.parent() {
+            for component in parent.components() {
+                let child_path = dir.path.join(component);
+
+                let position = match dir.dirs.iter().position(|d| d.path == child_path) {
+                    Some(position) => position,
+                    None => {
+                        dir.dirs.push(Directory {
+                            path: child_path,
+                            docs: vec![],
+                            dirs: vec![],
+                        });
+                        dir.dirs.len() - 1
+                    }
+                };
+
+                dir = &mut dir.dirs[position];
+            }
+        }
+
+        match dir.docs.iter().position(|d| d.path == doc.path) {
+            Some(position) => dir.docs[position] = doc,
+            None => dir.docs.push(doc),
+        }
+
+        self.prune();
+    }
+
+    /// Removes a document from the tree by its relative path in the docs folder.
+    ///
+    /// Must be called on the root directory, since document paths are
+    /// relative to the docs folder.
+    fn remove(&mut self, relative_path: &Path) {
+        self.docs.retain(|d| d.path != relative_path);
+
+        for dir in &mut self.dirs {
+            dir.remove(relative_path);
+        }
+
+        self.prune();
+    }
+
+    /// Removes the documents marked as drafts, along with any directories
+    /// left without documents.
+    fn remove_drafts(&mut self) {
+        self.docs.retain(|d| !d.frontmatter.draft);
+
+        for dir in &mut self.dirs {
+            dir.remove_drafts();
+        }
+
+        self.prune();
+    }
+
+    /// Drops any subdirectories that don't contain documents, matching how
+    /// directories are discovered when walking the docs folder.
+    fn prune(&mut self) {
+        for dir in &mut self.dirs {
+            dir.prune();
+        }
+
+        self.dirs.retain(|d| !d.docs.is_empty());
     }
 
     fn index(&self) -> &Document {
@@ -116,10 +168,13 @@ struct Document {
     pub id: u32,
     /// The relative path in the docs folder to the file
     path: PathBuf,
+    /// The relative path in the docs folder to the file the document was
+    /// read from. Differs from `path` for translations, like `tutorial.de.md`.
+    source: PathBuf,
     rename: Option<String>,
     raw: String,
     markdown: Markdown,
-    frontmatter: BTreeMap<String, String>,
+    frontmatter: Frontmatter,
 }
 
 impl Document {
@@ -127,16 +182,41 @@ impl Document {
     ///
     /// Must be provided both the absolute path to the file, and the relative
     /// path inside the docs directory to the original file.
-    fn load(absolute_path: &Path, relative_docs_path: &Path) -> Self {
-        let raw = fs::read_to_string(absolute_path).unwrap();
+    fn load(absolute_path: &Path, relative_docs_path: &Path) -> Result<Self> {
+        let raw = fs::read_to_string(absolute_path)
+            .map_err(|e| Error::io(e, format!("Could not read {}", absolute_path.display())))?;
+
         let frontmatter =
-            frontmatter::parse(&raw).expect("TODO: Print an error when frontmatter is busted");
+            frontmatter::parse(&raw).map_err(|e| match frontmatter::error_location(&e) {
+                Some((line, column)) => Error::new(format!(
+                    "Invalid frontmatter in {}:{}:{}\n\n{}\n{}",
+                    absolute_path.display(),
+                    line,
+                    column,
+                    frontmatter::snippet(&raw, line, column),
+                    frontmatter::error_description(&e)
+                )),
+                None => Error::yaml(
+                    e,
+                    format!("Invalid frontmatter in {}", absolute_path.display()),
+                ),
+            })?;
+
+        if let Some(toc) = &frontmatter.toc {
+            toc.validate().map_err(|e| {
+                Error::new(format!(
+                    "Invalid frontmatter in {}\n\n{}",
+                    absolute_path.display(),
+                    e
+                ))
+            })?;
+        }
 
-        Document::new(relative_docs_path, raw, frontmatter)
+        Ok(Document::new(relative_docs_path, raw, frontmatter))
     }
 
     /// Creates a new document from its raw components
-    fn new(path: &Path, raw: String, frontmatter: BTreeMap<String, String>) -> Self {
+    fn new(path: &Path, raw: String, frontmatter: Frontmatter) -> Self {
         let rename = if path.ends_with("README.md") {
             Some("index".to_string())
         } else {
@@ -148,6 +228,7 @@ impl Document {
         Document {
             id: DOCUMENT_ID.fetch_add(1, std::sync::atomic::Ordering::Relaxed),
             path: path.to_path_buf(),
+            source: path.to_path_buf(),
             raw,
             markdown,
             rename,
@@ -155,6 +236,12 @@ impl Document {
         }
     }
 
+    /// Sets the file the document was read from, if it was not `path`.
+    fn with_source(mut self, source: &Path) -> Self {
+        self.source = source.to_path_buf();
+        self
+    }
+
     fn original_file_name(&self) -> Option<&OsStr> {
         self.path.file_name()
     }
@@ -192,8 +279,212 @@ impl Document {
 
     fn title(&self) -> &str {
         self.frontmatter
-            .get("title")
-            .map(|t| t.as_ref())
+            .title
+            .as_deref()
             .unwrap_or_else(|| self.path.file_stem().unwrap().to_str().unwrap())
     }
+
+    /// A short description of the page for search engines and link
+    /// previews. Taken from the frontmatter, or the first paragraph of the
+    /// page if it has none.
+    fn description(&self) -> Option<String> {
+        match &self.frontmatter.description {
+            Some(description) => Some(description.clone()),
+            None => summary(self.markdown_section(), DESCRIPTION_LENGTH),
+        }
+    }
+}
+
+/// How many characters of the first paragraph to use as a description
+static DESCRIPTION_LENGTH: usize = 160;
+
+/// The text of the first paragraph of some Markdown, cut off at a word
+/// boundary if it is longer than `max_length` characters.
+fn summary(markdown: &str, max_length: usize) -> Option<String> {
+    use pulldown_cmark::{Event, Parser, Tag};
+
+    let mut text = String::new();
+    let mut in_paragraph = false;
+    let mut in_image = false;
+
+    for event in Parser::new(markdown) {
+        match event {
+            Event::Start(Tag::Paragraph) => in_paragraph = true,
+            Event::End(Tag::Paragraph) if !text.trim().is_empty() => break,
+            Event::End(Tag::Paragraph) => in_paragraph = false,
+            // Alt text of images is not part of the text
+            Event::Start(Tag::Image(..)) => in_image = true,
+            Event::End(Tag::Image(..)) => in_image = false,
+            Event::Text(t) | Event::Code(t) if in_paragraph && !in_image => text.push_str(&t),
+            Event::SoftBreak | Event::HardBreak if in_paragraph => text.push(' '),
+            _ => {}
+        }
+    }
+
+    shorten(&text, max_length)
+}
+
+/// Some text with its whitespace collapsed, cut off at a word boundary if
+/// it is longer than `max_length` characters, or within the first word if
+/// even that is too long. `None` if there is no text.
+fn shorten(text: &str, max_length: usize) -> Option<String> {
+    let words = text.split_whitespace().collect::<Vec<_>>();
+    if words.is_empty() {
+        return None;
+    }
+
+    let mut summary = String::new();
+    for (i, word) in words.iter().enumerate() {
+        let separator = if summary.is_empty() { 0 } else { 1 };
+        // Leaves room for the ellipsis, unless this is the last word
+        let ellipsis = if i + 1 < words.len() { 1 } else { 0 };
+
+        let length = summary.chars().count() + separator + word.chars().count() + ellipsis;
+        if length > max_length {
+            if summary.is_empty() {
+                summary.extend(word.chars().take(max_length.saturating_sub(1)));
+            }
+
+            summary.push('…');
+            return Some(summary);
+        }
+
+        if !summary.is_empty() {
+            summary.push(' ');
+        }
+        summary.push_str(word);
+    }
+
+    Some(summary)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn page(path: &str, content: &str) -> Document {
+        Document::new(Path::new(path), content.to_string(), Frontmatter::default())
+    }
+
+    fn root() -> Directory {
+        Directory {
+            path: PathBuf::from("docs"),
+            docs: vec![page("README.md", "# Root")],
+            dirs: vec![Directory {
+                path: PathBuf::from("docs").join("child"),
+                docs: vec![page("child/README.md", "# Child")],
+                dirs: vec![],
+            }],
+        }
+    }
+
+    #[test]
+    fn upsert_replaces_existing_document() {
+        let mut root = root();
+
+        root.upsert(page("child/README.md", "# Updated"));
+
+        assert_eq!(root.dirs[0].docs.len(), 1);
+        assert_eq!(root.dirs[0].docs[0].raw, "# Updated");
+    }
+
+    #[test]
+    fn upsert_creates_missing_directories() {
+        let mut root = root();
+
+        root.upsert(page("other/README.md", "# Other"));
+
+        assert_eq!(root.dirs.len(), 2);
+        assert_eq!(root.dirs[1].path, PathBuf::from("docs").join("other"));
+        assert_eq!(root.dirs[1].docs[0].raw, "# Other");
+    }
+
+    #[test]
+    fn remove_drafts_prunes_empty_directories() {
+        let draft = |path: &str| {
+            Document::new(
+                Path::new(path),
+                String::from("# Draft"),
+                Frontmatter {
+                    draft: true,
+                    ..Frontmatter::default()
+                },
+            )
+        };
+
+        let mut root = root();
+        root.docs.push(draft("upcoming.md"));
+        root.dirs[0].docs[0] = draft("child/README.md");
+
+        root.remove_drafts();
+
+        assert_eq!(root.docs.len(), 1);
+        assert_eq!(root.docs[0].raw, "# Root");
+        assert!(root.dirs.is_empty());
+    }
+
+    #[test]
+    fn description_from_first_paragraph() {
+        let doc = page(
+            "README.md",
+            "# Title\n\n![Logo](/logo.png)\n\nThe *first* paragraph,\nwith `code`.\n\nThe second.",
+        );
+
+        assert_eq!(
+            doc.description(),
+            Some(String::from("The first paragraph, with code."))
+        );
+        assert_eq!(page("README.md", "# Only a title").description(), None);
+    }
+
+    #[test]
+    fn description_from_frontmatter() {
+        let doc = Document::new(
+            Path::new("README.md"),
+            String::from("# Title\n\nA paragraph."),
+            Frontmatter {
+                description: Some(String::from("From frontmatter")),
+                ..Frontmatter::default()
+            },
+        );
+
+        assert_eq!(doc.description(), Some(String::from("From frontmatter")));
+    }
+
+    #[test]
+    fn long_summaries_are_cut_at_words() {
+        assert_eq!(
+            summary("one two three four", 10),
+            Some(String::from("one two…"))
+        );
+        assert_eq!(summary("one two", 7), Some(String::from("one two")));
+    }
+
+    #[test]
+    fn long_first_words_are_cut_off() {
+        assert_eq!(
+            shorten("Supercalifragilistic expialidocious", 10),
+            Some(String::from("Supercali…"))
+        );
+        assert_eq!(shorten("Ääkkösiä", 4), Some(String::from("Ääk…")));
+        assert_eq!(shorten("Ääkkösiä", 1), Some(String::from("…")));
+    }
+
+    #[test]
+    fn text_of_the_maximum_length_is_kept() {
+        assert_eq!(shorten("abc", 3), Some(String::from("abc")));
+        assert_eq!(shorten("ab cd", 5), Some(String::from("ab cd")));
+        assert_eq!(shorten("abc def", 3), Some(String::from("ab…")));
+        assert_eq!(shorten("ab cd ef", 6), Some(String::from("ab cd…")));
+    }
+
+    #[test]
+    fn remove_prunes_empty_directories() {
+        let mut root = root();
+
+        root.remove(Path::new("child/README.md"));
+
+        assert_eq!(root.docs.len(), 1);
+        assert!(root.dirs.is_empty());
+    }
 }
@@ -17,13 +17,16 @@ use tungstenite::protocol::WebSocket;
 /// Note that this server does not serve the actual livereload.js payload.
 /// This module expects the client to already have access to it by some
 /// other means.
+///
+/// Each update is a list of URIs that changed, which get passed on to the
+/// browser as the paths in the reload commands.
 pub struct LivereloadServer {
-    channel: Receiver<()>,
-    bus: Arc<Mutex<Bus<()>>>,
+    channel: Receiver<Vec<String>>,
+    bus: Arc<Mutex<Bus<Vec<String>>>>,
 }
 
 impl LivereloadServer {
-    pub fn new(channel: Receiver<()>) -> Self {
+    pub fn new(channel: Receiver<Vec<String>>) -> Self {
         LivereloadServer {
             channel,
             bus: Arc::new(Mutex::new(Bus::new(128))),
@@ -40,13 +43,13 @@ impl LivereloadServer {
             .spawn(move || run_listener(bus_clone))
             .unwrap();
 
-        for _msg in self.channel {
-            self.bus.lock().unwrap().broadcast(());
+        for msg in self.channel {
+            self.bus.lock().unwrap().broadcast(msg);
         }
     }
 }
 
-fn run_listener(bus: Arc<Mutex<Bus<()>>>) {
+fn run_listener(bus: Arc<Mutex<Bus<Vec<String>>>>) {
     let server = std::net::TcpListener::bind("127.0.0.1:35729").unwrap();
 
     for stream in server.incoming().filter_map(Result::ok) {
@@ -61,7 +64,7 @@ fn run_listener(bus: Arc<Mutex<Bus<()>>>) {
     }
 }
 
-fn handle_websocket(stream: std::net::TcpStream, mut listener: BusReader<()>) {
+fn handle_websocket(stream: std::net::TcpStream, mut listener: BusReader<Vec<String>>) {
     let result = || -> io::Result<()> {
         let mut websocket = tungstenite::accept(stream).map_err(|err| match err {
             HandshakeError::Failure(e) => map_tungstenite_error(e),
@@ -76,19 +79,18 @@ fn handle_websocket(stream: std::net::TcpStream, mut listener: BusReader<()>) {
         }
 
         loop {
-            if let Ok(_msg) = listener.recv_timeout(Duration::from_millis(1000)) {
-                websocket
-                    .write_message(
-                        r#"
-                        {
-                            "command": "reload",
-                            "path": "",
-                            "liveCSS": true
-                        }
-                        "#
-                        .into(),
-                    )
-                    .map_err(|e| map_tungstenite_error(e))?;
+            if let Ok(paths) = listener.recv_timeout(Duration::from_millis(1000)) {
+                for path in paths {
+                    let command = serde_json::json!({
+                        "command": "reload",
+                        "path": path,
+                        "liveCSS": true
+                    });
+
+                    websocket
+                        .write_message(command.to_string().into())
+                        .map_err(|e| map_tungstenite_error(e))?;
+                }
             } else {
                 websocket
                     .write_message(tungstenite::Message::Ping(Vec::new()))
@@ -139,6 +141,11 @@ fn livereload_handshake(websocket: &mut WebSocket<std::net::TcpStream>) -> io::R
 fn map_tungstenite_error(error: TungsteniteError) -> io::Error {
     match error {
         TungsteniteError::Io(io_error) => io_error,
+        // The browser may close the connection as soon as it gets the first
+        // reload command, while we still have more to send.
+        e @ TungsteniteError::ConnectionClosed | e @ TungsteniteError::AlreadyClosed => {
+            io::Error::new(io::ErrorKind::BrokenPipe, e)
+        }
         e => io::Error::new(io::ErrorKind::Other, e),
     }
 }
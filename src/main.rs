@@ -13,6 +13,14 @@ fn main() {
                 .help("Disable terminal color output")
                 .global(true),
         )
+        .arg(
+            Arg::with_name("project")
+                .long("project")
+                .takes_value(true)
+                .value_name("PATH")
+                .help("Path to the project. Defaults to the current directory.")
+                .global(true),
+        )
         .subcommand(SubCommand::with_name("init").about("Initialize a new project (start here!)"))
         .subcommand(
             SubCommand::with_name("build")
@@ -21,7 +29,25 @@ fn main() {
                     Arg::with_name("release")
                         .long("release")
                         .help("Build the site in release mode"),
-                ),
+                )
+                .arg(
+                    Arg::with_name("strict")
+                        .long("strict")
+                        .help("Fail the build if any documents contain broken links"),
+                )
+                .arg(
+                    Arg::with_name("relative-links")
+                        .long("relative-links")
+                        .help(
+                            "Link pages relative to each other, so the site works without a server",
+                        ),
+                )
+                .arg(out_dir_arg())
+                .arg(base_path_arg()),
+        )
+        .subcommand(
+            SubCommand::with_name("check")
+                .about("Checks your Markdown files for broken links and images"),
         )
         .subcommand(
             SubCommand::with_name("serve")
@@ -42,13 +68,16 @@ fn main() {
                             Ok(_) => Ok(()),
                             Err(e) => Err(e.to_string()),
                         }),
-                ),
+                )
+                .arg(out_dir_arg())
+                .arg(base_path_arg()),
         )
         .get_matches();
 
     let result = match matches.subcommand() {
         ("init", Some(cmd)) => init(cmd),
         ("build", Some(cmd)) => build(cmd),
+        ("check", Some(cmd)) => check(cmd),
         ("serve", Some(cmd)) => serve(cmd),
         _ => Ok(()),
     };
@@ -59,45 +88,105 @@ fn main() {
     }
 }
 
+fn out_dir_arg() -> Arg<'static, 'static> {
+    Arg::with_name("out-dir")
+        .long("out-dir")
+        .takes_value(true)
+        .value_name("PATH")
+        .help("Directory to build the site into. Defaults to site/ in the project.")
+}
+
+fn base_path_arg() -> Arg<'static, 'static> {
+    Arg::with_name("base-path")
+        .long("base-path")
+        .takes_value(true)
+        .value_name("PATH")
+        .help(
+            "URL path the site is hosted under, like /project. \
+             Overrides base_path in doctave.yaml.",
+        )
+}
+
 fn init(cmd: &ArgMatches) -> doctave::Result<()> {
-    let root_dir = std::env::current_dir().expect("Unable to determine current directory");
+    let current_dir = std::env::current_dir().expect("Unable to determine current directory");
+    let root_dir = match cmd.value_of("project") {
+        Some(path) => current_dir.join(path),
+        None => current_dir,
+    };
+
     doctave::InitCommand::run(root_dir, !cmd.is_present("no-color"))
 }
 
-fn build(cmd: &ArgMatches) -> doctave::Result<()> {
-    let project_dir = doctave::config::project_root().unwrap_or_else(|| {
-        println!("Could not find a doctave project in this directory, or its parents.");
-        std::process::exit(1);
-    });
+/// Loads the project given with --project, or the one the current directory
+/// is in.
+fn load_config(cmd: &ArgMatches) -> doctave::Result<doctave::Config> {
+    let current_dir = std::env::current_dir().expect("Unable to determine current directory");
+
+    let project_dir = match cmd.value_of("project") {
+        Some(path) => {
+            let project_dir = current_dir.join(path);
+
+            if !doctave::config::is_project_root(&project_dir) {
+                println!(
+                    "Could not find a doctave project in {}.",
+                    project_dir.display()
+                );
+                std::process::exit(1);
+            }
+
+            project_dir
+        }
+        None => doctave::config::project_root(&current_dir).unwrap_or_else(|| {
+            println!("Could not find a doctave project in this directory, or its parents.");
+            std::process::exit(1);
+        }),
+    };
 
     let mut config = doctave::Config::load(&project_dir)?;
-    if cmd.is_present("release") {
-        config.set_build_mode(doctave::BuildMode::Release);
+
+    if let Some(out_dir) = cmd.value_of("out-dir") {
+        config.set_out_dir(current_dir.join(out_dir))?;
+    }
+
+    if let Some(base_path) = cmd.value_of("base-path") {
+        config.set_base_path(base_path)?;
     }
 
     if cmd.is_present("no-color") {
         config.disable_colors();
     }
 
-    doctave::BuildCommand::run(config)
+    Ok(config)
 }
 
-fn serve(cmd: &ArgMatches) -> doctave::Result<()> {
-    let project_dir = doctave::config::project_root().unwrap_or_else(|| {
-        println!("Could not find a doctave project in this directory, or its parents.");
-        std::process::exit(1);
-    });
+fn build(cmd: &ArgMatches) -> doctave::Result<()> {
+    let mut config = load_config(cmd)?;
+    if cmd.is_present("release") {
+        config.set_build_mode(doctave::BuildMode::Release);
+    }
+
+    if cmd.is_present("relative-links") {
+        config.enable_relative_links();
+    }
 
+    let options = doctave::BuildOptions {
+        strict: cmd.is_present("strict"),
+    };
+
+    doctave::BuildCommand::run(options, config)
+}
+
+fn check(cmd: &ArgMatches) -> doctave::Result<()> {
+    doctave::CheckCommand::run(load_config(cmd)?)
+}
+
+fn serve(cmd: &ArgMatches) -> doctave::Result<()> {
     let mut options = doctave::ServeOptions::default();
-    let mut config = doctave::Config::load(&project_dir)?;
+    let config = load_config(cmd)?;
 
     if let Some(p) = cmd.value_of("port") {
         options.port = Some(p.parse::<u32>().unwrap());
     }
 
-    if cmd.is_present("no-color") {
-        config.disable_colors();
-    }
-
     doctave::ServeCommand::run(options, config)
 }
@@ -1,7 +1,10 @@
-use crate::config::{Config, DirIncludeRule, NavRule};
-use crate::Directory;
+use crate::config::{self, Config, DirIncludeRule, NavRule};
+use crate::{Directory, Result};
+use glob::Pattern;
 use serde::Serialize;
 
+use std::cmp::Ordering;
+use std::collections::HashSet;
 use std::ffi::OsStr;
 use std::path::{Path, PathBuf};
 
@@ -14,42 +17,82 @@ impl<'a> Navigation<'a> {
         Navigation { config }
     }
 
-    /// Builds a navigation tree given a root directory
-    pub fn build_for(&self, dir: &Directory) -> Vec<Link> {
-        let default: Vec<Link> = dir.into();
+    /// Builds a navigation tree given a root directory of the pages being
+    /// built, and the documents they were made from.
+    ///
+    /// Fails if rules in the doctave.yaml config don't match any page. Rules
+    /// for documents that were left out of the build, like drafts in a
+    /// release build, are skipped, as are all rules that don't match when
+    /// building another version or language of the docs, which may not
+    /// have every page.
+    pub fn build_for(&self, pages: &Directory, sources: &Directory) -> Result<Vec<Link>> {
+        let default: Vec<Link> = pages.into();
+
+        let rules = match self.config.navigation() {
+            None => return Ok(default),
+            Some(rules) => rules,
+        };
+
+        let mut unmatched = vec![];
+        let links = self.customize(rules, &default, pages, &mut unmatched);
 
-        match &self.config.navigation() {
-            None => default,
-            Some(nav) => self.customize(nav, &default),
+        let source_uris = sources
+            .docs_recursive()
+            .iter()
+            .map(|doc| doc.uri_path())
+            .collect::<HashSet<_>>();
+
+        unmatched.retain(|path| {
+            self.config.builds_navigation_pages()
+                && !source_uris.contains(&Link::path_to_uri(&self.relative_path(path)))
+        });
+
+        if unmatched.is_empty() {
+            Ok(links)
+        } else {
+            Err(self.config.navigation_error(
+                "Could not find pages for these entries in navigation:",
+                &unmatched,
+            ))
         }
     }
 
     /// Customizes the navigation tree given some rules provided through the
     /// doctave.yaml config.
     ///
-    /// Note that the config validates that any files/directories referenced
-    /// in the rules already exist, which is why we can reasonably confidently
-    /// unwrap some Nones here. The only case they would trip is if the files
-    /// got removed between the validation and building these rules, which is
-    /// a _very_ small window.
+    /// Rules that don't match a link are skipped, and their paths added to
+    /// `unmatched`.
     ///
     /// Note that in the case where an explicit path is provided, the link is
     /// not necessarily a direct child of its parent. It could be that links
     /// under a directory actually point to a parent's sibling, or to somewhere
     /// else in the tree.
-    fn customize(&self, rules: &[NavRule], default: &[Link]) -> Vec<Link> {
+    ///
+    /// The root directory is used to match the children of directories
+    /// against patterns, by the paths of their files.
+    fn customize(
+        &self,
+        rules: &[NavRule],
+        default: &[Link],
+        root: &Directory,
+        unmatched: &mut Vec<PathBuf>,
+    ) -> Vec<Link> {
         let mut links = vec![];
 
         for rule in rules {
             match rule {
-                NavRule::File(path) => links.push(
-                    self.find_matching_link(path, &default)
-                        .expect("No matching link found"),
-                ),
-                NavRule::Dir(path, dir_rule) => {
-                    let mut index_link = self
-                        .find_matching_link(path, &default)
-                        .expect("No matching link found");
+                NavRule::File(path, title) => match self.find_matching_link(path, default) {
+                    Some(link) => links.push(link.with_title(title)),
+                    None => unmatched.push(path.clone()),
+                },
+                NavRule::Dir(path, dir_rule, title) => {
+                    let mut index_link = match self.find_matching_link(path, default) {
+                        Some(link) => link.with_title(title),
+                        None => {
+                            unmatched.push(path.clone());
+                            continue;
+                        }
+                    };
 
                     match dir_rule {
                         // Don't include any children
@@ -61,27 +104,87 @@ impl<'a> Navigation<'a> {
                         Some(DirIncludeRule::WildCard) => links.push(index_link),
                         // Include only links that match the description
                         Some(DirIncludeRule::Explicit(nested_rules)) => {
-                            let children = self.customize(nested_rules, &default);
+                            let children = self.customize(nested_rules, &default, root, unmatched);
                             index_link.children = children;
                             links.push(index_link);
                         }
+                        // Include only links whose files match the patterns
+                        Some(DirIncludeRule::Matching { pattern, exclude }) => {
+                            let dir = self.relative_path(path);
+                            let children = std::mem::take(&mut index_link.children);
+
+                            index_link.children =
+                                self.matching_links(children, &dir, pattern, exclude, root);
+                            links.push(index_link);
+                        }
                     }
                 }
+                NavRule::External(url, title) => links.push(Link {
+                    path: url.clone(),
+                    title: title.clone(),
+                    kind: LinkKind::External,
+                    children: vec![],
+                }),
+                NavRule::Label(title, nested_rules) => links.push(Link {
+                    path: String::new(),
+                    title: title.clone(),
+                    kind: LinkKind::Label,
+                    children: self.customize(nested_rules, default, root, unmatched),
+                }),
             }
         }
 
         links
     }
 
+    /// Filters links to the ones whose files match the pattern, relative to
+    /// the directory. Matching links keep their matching children, while the
+    /// matching children of other links take their place.
+    fn matching_links(
+        &self,
+        links: Vec<Link>,
+        dir: &Path,
+        pattern: &Option<Pattern>,
+        exclude: &[Pattern],
+        root: &Directory,
+    ) -> Vec<Link> {
+        let docs = root.docs_recursive();
+        let mut matching = vec![];
+
+        for mut link in links {
+            let path = match docs
+                .iter()
+                .find(|doc| doc.uri_path() == link.path)
+                .and_then(|doc| doc.path.strip_prefix(dir).ok())
+            {
+                Some(path) => path,
+                None => continue,
+            };
+
+            if exclude.iter().any(|p| config::glob_matches(p, path)) {
+                continue;
+            }
+
+            let children = std::mem::take(&mut link.children);
+            let children = self.matching_links(children, dir, pattern, exclude, root);
+
+            match pattern {
+                Some(pattern) if !config::glob_matches(pattern, path) => matching.extend(children),
+                _ => {
+                    link.children = children;
+                    matching.push(link);
+                }
+            }
+        }
+
+        matching
+    }
+
     /// Matches a path provided in a NavRule to a Link. Recursively searches through
     /// the link children to find a match.
     fn find_matching_link(&self, path: &Path, links: &[Link]) -> Option<Link> {
-        let search_result = links.iter().find(|link| {
-            let mut without_docs_part = path.components();
-            let _ = without_docs_part.next();
-
-            link.path == Link::path_to_uri(without_docs_part.as_path())
-        });
+        let uri = Link::path_to_uri(&self.relative_path(path));
+        let search_result = links.iter().find(|link| link.path == uri);
 
         match search_result {
             Some(link) => Some(link.clone()),
@@ -96,35 +199,88 @@ impl<'a> Navigation<'a> {
             }
         }
     }
+
+    /// Turns a path provided in a NavRule into a path relative to the docs
+    /// directory of the site being built.
+    fn relative_path(&self, path: &Path) -> PathBuf {
+        let mut without_docs_part = match path.strip_prefix(self.config.docs_path()) {
+            Ok(relative) => relative.components(),
+            Err(_) => {
+                let mut components = path.components();
+                let _ = components.next();
+                components
+            }
+        };
+
+        // Paths point to the default language's files if each language has
+        // its own directory, but should match the pages in every language.
+        if self.config.language_directories() {
+            let default = self.config.default_language().map(|l| l.code.as_str());
+            let mut without_language_part = without_docs_part.clone();
+
+            if without_language_part
+                .next()
+                .and_then(|c| c.as_os_str().to_str())
+                == default
+            {
+                without_docs_part = without_language_part;
+            }
+        }
+
+        without_docs_part.as_path().to_path_buf()
+    }
 }
 
 impl From<&Directory> for Vec<Link> {
+    /// Lists the pages and subdirectories of a directory. They are sorted by
+    /// the `weight` in their frontmatter, or in the `README.md` for
+    /// directories, with lower weights first. Anything without a weight comes
+    /// after, and ties are sorted alphanumerically by title.
     fn from(dir: &Directory) -> Vec<Link> {
         let mut links = dir
             .docs
             .iter()
-            .map(|d| Link {
-                title: d.title().to_owned(),
-                path: d.uri_path(),
-                children: vec![],
+            .map(|d| {
+                let link = Link {
+                    title: d.title().to_owned(),
+                    path: d.uri_path(),
+                    kind: LinkKind::Page,
+                    children: vec![],
+                };
+
+                (d.frontmatter.weight, link)
             })
-            .filter(|l| l.path != dir.index().uri_path())
+            .filter(|(_, l)| l.path != dir.index().uri_path())
             .collect::<Vec<_>>();
 
         let mut children = dir
             .dirs
             .iter()
-            .map(|d| Link {
-                title: d.index().title().to_owned(),
-                path: d.index().uri_path(),
-                children: d.into(),
+            .map(|d| {
+                let link = Link {
+                    title: d.index().title().to_owned(),
+                    path: d.index().uri_path(),
+                    kind: LinkKind::Page,
+                    children: d.into(),
+                };
+
+                (d.index().frontmatter.weight, link)
             })
             .collect::<Vec<_>>();
 
         links.append(&mut children);
-        links.sort_by(|a, b| alphanumeric_sort::compare_str(&a.title, &b.title));
+        links.sort_by(|(weight_a, a), (weight_b, b)| {
+            let by_weight = match (weight_a, weight_b) {
+                (Some(weight_a), Some(weight_b)) => weight_a.cmp(weight_b),
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (None, None) => Ordering::Equal,
+            };
 
-        links
+            by_weight.then_with(|| alphanumeric_sort::compare_str(&a.title, &b.title))
+        });
+
+        links.into_iter().map(|(_, link)| link).collect()
     }
 }
 
@@ -132,9 +288,22 @@ impl From<&Directory> for Vec<Link> {
 pub struct Link {
     pub path: String,
     pub title: String,
+    pub kind: LinkKind,
     pub children: Vec<Link>,
 }
 
+/// What a link in the navigation points to.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LinkKind {
+    /// A page of the site
+    Page,
+    /// A page on another site
+    External,
+    /// Nothing. The link is a heading for its children.
+    Label,
+}
+
 impl Link {
     pub fn path_to_uri(path: &Path) -> String {
         let mut tmp = path.to_owned();
@@ -161,6 +330,33 @@ impl Link {
         format!("/{}", uri_path)
     }
 
+    /// Adds a prefix to the path of this link and all of its children.
+    /// Links to other sites and labels are left as they are.
+    pub fn with_prefix(self, prefix: &str) -> Link {
+        Link {
+            path: match self.kind {
+                LinkKind::Page => format!("{}{}", prefix, self.path),
+                LinkKind::External | LinkKind::Label => self.path,
+            },
+            title: self.title,
+            kind: self.kind,
+            children: self
+                .children
+                .into_iter()
+                .map(|child| child.with_prefix(prefix))
+                .collect(),
+        }
+    }
+
+    /// Replaces the title of the link, if a new one is given.
+    fn with_title(mut self, title: &Option<String>) -> Link {
+        if let Some(title) = title {
+            self.title = title.clone();
+        }
+
+        self
+    }
+
     pub fn path_to_uri_with_extension(path: &Path) -> String {
         let mut tmp = path.to_owned();
 
@@ -187,16 +383,17 @@ impl Link {
 #[cfg(test)]
 mod test {
     use super::*;
-    use std::collections::BTreeMap;
     use std::path::Path;
 
+    use crate::frontmatter::Frontmatter;
     use crate::Document;
 
     fn page(path: &str, name: &str) -> Document {
-        let mut frontmatter = BTreeMap::new();
-        frontmatter.insert("title".to_string(), name.to_string());
-
-        Document::new(Path::new(path), "Not important".to_string(), frontmatter)
+        Document::new(
+            Path::new(path),
+            "Not important".to_string(),
+            Frontmatter::with_title(name),
+        )
     }
 
     fn config(yaml: Option<&str>) -> Config {
@@ -228,25 +425,29 @@ mod test {
         let navigation = Navigation::new(&config);
 
         assert_eq!(
-            navigation.build_for(&root),
+            navigation.build_for(&root, &root).unwrap(),
             vec![
                 Link {
                     path: String::from("/child"),
                     title: String::from("Nested Root"),
+                    kind: LinkKind::Page,
                     children: vec![Link {
                         path: String::from("/child/three"),
                         title: String::from("Three"),
+                        kind: LinkKind::Page,
                         children: vec![]
                     }]
                 },
                 Link {
                     path: String::from("/one"),
                     title: String::from("One"),
+                    kind: LinkKind::Page,
                     children: vec![]
                 },
                 Link {
                     path: String::from("/two"),
                     title: String::from("Two"),
+                    kind: LinkKind::Page,
                     children: vec![]
                 },
             ]
@@ -292,35 +493,41 @@ mod test {
         let navigation = Navigation::new(&config);
 
         assert_eq!(
-            navigation.build_for(&root),
+            navigation.build_for(&root, &root).unwrap(),
             vec![
                 Link {
                     path: String::from("/002"),
                     title: String::from("11"),
+                    kind: LinkKind::Page,
                     children: vec![],
                 },
                 Link {
                     path: String::from("/child"),
                     title: String::from("Index"),
+                    kind: LinkKind::Page,
                     children: vec![
                         Link {
                             path: String::from("/child/004"),
                             title: String::from("11"),
+                            kind: LinkKind::Page,
                             children: vec![],
                         },
                         Link {
                             path: String::from("/child/002"),
                             title: String::from("22"),
+                            kind: LinkKind::Page,
                             children: vec![],
                         },
                         Link {
                             path: String::from("/child/003"),
                             title: String::from("AA"),
+                            kind: LinkKind::Page,
                             children: vec![],
                         },
                         Link {
                             path: String::from("/child/001"),
                             title: String::from("BB"),
+                            kind: LinkKind::Page,
                             children: vec![],
                         },
                     ]
@@ -328,25 +535,30 @@ mod test {
                 Link {
                     path: String::from("/child2"),
                     title: String::from("Index"),
+                    kind: LinkKind::Page,
                     children: vec![
                         Link {
                             path: String::from("/child2/001"),
                             title: String::from("123"),
+                            kind: LinkKind::Page,
                             children: vec![]
                         },
                         Link {
                             path: String::from("/child2/002"),
                             title: String::from("aa"),
+                            kind: LinkKind::Page,
                             children: vec![]
                         },
                         Link {
                             path: String::from("/child2/004"),
                             title: String::from("bb"),
+                            kind: LinkKind::Page,
                             children: vec![]
                         },
                         Link {
                             path: String::from("/child2/003"),
                             title: String::from("cc"),
+                            kind: LinkKind::Page,
                             children: vec![]
                         },
                     ]
@@ -354,12 +566,59 @@ mod test {
                 Link {
                     path: String::from("/001"),
                     title: String::from("bb"),
+                    kind: LinkKind::Page,
                     children: vec![],
                 },
             ],
         )
     }
 
+    #[test]
+    fn sorting_by_weight() {
+        let config = config(None);
+        let weighted = |path: &str, name: &str, weight: i64| {
+            let mut doc = page(path, name);
+            doc.frontmatter.weight = Some(weight);
+            doc
+        };
+
+        let root = Directory {
+            path: PathBuf::from("docs"),
+            docs: vec![
+                page("README.md", "Getting Started"),
+                page("faq.md", "FAQ"),
+                weighted("tutorial.md", "Tutorial", 1),
+                weighted("troubleshooting.md", "Troubleshooting", 20),
+                weighted("installing.md", "Installing", -5),
+                weighted("upgrading.md", "Upgrading", 20),
+            ],
+            dirs: vec![Directory {
+                path: PathBuf::from("docs").join("child"),
+                docs: vec![weighted("child/README.md", "Guides", 10)],
+                dirs: vec![],
+            }],
+        };
+
+        let navigation = Navigation::new(&config);
+
+        assert_eq!(
+            navigation
+                .build_for(&root, &root)
+                .unwrap()
+                .iter()
+                .map(|l| l.title.as_str())
+                .collect::<Vec<_>>(),
+            vec![
+                "Installing",
+                "Tutorial",
+                "Guides",
+                "Troubleshooting",
+                "Upgrading",
+                "FAQ"
+            ]
+        )
+    }
+
     #[test]
     fn manual_menu_simple() {
         let root = Directory {
@@ -380,8 +639,12 @@ mod test {
         };
 
         let rules = vec![
-            NavRule::File(PathBuf::from("docs/one.md")),
-            NavRule::Dir(PathBuf::from("docs/child"), Some(DirIncludeRule::WildCard)),
+            NavRule::File(PathBuf::from("docs/one.md"), None),
+            NavRule::Dir(
+                PathBuf::from("docs/child"),
+                Some(DirIncludeRule::WildCard),
+                None,
+            ),
         ];
 
         let config = config(None);
@@ -389,19 +652,22 @@ mod test {
         let links: Vec<Link> = (&root).into();
 
         assert_eq!(
-            navigation.customize(&rules, &links),
+            navigation.customize(&rules, &links, &root, &mut vec![]),
             vec![
                 Link {
                     path: String::from("/one"),
                     title: String::from("One"),
+                    kind: LinkKind::Page,
                     children: vec![],
                 },
                 Link {
                     path: String::from("/child"),
                     title: String::from("Nested Root"),
+                    kind: LinkKind::Page,
                     children: vec![Link {
                         path: String::from("/child/three"),
                         title: String::from("Three"),
+                        kind: LinkKind::Page,
                         children: vec![],
                     },],
                 },
@@ -436,7 +702,7 @@ mod test {
         };
 
         let rules = vec![
-            NavRule::File(PathBuf::from("docs").join("one.md")),
+            NavRule::File(PathBuf::from("docs").join("one.md"), None),
             NavRule::Dir(
                 PathBuf::from("docs").join("child"),
                 Some(DirIncludeRule::Explicit(vec![NavRule::Dir(
@@ -446,8 +712,11 @@ mod test {
                             .join("child")
                             .join("nested")
                             .join("four.md"),
+                        None,
                     )])),
+                    None,
                 )])),
+                None,
             ),
         ];
 
@@ -456,22 +725,26 @@ mod test {
         let links: Vec<Link> = (&root).into();
 
         assert_eq!(
-            navigation.customize(&rules, &links),
+            navigation.customize(&rules, &links, &root, &mut vec![]),
             vec![
                 Link {
                     path: String::from("/one"),
                     title: String::from("One"),
+                    kind: LinkKind::Page,
                     children: vec![]
                 },
                 Link {
                     path: String::from("/child"),
                     title: String::from("Nested Root"),
+                    kind: LinkKind::Page,
                     children: vec![Link {
                         path: String::from("/child/nested"),
                         title: String::from("Nested Root"),
+                        kind: LinkKind::Page,
                         children: vec![Link {
                             path: String::from("/child/nested/four"),
                             title: String::from("Four"),
+                            kind: LinkKind::Page,
                             children: vec![]
                         },]
                     }]
@@ -497,6 +770,7 @@ mod test {
 
         let rules = vec![NavRule::File(
             PathBuf::from("docs").join("child").join("three.md"),
+            None,
         )];
 
         let config = config(None);
@@ -504,10 +778,11 @@ mod test {
         let links: Vec<Link> = (&root).into();
 
         assert_eq!(
-            navigation.customize(&rules, &links),
+            navigation.customize(&rules, &links, &root, &mut vec![]),
             vec![Link {
                 path: String::from("/child/three"),
                 title: String::from("Three"),
+                kind: LinkKind::Page,
                 children: vec![]
             },]
         );
@@ -529,7 +804,9 @@ mod test {
             PathBuf::from("docs").join("child"),
             Some(DirIncludeRule::Explicit(vec![NavRule::File(
                 PathBuf::from("docs").join("one.md"),
+                None,
             )])),
+            None,
         )];
 
         let config = config(None);
@@ -537,16 +814,247 @@ mod test {
         let links: Vec<Link> = (&root).into();
 
         assert_eq!(
-            navigation.customize(&rules, &links),
+            navigation.customize(&rules, &links, &root, &mut vec![]),
             vec![Link {
                 path: String::from("/child"),
                 title: String::from("Nested Root"),
+                kind: LinkKind::Page,
                 children: vec![Link {
                     path: String::from("/one"),
                     title: String::from("One"),
+                    kind: LinkKind::Page,
                     children: vec![],
                 }]
             },]
         );
     }
+
+    #[test]
+    fn manual_menu_external_links_labels_and_titles() {
+        let root = Directory {
+            path: PathBuf::from("docs"),
+            docs: vec![page("README.md", "Getting Started"), page("one.md", "One")],
+            dirs: vec![Directory {
+                path: PathBuf::from("docs").join("child"),
+                docs: vec![
+                    page("child/README.md", "Nested Root"),
+                    page("child/three.md", "Three"),
+                ],
+                dirs: vec![],
+            }],
+        };
+
+        let rules = vec![
+            NavRule::File(
+                PathBuf::from("docs").join("one.md"),
+                Some(String::from("The First")),
+            ),
+            NavRule::Label(
+                String::from("Elsewhere"),
+                vec![
+                    NavRule::External(String::from("https://example.com"), String::from("Example")),
+                    NavRule::Dir(
+                        PathBuf::from("docs").join("child"),
+                        None,
+                        Some(String::from("Child")),
+                    ),
+                ],
+            ),
+        ];
+
+        let config = config(None);
+        let navigation = Navigation::new(&config);
+        let links: Vec<Link> = (&root).into();
+
+        assert_eq!(
+            navigation.customize(&rules, &links, &root, &mut vec![]),
+            vec![
+                Link {
+                    path: String::from("/one"),
+                    title: String::from("The First"),
+                    kind: LinkKind::Page,
+                    children: vec![],
+                },
+                Link {
+                    path: String::new(),
+                    title: String::from("Elsewhere"),
+                    kind: LinkKind::Label,
+                    children: vec![
+                        Link {
+                            path: String::from("https://example.com"),
+                            title: String::from("Example"),
+                            kind: LinkKind::External,
+                            children: vec![],
+                        },
+                        Link {
+                            path: String::from("/child"),
+                            title: String::from("Child"),
+                            kind: LinkKind::Page,
+                            children: vec![],
+                        },
+                    ],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn prefixes_only_pages() {
+        let link = Link {
+            path: String::new(),
+            title: String::from("Elsewhere"),
+            kind: LinkKind::Label,
+            children: vec![
+                Link {
+                    path: String::from("https://example.com"),
+                    title: String::from("Example"),
+                    kind: LinkKind::External,
+                    children: vec![],
+                },
+                Link {
+                    path: String::from("/one"),
+                    title: String::from("One"),
+                    kind: LinkKind::Page,
+                    children: vec![],
+                },
+            ],
+        }
+        .with_prefix("/v1");
+
+        assert_eq!(link.path, "");
+        assert_eq!(link.children[0].path, "https://example.com");
+        assert_eq!(link.children[1].path, "/v1/one");
+    }
+
+    #[test]
+    fn manual_menu_directory_patterns() {
+        let root = Directory {
+            path: PathBuf::from("docs"),
+            docs: vec![page("README.md", "Getting Started")],
+            dirs: vec![Directory {
+                path: PathBuf::from("docs").join("child"),
+                docs: vec![
+                    page("child/README.md", "Nested Root"),
+                    page("child/a-howto.md", "A How-To"),
+                    page("child/three.md", "Three"),
+                ],
+                dirs: vec![Directory {
+                    path: PathBuf::from("docs").join("child").join("nested"),
+                    docs: vec![
+                        page("child/nested/README.md", "Nested"),
+                        page("child/nested/b-howto.md", "B How-To"),
+                    ],
+                    dirs: vec![],
+                }],
+            }],
+        };
+
+        let link = |path: &str, title: &str, children: Vec<Link>| Link {
+            path: String::from(path),
+            title: String::from(title),
+            kind: LinkKind::Page,
+            children,
+        };
+
+        let rules = vec![
+            NavRule::Dir(
+                PathBuf::from("docs").join("child"),
+                Some(DirIncludeRule::Matching {
+                    pattern: Some(Pattern::new("**/*-howto.md").unwrap()),
+                    exclude: vec![],
+                }),
+                None,
+            ),
+            NavRule::Dir(
+                PathBuf::from("docs").join("child"),
+                Some(DirIncludeRule::Matching {
+                    pattern: None,
+                    exclude: vec![Pattern::new("*-howto.md").unwrap()],
+                }),
+                None,
+            ),
+        ];
+
+        let config = config(None);
+        let navigation = Navigation::new(&config);
+        let links: Vec<Link> = (&root).into();
+
+        assert_eq!(
+            navigation.customize(&rules, &links, &root, &mut vec![]),
+            vec![
+                link(
+                    "/child",
+                    "Nested Root",
+                    vec![
+                        link("/child/a-howto", "A How-To", vec![]),
+                        link("/child/nested/b-howto", "B How-To", vec![]),
+                    ]
+                ),
+                link(
+                    "/child",
+                    "Nested Root",
+                    vec![
+                        link(
+                            "/child/nested",
+                            "Nested",
+                            vec![link("/child/nested/b-howto", "B How-To", vec![])]
+                        ),
+                        link("/child/three", "Three", vec![]),
+                    ]
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn unmatched_rules() {
+        // TODO: Make not rely on our docs
+        let yaml = indoc! {"
+            ---
+            title: My project
+            navigation:
+              - path: docs/tutorial.md
+              - path: docs/installing.md
+              - path: docs/features
+        "};
+        let config = Config::from_yaml_str(Path::new(""), yaml).unwrap();
+        let navigation = Navigation::new(&config);
+
+        let pages = Directory {
+            path: PathBuf::from("docs"),
+            docs: vec![
+                page("README.md", "Getting Started"),
+                page("tutorial.md", "Tutorial"),
+            ],
+            dirs: vec![],
+        };
+
+        let error = navigation.build_for(&pages, &pages).unwrap_err();
+
+        assert_eq!(
+            format!("{}", error),
+            "Could not find pages for these entries in navigation:\n\n    \
+             doctave.yaml:5:11  docs/installing.md\n    \
+             doctave.yaml:6:11  docs/features"
+        );
+
+        // Documents left out of the build, like drafts, are skipped
+        let mut sources = pages.clone();
+        sources.docs.push(page("installing.md", "Installing"));
+        sources.dirs.push(Directory {
+            path: PathBuf::from("docs").join("features"),
+            docs: vec![page("features/README.md", "Features")],
+            dirs: vec![],
+        });
+
+        assert_eq!(
+            navigation
+                .build_for(&pages, &sources)
+                .unwrap()
+                .iter()
+                .map(|l| l.title.as_str())
+                .collect::<Vec<_>>(),
+            vec!["Tutorial"]
+        );
+    }
 }
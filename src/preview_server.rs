@@ -1,24 +1,68 @@
+use std::collections::HashMap;
 use std::ffi::OsStr;
 use std::fs::File;
+use std::io::Cursor;
 use std::net::SocketAddr;
 use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
 
 use ascii::AsciiString;
 use bunt::termcolor::{ColorChoice, StandardStream};
 use tiny_http::{Request, Response, Server};
 
+use crate::site::Site;
+use crate::templates::Templates;
+
+/// Where the search API is served, under the URI prefix of each version
+/// and language of the site
+static SEARCH_API_PATH: &str = "/api/search";
+
+/// How many results the search API returns, unless asked for another number
+static DEFAULT_SEARCH_LIMIT: usize = 20;
+
+/// Shared handle to the error from the latest build, if it failed.
+pub type BuildError = Arc<RwLock<Option<String>>>;
+
 pub struct PreviewServer {
     color: bool,
     addr: SocketAddr,
     out_dir: PathBuf,
+    build_error: BuildError,
+    templates: Templates,
+    base_path: String,
+    uri_prefix: String,
+    site: Arc<Site>,
 }
 
 impl PreviewServer {
-    pub fn new<P: Into<PathBuf>>(addr: &str, out_dir: P, color: bool) -> Self {
+    /// Creates a new server for the given output directory.
+    ///
+    /// The site is served under `base_path`, like it will be when hosted.
+    ///
+    /// While `build_error` contains an error, pages are replaced with an
+    /// error page describing what went wrong. The error page loads its
+    /// assets from under `uri_prefix`.
+    ///
+    /// Searches made through `/api/search` use the search index from the
+    /// latest build of `site`.
+    pub fn new<P: Into<PathBuf>>(
+        addr: &str,
+        out_dir: P,
+        color: bool,
+        build_error: BuildError,
+        base_path: String,
+        uri_prefix: String,
+        site: Arc<Site>,
+    ) -> Self {
         PreviewServer {
             color,
             addr: addr.parse().expect("invalid address for preview server"),
             out_dir: out_dir.into(),
+            build_error,
+            templates: Templates::builtin(),
+            base_path,
+            uri_prefix,
+            site,
         }
     }
 
@@ -35,8 +79,9 @@ impl PreviewServer {
 
             bunt::writeln!(
                 stdout,
-                "Server running on {$bold}http://{}/{/$}\n",
-                self.addr
+                "Server running on {$bold}http://{}{}/{/$}\n",
+                self.addr,
+                self.base_path
             )
             .unwrap();
         }
@@ -44,31 +89,45 @@ impl PreviewServer {
         for request in server.incoming_requests() {
             pool.scoped(|scope| {
                 scope.execute(|| {
-                    handle_request(request, self.out_dir.clone());
+                    handle_request(
+                        request,
+                        self.out_dir.clone(),
+                        &self.build_error,
+                        &self.templates,
+                        &self.base_path,
+                        &self.uri_prefix,
+                        &self.site,
+                    );
                 });
             })
         }
     }
 }
 
-fn handle_request(request: Request, out_dir: PathBuf) {
-    let result = {
-        let uri = request.url().parse::<http::Uri>().unwrap();
-
-        match resolve_file(&Path::new(uri.path()), &out_dir) {
-            Some((f, None)) => {
-                request.respond(Response::from_file(File::open(f).unwrap()).with_status_code(200))
-            }
-            Some((f, Some(content_type))) => request.respond(
-                Response::from_file(File::open(f).unwrap())
-                    .with_status_code(200)
-                    .with_header(tiny_http::Header {
-                        field: "Content-Type".parse().unwrap(),
-                        value: AsciiString::from_ascii(content_type).unwrap(),
-                    }),
-            ),
-            None => request.respond(Response::new_empty(tiny_http::StatusCode(404))),
-        }
+fn handle_request(
+    request: Request,
+    out_dir: PathBuf,
+    build_error: &BuildError,
+    templates: &Templates,
+    base_path: &str,
+    uri_prefix: &str,
+    site: &Site,
+) {
+    let uri = request.url().parse::<http::Uri>().unwrap();
+
+    let result = match strip_base_path(uri.path(), base_path) {
+        Some(path) => match search_api_prefix(uri.path(), path, &out_dir, site) {
+            Some(prefix) => serve_search(request, prefix, uri.query().unwrap_or_default(), site),
+            None => serve_file(request, path, &out_dir, build_error, templates, uri_prefix),
+        },
+        // Point the root at the site, when it is served under a base path
+        None if uri.path() == "/" => request.respond(
+            Response::new_empty(tiny_http::StatusCode(302)).with_header(tiny_http::Header {
+                field: "Location".parse().unwrap(),
+                value: AsciiString::from_ascii(format!("{}/", base_path)).unwrap(),
+            }),
+        ),
+        None => request.respond(Response::new_empty(tiny_http::StatusCode(404))),
     };
 
     match result {
@@ -78,6 +137,178 @@ fn handle_request(request: Request, out_dir: PathBuf) {
     }
 }
 
+fn serve_file(
+    request: Request,
+    path: &str,
+    out_dir: &Path,
+    build_error: &BuildError,
+    templates: &Templates,
+    uri_prefix: &str,
+) -> std::io::Result<()> {
+    let resolved = resolve_file(Path::new(path), out_dir);
+
+    // Assets are still served as usual, so the error page can be styled
+    // and reload itself once the error has been fixed.
+    let is_page = resolved
+        .as_ref()
+        .map(|(f, _)| f.extension() == Some(OsStr::new("html")))
+        .unwrap_or(true);
+    let build_error = build_error.read().unwrap().clone();
+
+    match (resolved, build_error) {
+        (_, Some(message)) if is_page => {
+            request.respond(error_page(&message, templates, uri_prefix))
+        }
+        (Some((f, None)), _) => {
+            request.respond(Response::from_file(File::open(f).unwrap()).with_status_code(200))
+        }
+        (Some((f, Some(content_type))), _) => request.respond(
+            Response::from_file(File::open(f).unwrap())
+                .with_status_code(200)
+                .with_header(tiny_http::Header {
+                    field: "Content-Type".parse().unwrap(),
+                    value: AsciiString::from_ascii(content_type).unwrap(),
+                }),
+        ),
+        (None, _) => request.respond(Response::new_empty(tiny_http::StatusCode(404))),
+    }
+}
+
+/// The URI prefix of the part of the site a request to the search API is
+/// for. `None` if the request is for anything else, including pages that
+/// happen to be at the same path as the API.
+fn search_api_prefix<'a>(
+    uri_path: &'a str,
+    path: &str,
+    out_dir: &Path,
+    site: &Site,
+) -> Option<&'a str> {
+    let prefix = uri_path.strip_suffix(SEARCH_API_PATH)?;
+
+    if !site.search_uri_prefixes().iter().any(|p| p == prefix)
+        || resolve_file(Path::new(path), out_dir).is_some()
+    {
+        return None;
+    }
+
+    Some(prefix)
+}
+
+/// Searches the part of the site under a URI prefix, and responds with the
+/// results as JSON.
+///
+/// The query string has the search in `q`, and optionally the number of
+/// results to return in `limit`.
+fn serve_search(
+    request: Request,
+    uri_prefix: &str,
+    query: &str,
+    site: &Site,
+) -> std::io::Result<()> {
+    let params = query_params(query);
+    let search = params.get("q").map(|q| q.as_str()).unwrap_or_default();
+    let limit = params
+        .get("limit")
+        .and_then(|limit| limit.parse().ok())
+        .unwrap_or(DEFAULT_SEARCH_LIMIT);
+
+    let results = match site.search(uri_prefix, search, limit) {
+        Some(results) => results,
+        None => return request.respond(Response::new_empty(tiny_http::StatusCode(404))),
+    };
+
+    let body = serde_json::json!({
+        "query": search,
+        "results": results,
+    });
+
+    request.respond(
+        Response::from_data(body.to_string().into_bytes())
+            .with_status_code(200)
+            .with_header(tiny_http::Header {
+                field: "Content-Type".parse().unwrap(),
+                value: AsciiString::from_ascii("application/json").unwrap(),
+            }),
+    )
+}
+
+/// Parses a query string, like `q=getting+started&limit=5`.
+fn query_params(query: &str) -> HashMap<String, String> {
+    query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let mut parts = pair.splitn(2, '=');
+            let key = parts.next().unwrap_or_default();
+            let value = parts.next().unwrap_or_default();
+
+            (decode_query_component(key), decode_query_component(value))
+        })
+        .collect()
+}
+
+/// Decodes `+` and percent-encoded characters in a part of a query string.
+fn decode_query_component(text: &str) -> String {
+    let mut bytes = vec![];
+    let mut i = 0;
+
+    while i < text.len() {
+        match text.as_bytes()[i] {
+            b'+' => bytes.push(b' '),
+            b'%' => match text
+                .get(i + 1..i + 3)
+                .and_then(|h| u8::from_str_radix(h, 16).ok())
+            {
+                Some(byte) => {
+                    bytes.push(byte);
+                    i += 2;
+                }
+                None => bytes.push(b'%'),
+            },
+            byte => bytes.push(byte),
+        }
+        i += 1;
+    }
+
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+fn error_page(message: &str, templates: &Templates, uri_prefix: &str) -> Response<Cursor<Vec<u8>>> {
+    let mut data = serde_json::Map::new();
+    data.insert(
+        "message".to_string(),
+        serde_json::Value::String(message.to_string()),
+    );
+    data.insert(
+        "uri_prefix".to_string(),
+        serde_json::Value::String(uri_prefix.to_string()),
+    );
+
+    let body = templates
+        .render("error", &data)
+        .unwrap_or_else(|_| message.to_string());
+
+    Response::from_string(body)
+        .with_status_code(500)
+        .with_header(tiny_http::Header {
+            field: "Content-Type".parse().unwrap(),
+            value: AsciiString::from_ascii("text/html; charset=utf8").unwrap(),
+        })
+}
+
+/// The path of the request inside the site, if it is under the base path.
+fn strip_base_path<'a>(path: &'a str, base_path: &str) -> Option<&'a str> {
+    if base_path.is_empty() {
+        return Some(path);
+    }
+
+    match path.strip_prefix(base_path) {
+        Some("") => Some("/"),
+        Some(rest) if rest.starts_with('/') => Some(rest),
+        _ => None,
+    }
+}
+
 fn resolve_file(path: &Path, out_dir: &Path) -> Option<(PathBuf, Option<&'static str>)> {
     if path.to_str().map(|s| s.contains("..")).unwrap_or(false) {
         return None;
@@ -126,3 +357,30 @@ fn content_type_for(extension: Option<&OsStr>) -> Option<&'static str> {
         None => None,
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn strips_base_path() {
+        assert_eq!(strip_base_path("/tutorial", ""), Some("/tutorial"));
+        assert_eq!(strip_base_path("/project", "/project"), Some("/"));
+        assert_eq!(
+            strip_base_path("/project/tutorial", "/project"),
+            Some("/tutorial")
+        );
+        assert_eq!(strip_base_path("/projects/tutorial", "/project"), None);
+        assert_eq!(strip_base_path("/tutorial", "/project"), None);
+    }
+
+    #[test]
+    fn parses_query_strings() {
+        let params = query_params("q=getting+started%21&limit=5&empty&%E2%9C%93=%ZZ");
+
+        assert_eq!(params["q"], "getting started!");
+        assert_eq!(params["limit"], "5");
+        assert_eq!(params["empty"], "");
+        assert_eq!(params["✓"], "%ZZ");
+    }
+}
@@ -0,0 +1,307 @@
+use std::collections::HashSet;
+use std::ffi::OsStr;
+use std::path::{Component, Path, PathBuf};
+
+use crate::check;
+use crate::config::Config;
+use crate::navigation::Link;
+
+/// Rewrites the links in rendered pages into links relative to the page,
+/// pointing straight at the files they link to. This way the site works
+/// without a server, like when opened from disk.
+pub struct RelativeLinks {
+    site_prefix: String,
+    /// URI prefixes of the versions and languages of the site, longest
+    /// first, without the site prefix
+    part_prefixes: Vec<String>,
+    /// URI paths of the pages that are the index of a directory, like
+    /// `/features`, without any prefix
+    dir_indices: HashSet<String>,
+}
+
+impl RelativeLinks {
+    /// Creates a rewriter for one part of the site. Links to other versions
+    /// and languages are assumed to have the same directories.
+    pub fn new(config: &Config, dir_indices: HashSet<String>) -> Self {
+        let versions = match config.versions() {
+            [] => vec![String::new()],
+            versions => versions.iter().map(|v| v.uri_prefix()).collect(),
+        };
+
+        let mut part_prefixes = versions
+            .iter()
+            .flat_map(|version| {
+                let mut prefixes = vec![version.clone()];
+                prefixes.extend(
+                    config
+                        .languages()
+                        .iter()
+                        .map(move |lang| format!("{}/{}", version, lang.code)),
+                );
+                prefixes
+            })
+            .filter(|prefix| !prefix.is_empty())
+            .collect::<Vec<_>>();
+        part_prefixes.sort_by_key(|prefix| std::cmp::Reverse(prefix.len()));
+
+        RelativeLinks {
+            site_prefix: config.site_uri_prefix().to_string(),
+            part_prefixes,
+            dir_indices,
+        }
+    }
+
+    /// Rewrites the links in the HTML of a page. The page's path is the file
+    /// it is written to, relative to the root of the site.
+    pub fn rewrite(&self, html: &str, page: &Path) -> String {
+        static ATTRIBUTES: &[&str] = &[
+            " href=\"",
+            " src=\"",
+            " value=\"",
+            " href='",
+            " src='",
+            " value='",
+        ];
+
+        let mut result = String::with_capacity(html.len());
+        let mut rest = html;
+
+        while let Some((start, attribute)) = ATTRIBUTES
+            .iter()
+            .filter_map(|a| rest.find(a).map(|start| (start, a)))
+            .min()
+        {
+            let value_start = start + attribute.len();
+            result.push_str(&rest[..value_start]);
+            rest = &rest[value_start..];
+
+            let quote = attribute.chars().last().unwrap();
+            let value_end = rest.find(quote).unwrap_or(rest.len());
+            let value = &rest[..value_end];
+
+            match root_relative_uri(attribute, value, page) {
+                Some(uri) => result.push_str(&self.relative_uri(&uri, page)),
+                None => result.push_str(value),
+            }
+
+            rest = &rest[value_end..];
+        }
+
+        result.push_str(rest);
+        result
+    }
+
+    /// Turns a root-relative URI into one relative to the page.
+    fn relative_uri(&self, uri: &str, page: &Path) -> String {
+        let split = uri.find(&['?', '#'][..]).unwrap_or(uri.len());
+        let (path, suffix) = uri.split_at(split);
+
+        format!("{}{}", relative_path(page, &self.target_file(path)), suffix)
+    }
+
+    /// The file a root-relative URI path points to, relative to the root of
+    /// the site.
+    pub fn target_file(&self, path: &str) -> PathBuf {
+        let path = strip_uri_prefix(path, &self.site_prefix).unwrap_or(path);
+
+        let file = path.trim_start_matches('/');
+
+        if path.ends_with('/') || file.is_empty() {
+            return Path::new(file).join("index.html");
+        }
+
+        let has_extension = file
+            .rsplit('/')
+            .next()
+            .map(|name| name.contains('.'))
+            .unwrap_or(false);
+        if has_extension {
+            return PathBuf::from(file);
+        }
+
+        let within_part = self
+            .part_prefixes
+            .iter()
+            .find_map(|prefix| strip_uri_prefix(path, prefix))
+            .unwrap_or(path);
+
+        if within_part == "/" || self.dir_indices.contains(within_part) {
+            Path::new(file).join("index.html")
+        } else {
+            PathBuf::from(format!("{}.html", file))
+        }
+    }
+}
+
+/// The root-relative URI a link on a page points to. Links relative to the
+/// page are resolved against its directory, the same way the link checker
+/// does, and links to Markdown files point to the page they are rendered
+/// into. Returns None for links to other sites, anchors on the same page,
+/// and values of form fields.
+fn root_relative_uri(attribute: &str, value: &str, page: &Path) -> Option<String> {
+    // Leave protocol-relative URLs like //example.com alone
+    if value.starts_with("//") {
+        return None;
+    }
+    if value.starts_with('/') {
+        return Some(value.to_string());
+    }
+
+    let has_scheme = value
+        .split(&['/', '?', '#'][..])
+        .next()
+        .map(|first| first.contains(':'))
+        .unwrap_or(false);
+    if attribute.contains("value")
+        || value.is_empty()
+        || value.starts_with(&['#', '?'][..])
+        || has_scheme
+    {
+        return None;
+    }
+
+    let split = value.find(&['?', '#'][..]).unwrap_or(value.len());
+    let (path, suffix) = value.split_at(split);
+
+    let resolved = check::resolve(page.parent().unwrap_or_else(|| Path::new("")), path)?;
+    let uri = if resolved.extension() == Some(OsStr::new("md")) {
+        check::page_uri(&resolved)
+    } else {
+        Link::path_to_uri_with_extension(&resolved)
+    };
+
+    Some(format!("{}{}", uri, suffix))
+}
+
+/// The rest of a URI path after a prefix of whole segments.
+fn strip_uri_prefix<'a>(path: &'a str, prefix: &str) -> Option<&'a str> {
+    match path.strip_prefix(prefix) {
+        Some("") => Some("/"),
+        Some(rest) if rest.starts_with('/') => Some(rest),
+        _ => None,
+    }
+}
+
+/// The path from one file to another, both relative to the same
+/// directory, with forward slashes.
+pub fn relative_path(from: &Path, to: &Path) -> String {
+    let from_dir = from
+        .parent()
+        .map(|p| p.components().collect::<Vec<_>>())
+        .unwrap_or_default();
+    let to = to.components().collect::<Vec<_>>();
+
+    let common = from_dir.iter().zip(&to).take_while(|(a, b)| a == b).count();
+
+    let mut segments = vec![String::from(".."); from_dir.len() - common];
+    segments.extend(to[common..].iter().filter_map(|c| match c {
+        Component::Normal(name) => Some(name.to_string_lossy().to_string()),
+        _ => None,
+    }));
+
+    segments.join("/")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn rewriter(yaml: &str, dir_indices: &[&str]) -> RelativeLinks {
+        let config = Config::from_yaml_str(Path::new(""), yaml).unwrap();
+
+        RelativeLinks::new(
+            &config,
+            dir_indices.iter().map(|uri| uri.to_string()).collect(),
+        )
+    }
+
+    #[test]
+    fn paths_between_files() {
+        let page = Path::new("features").join("index.html");
+
+        assert_eq!(
+            relative_path(&page, Path::new("index.html")),
+            "../index.html"
+        );
+        assert_eq!(
+            relative_path(&page, &Path::new("features").join("one.html")),
+            "one.html"
+        );
+        assert_eq!(
+            relative_path(Path::new("index.html"), &Path::new("assets").join("app.js")),
+            "assets/app.js"
+        );
+    }
+
+    #[test]
+    fn links_point_at_files() {
+        let links = rewriter("---\ntitle: Test\n", &["/features"]);
+        let page = Path::new("features").join("one.html");
+
+        assert_eq!(
+            links.rewrite(
+                "<a href=\"/\">Home</a> <a href='/features'>Features</a> <a href=\"/tutorial#setup\">",
+                &page
+            ),
+            "<a href=\"../index.html\">Home</a> <a href='index.html'>Features</a> <a href=\"../tutorial.html#setup\">"
+        );
+        assert_eq!(
+            links.rewrite("<img src=\"/assets/logo.png?v=1\">", &page),
+            "<img src=\"../assets/logo.png?v=1\">"
+        );
+    }
+
+    #[test]
+    fn relative_links_point_at_files() {
+        let links = rewriter("---\ntitle: Test\n", &["/features"]);
+        let page = Path::new("features").join("one.html");
+
+        assert_eq!(
+            links.rewrite(
+                "<a href=\"two\"></a><a href=\"./two.md#setup\"></a><a href=\"README.md\"></a>",
+                &page
+            ),
+            "<a href=\"two.html\"></a><a href=\"two.html#setup\"></a><a href=\"index.html\"></a>"
+        );
+        assert_eq!(
+            links.rewrite(
+                "<a href=\"features\"></a><a href=\"features/one.md\"></a><a href=\"../x\"></a>",
+                Path::new("index.html")
+            ),
+            "<a href=\"features/index.html\"></a><a href=\"features/one.html\"></a><a href=\"../x\"></a>"
+        );
+        assert_eq!(
+            links.rewrite(
+                "<a href=\"../tutorial\"></a><img src=\"diagram.png\">",
+                &page
+            ),
+            "<a href=\"../tutorial.html\"></a><img src=\"diagram.png\">"
+        );
+    }
+
+    #[test]
+    fn leaves_other_links_alone() {
+        let links = rewriter("---\ntitle: Test\n", &[]);
+        let html = "<a href=\"https://example.com\"></a><a href=\"//cdn.com/x.js\"></a><a href=\"#top\"></a><a href=\"mailto:a@b.com\"></a><input value='0'>";
+
+        assert_eq!(links.rewrite(html, Path::new("index.html")), html);
+    }
+
+    #[test]
+    fn links_between_versions_and_languages() {
+        let links = rewriter(
+            "---\ntitle: Test\nbase_path: /project\nversions:\n  - name: v2\n  - name: v1\nlanguages:\n  - code: en\n  - code: de\n",
+            &["/features"],
+        );
+        let page = Path::new("v2").join("en").join("index.html");
+
+        assert_eq!(
+            links.rewrite("<option value='/project/v1/de/features'>", &page),
+            "<option value='../../v1/de/features/index.html'>"
+        );
+        assert_eq!(
+            links.rewrite("<option value='/project/v1/en'>", &page),
+            "<option value='../../v1/en/index.html'>"
+        );
+    }
+}
@@ -0,0 +1,987 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::ops::Bound;
+use std::path::PathBuf;
+
+use glob::Pattern;
+use serde::{Deserialize, Serialize};
+
+use crate::stemmer;
+use crate::Heading;
+
+/// How many characters of a section to show in search results
+static EXCERPT_LENGTH: usize = 160;
+
+/// How many characters of text to show before the first match in a search
+/// result from the preview server
+static SNIPPET_CONTEXT: usize = 40;
+
+/// How many results to put in each file of a sharded index
+static DOCS_PER_FILE: usize = 500;
+
+/// Elements that don't separate words when their tags are removed
+static INLINE_ELEMENTS: &[&str] = &[
+    "a", "abbr", "b", "code", "del", "em", "i", "kbd", "mark", "s", "small", "span", "strong",
+    "sub", "sup", "u",
+];
+
+/// A part of a page for the search index, starting at a heading.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Section {
+    /// The title of the heading, or of the page for the text before the
+    /// first heading
+    pub title: String,
+    /// The anchor of the heading, if the section starts at one
+    pub anchor: Option<String>,
+    /// The text of the section, without the heading
+    pub body: String,
+    /// The beginning of the text, to show in search results
+    pub excerpt: String,
+}
+
+/// Splits the rendered HTML of a page into a section for each heading.
+///
+/// The text before the first heading gets a section of its own, with the
+/// title of the page. If there is no such text, the first heading starts
+/// the page, so its section links to the page itself instead of the
+/// heading. Either way, every page has a section without an anchor.
+pub fn sections(title: &str, html: &str, headings: &[Heading]) -> Vec<Section> {
+    let mut sections = vec![];
+    let mut current: (&str, Option<&str>) = (title, None);
+    let mut rest = html;
+
+    for (i, heading) in headings.iter().enumerate() {
+        let (start, end) = match find_heading(rest, &heading.anchor) {
+            Some(position) => position,
+            None => continue,
+        };
+
+        let body = plain_text(&rest[..start]);
+        if i == 0 && body.is_empty() {
+            current = (&heading.title, None);
+        } else {
+            sections.push(section(current, body));
+            current = (&heading.title, Some(&heading.anchor));
+        }
+
+        rest = &rest[end..];
+    }
+
+    sections.push(section(current, plain_text(rest)));
+    sections
+}
+
+fn section((title, anchor): (&str, Option<&str>), body: String) -> Section {
+    Section {
+        title: title.to_string(),
+        anchor: anchor.map(|a| a.to_string()),
+        excerpt: crate::shorten(&body, EXCERPT_LENGTH).unwrap_or_default(),
+        body,
+    }
+}
+
+/// Where the heading element with an anchor starts, and where it ends
+/// including its closing tag. Headings are found by their `id` rather than
+/// by their order, since the page can have headings written in raw HTML.
+fn find_heading(html: &str, anchor: &str) -> Option<(usize, usize)> {
+    let id = html.find(&format!(" id=\"{}\"", anchor))?;
+    let start = html[..id].rfind('<')?;
+
+    let bytes = html.as_bytes();
+    let is_heading = matches!(bytes.get(start + 1), Some(b'h') | Some(b'H'))
+        && matches!(bytes.get(start + 2), Some(b'1'..=b'6'));
+    if !is_heading {
+        return None;
+    }
+
+    let closing = format!("</h{}>", bytes[start + 2] as char);
+    let end = find_ignore_case(&html[id..], &closing)
+        .map(|i| id + i + closing.len())
+        .unwrap_or(html.len());
+
+    Some((start, end))
+}
+
+/// Where some ASCII text first appears in HTML, ignoring case.
+fn find_ignore_case(html: &str, text: &str) -> Option<usize> {
+    html.as_bytes()
+        .windows(text.len())
+        .position(|window| window.eq_ignore_ascii_case(text.as_bytes()))
+}
+
+/// The text of some HTML, without tags, scripts, or styles, and with its
+/// whitespace collapsed.
+pub fn plain_text(html: &str) -> String {
+    let mut text = String::new();
+    let mut rest = html;
+
+    while let Some(start) = rest.find('<') {
+        text.push_str(&decode_entities(&rest[..start]));
+
+        let end = match rest[start..].find('>') {
+            Some(end) => start + end + 1,
+            None => {
+                rest = "";
+                break;
+            }
+        };
+
+        let tag = &rest[start + 1..end - 1];
+        let name = tag
+            .trim_start_matches('/')
+            .split(|c: char| c.is_ascii_whitespace() || c == '/')
+            .next()
+            .unwrap_or("")
+            .to_ascii_lowercase();
+
+        rest = &rest[end..];
+
+        // Skip over the contents of elements that aren't text
+        if !tag.starts_with('/') && (name == "script" || name == "style") {
+            let closing = format!("</{}", name);
+            rest = match find_ignore_case(rest, &closing) {
+                Some(i) => &rest[i..],
+                None => "",
+            };
+        }
+
+        if !INLINE_ELEMENTS.contains(&name.as_str()) {
+            text.push(' ');
+        }
+    }
+    text.push_str(&decode_entities(rest));
+
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Turns the character references HTML escapes text with back into the
+/// characters they stand for.
+fn decode_entities(text: &str) -> String {
+    let mut decoded = String::new();
+    let mut rest = text;
+
+    while let Some(start) = rest.find('&') {
+        decoded.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let end = match rest.find(';') {
+            Some(end) if end <= 10 => end,
+            _ => {
+                decoded.push('&');
+                rest = &rest[1..];
+                continue;
+            }
+        };
+
+        let character = match &rest[1..end] {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            "nbsp" => Some(' '),
+            entity if entity.starts_with("#x") || entity.starts_with("#X") => {
+                u32::from_str_radix(&entity[2..], 16)
+                    .ok()
+                    .and_then(std::char::from_u32)
+            }
+            entity if entity.starts_with('#') => {
+                entity[1..].parse().ok().and_then(std::char::from_u32)
+            }
+            _ => None,
+        };
+
+        match character {
+            Some(character) => {
+                decoded.push(character);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                decoded.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    decoded.push_str(rest);
+
+    decoded
+}
+
+/// How to split the search index into files, so that browsers only load
+/// the parts a search needs.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Sharding {
+    /// A file for each first letter of the words in the index
+    Prefix,
+    /// A file for each top-level directory of the docs
+    Section,
+}
+
+impl Sharding {
+    fn name(&self) -> &'static str {
+        match self {
+            Sharding::Prefix => "prefix",
+            Sharding::Section => "section",
+        }
+    }
+}
+
+/// How the search index for one language of the site is built.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchSettings {
+    /// Whether the site has search at all
+    pub enabled: bool,
+    /// Whether to split the index into files, and how
+    pub sharding: Option<Sharding>,
+    /// Whether to reduce words to their stem, so that "installing" also
+    /// finds "installation"
+    pub stemming: bool,
+    /// Words too common to be worth searching for
+    pub stop_words: Vec<String>,
+    pub boosts: Boosts,
+    /// Pages to leave out of the index, relative to the docs directory
+    pub exclude: Vec<Pattern>,
+}
+
+/// How much a word counts towards a search result, depending on where in
+/// the section it is.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct Boosts {
+    pub title: f64,
+    pub body: f64,
+}
+
+impl Default for Boosts {
+    /// Words in the title count twice as much as ones in the text.
+    fn default() -> Self {
+        Boosts {
+            title: 2.0,
+            body: 1.0,
+        }
+    }
+}
+
+/// A search result, as the browser shows it.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SearchDoc {
+    /// The title of the section
+    pub title: String,
+    /// The title of the page the section is on
+    pub page: String,
+    pub uri: String,
+    pub excerpt: String,
+}
+
+/// An index of sections of pages, searched by the browser, or by the
+/// preview server for it.
+///
+/// Instead of storing how often each word appears and leaving the browser
+/// to score matches, like elasticlunr does, the index stores the score of
+/// each word in each section. This keeps it small, and lets it be split
+/// into files that can be searched on their own.
+#[derive(Debug, Clone)]
+pub struct SearchIndex {
+    settings: SearchSettings,
+    stop_words: HashSet<String>,
+    /// Stems of the words seen so far, since stemming is slow
+    stems: HashMap<String, String>,
+    docs: Vec<SearchDoc>,
+    /// The text of each section, to show where the matches are
+    bodies: Vec<String>,
+    /// The top-level directory of the page each section is on
+    sections: Vec<String>,
+    /// How many times each term appears in the title and the text of each
+    /// section
+    terms: BTreeMap<String, BTreeMap<usize, [u32; 2]>>,
+    /// How many terms the title and the text of each section have
+    lengths: Vec<[u32; 2]>,
+}
+
+/// A section found by searching the index on the server.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SearchResult {
+    #[serde(flatten)]
+    pub doc: SearchDoc,
+    pub score: f64,
+    pub highlights: Highlights,
+}
+
+/// The parts of a search result to show, as HTML with the matching words
+/// in `<mark>` tags.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Highlights {
+    pub title: String,
+    /// The text around the first match, or the excerpt if only the title
+    /// matches
+    pub excerpt: String,
+}
+
+/// How much a site's search index adds to it.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct IndexSize {
+    pub bytes: u64,
+    pub files: usize,
+}
+
+/// A section a term appears in, with the score of the term in its title
+/// and in its text.
+type Posting = (usize, f64, f64);
+
+/// The contents of a search index file, along with its path relative to
+/// the root of the site.
+pub type IndexFile = (PathBuf, String);
+
+/// The file browsers load first, which describes how to search the rest.
+#[derive(Serialize)]
+struct Manifest<'a> {
+    boosts: Boosts,
+    stemming: bool,
+    stop_words: &'a [String],
+    #[serde(skip_serializing_if = "Option::is_none")]
+    sharding: Option<&'static str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    docs: Option<&'a [SearchDoc]>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    terms: Option<BTreeMap<&'a str, Vec<Posting>>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    docs_per_file: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    doc_files: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    shards: Option<BTreeMap<String, String>>,
+}
+
+#[derive(Serialize)]
+struct Shard<'a> {
+    terms: BTreeMap<&'a str, Vec<Posting>>,
+}
+
+impl SearchIndex {
+    pub fn new(mut settings: SearchSettings) -> Self {
+        for word in &mut settings.stop_words {
+            *word = word.to_lowercase();
+        }
+
+        SearchIndex {
+            stop_words: settings.stop_words.iter().cloned().collect(),
+            settings,
+            stems: HashMap::new(),
+            docs: vec![],
+            bodies: vec![],
+            sections: vec![],
+            terms: BTreeMap::new(),
+            lengths: vec![],
+        }
+    }
+
+    /// Adds a section of a page that is in the given top-level directory
+    /// of the docs.
+    pub fn add(&mut self, section: &str, doc: SearchDoc, body: &str) {
+        let id = self.docs.len();
+        let mut lengths = [0; 2];
+
+        for (field, text) in [doc.title.as_str(), body].iter().enumerate() {
+            let terms = self.terms(text);
+            lengths[field] = terms.len() as u32;
+
+            for term in terms {
+                self.terms.entry(term).or_default().entry(id).or_default()[field] += 1;
+            }
+        }
+
+        self.docs.push(doc);
+        self.bodies.push(body.to_string());
+        self.sections.push(section.to_string());
+        self.lengths.push(lengths);
+    }
+
+    /// Splits text into the terms it is indexed under: lowercase words,
+    /// without stop words, and stemmed if stemming is on.
+    ///
+    /// app.js does the same to search queries, so the two must agree.
+    fn terms(&mut self, text: &str) -> Vec<String> {
+        let mut terms = vec![];
+        let stop_words = &self.stop_words;
+        let stemming = self.settings.stemming;
+
+        for word in text
+            .to_lowercase()
+            .split(|c: char| !c.is_alphanumeric())
+            .filter(|w| !w.is_empty() && !stop_words.contains(*w))
+        {
+            let term = if stemming {
+                self.stems
+                    .entry(word.to_string())
+                    .or_insert_with(|| stemmer::stem(word))
+                    .clone()
+            } else {
+                word.to_string()
+            };
+
+            terms.push(term);
+        }
+
+        terms
+    }
+
+    /// The term a lowercase word is indexed under, or `None` for stop
+    /// words.
+    fn term(&self, word: &str) -> Option<String> {
+        if word.is_empty() || self.stop_words.contains(word) {
+            return None;
+        }
+
+        Some(if self.settings.stemming {
+            stemmer::stem(word)
+        } else {
+            word.to_string()
+        })
+    }
+
+    /// Searches the index the same way app.js does, and returns the best
+    /// results first.
+    ///
+    /// Every word that starts with a term of the query matches, but words
+    /// that only start with it count for less. Sections that match more of
+    /// the terms come first.
+    pub fn search(&self, query: &str, limit: usize) -> Vec<SearchResult> {
+        let terms = query
+            .to_lowercase()
+            .split(|c: char| !c.is_alphanumeric())
+            .filter_map(|word| self.term(word))
+            .collect::<Vec<_>>();
+
+        let mut scores: BTreeMap<usize, (f64, BTreeSet<usize>)> = BTreeMap::new();
+
+        for (i, term) in terms.iter().enumerate() {
+            let words = self
+                .terms
+                .range::<str, _>((Bound::Included(term.as_str()), Bound::Unbounded))
+                .take_while(|(word, _)| word.starts_with(term.as_str()));
+
+            for (word, sections) in words {
+                // Lengths as JavaScript counts them
+                let similarity =
+                    term.encode_utf16().count() as f64 / word.encode_utf16().count() as f64;
+
+                for (id, title, body) in self.postings(sections, |_| true) {
+                    let score =
+                        title * self.settings.boosts.title + body * self.settings.boosts.body;
+
+                    let entry = scores.entry(id).or_default();
+                    entry.0 += score * similarity;
+                    entry.1.insert(i);
+                }
+            }
+        }
+
+        let mut results = scores
+            .into_iter()
+            .map(|(id, (score, matched))| (id, score * matched.len() as f64 / terms.len() as f64))
+            .collect::<Vec<_>>();
+
+        results.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+        results.truncate(limit);
+
+        results
+            .into_iter()
+            .map(|(id, score)| SearchResult {
+                doc: self.docs[id].clone(),
+                score: (score * 1000.0).round() / 1000.0,
+                highlights: Highlights {
+                    title: self.highlight(&self.docs[id].title, &terms),
+                    excerpt: self.snippet(id, &terms),
+                },
+            })
+            .collect()
+    }
+
+    /// Whether a word in the text matches one of the terms of a query.
+    fn matches(&self, word: &str, terms: &[String]) -> bool {
+        match self.term(&word.to_lowercase()) {
+            Some(term) => terms.iter().any(|t| term.starts_with(t.as_str())),
+            None => false,
+        }
+    }
+
+    /// Escapes text for HTML, and marks the words in it that match.
+    fn highlight(&self, text: &str, terms: &[String]) -> String {
+        let mut html = String::new();
+        let mut end = 0;
+
+        for (start, word) in words(text) {
+            if self.matches(word, terms) {
+                html.push_str(&escape_html(&text[end..start]));
+                html.push_str("<mark>");
+                html.push_str(&escape_html(word));
+                html.push_str("</mark>");
+                end = start + word.len();
+            }
+        }
+        html.push_str(&escape_html(&text[end..]));
+
+        html
+    }
+
+    /// The part of the text of a section around the first match.
+    fn snippet(&self, id: usize, terms: &[String]) -> String {
+        let body = &self.bodies[id];
+        let words = words(body);
+
+        let first = match words.iter().position(|(_, w)| self.matches(w, terms)) {
+            Some(first) => first,
+            None => return self.highlight(&self.docs[id].excerpt, terms),
+        };
+
+        // Start a few words before the match, so that it has some context
+        let context_start = words[first].0.saturating_sub(SNIPPET_CONTEXT);
+        let start = words[..first]
+            .iter()
+            .map(|(start, _)| *start)
+            .find(|start| *start >= context_start)
+            .unwrap_or(words[first].0);
+
+        let text = crate::shorten(&body[start..], EXCERPT_LENGTH).unwrap_or_default();
+        let snippet = self.highlight(&text, terms);
+
+        if start > 0 {
+            format!("…{}", snippet)
+        } else {
+            snippet
+        }
+    }
+
+    /// The scores of a term in the sections it appears in, or only in the
+    /// ones a filter allows.
+    ///
+    /// These are the scores elasticlunr would give a search for the term,
+    /// before boosting the title over the text.
+    fn postings(
+        &self,
+        sections: &BTreeMap<usize, [u32; 2]>,
+        filter: impl Fn(usize) -> bool,
+    ) -> Vec<Posting> {
+        let total = self.docs.len() as f64;
+        let idf = |field: usize| {
+            let appears_in = sections.values().filter(|counts| counts[field] > 0).count();
+            1.0 + (total / (appears_in as f64 + 1.0)).ln()
+        };
+        let idf = [idf(0), idf(1)];
+
+        let score = |id: usize, counts: &[u32; 2], field: usize| {
+            if counts[field] == 0 {
+                return 0.0;
+            }
+
+            let score = (counts[field] as f64).sqrt() * idf[field]
+                / (self.lengths[id][field] as f64).sqrt();
+
+            (score * 1000.0).round() / 1000.0
+        };
+
+        sections
+            .iter()
+            .filter(|(id, _)| filter(**id))
+            .map(|(id, counts)| (*id, score(*id, counts, 0), score(*id, counts, 1)))
+            .collect()
+    }
+
+    /// Renders the index into the files browsers load. The first one is
+    /// `search_index.json`, which is all there is unless the index is
+    /// sharded.
+    pub fn files(&self) -> Vec<IndexFile> {
+        let mut manifest = Manifest {
+            boosts: self.settings.boosts,
+            stemming: self.settings.stemming,
+            stop_words: &self.settings.stop_words,
+            sharding: self.settings.sharding.map(|s| s.name()),
+            docs: None,
+            terms: None,
+            docs_per_file: None,
+            doc_files: None,
+            shards: None,
+        };
+
+        let sharding = match self.settings.sharding {
+            Some(sharding) => sharding,
+            None => {
+                manifest.docs = Some(&self.docs);
+                manifest.terms = Some(
+                    self.terms
+                        .iter()
+                        .map(|(term, sections)| (term.as_str(), self.postings(sections, |_| true)))
+                        .collect(),
+                );
+
+                return vec![(PathBuf::from("search_index.json"), to_json(&manifest))];
+            }
+        };
+
+        let mut files = vec![];
+
+        // The terms of each shard, by the first letter of the term or the
+        // directory of the section
+        let mut shards: BTreeMap<String, BTreeMap<&str, Vec<Posting>>> = BTreeMap::new();
+        for (term, sections) in &self.terms {
+            match sharding {
+                Sharding::Prefix => {
+                    let prefix = term.chars().next().unwrap_or_default().to_string();
+
+                    shards
+                        .entry(prefix)
+                        .or_default()
+                        .insert(term, self.postings(sections, |_| true));
+                }
+                Sharding::Section => {
+                    let keys = sections
+                        .keys()
+                        .map(|id| &self.sections[*id])
+                        .collect::<BTreeSet<_>>();
+
+                    for key in keys {
+                        shards.entry(key.clone()).or_default().insert(
+                            term,
+                            self.postings(sections, |id| &self.sections[id] == key),
+                        );
+                    }
+                }
+            }
+        }
+
+        let mut shard_files = BTreeMap::new();
+        for (i, (key, terms)) in shards.into_iter().enumerate() {
+            let path = format!("search_index/terms-{}.json", i);
+
+            files.push((PathBuf::from(&path), to_json(&Shard { terms })));
+            shard_files.insert(key, path);
+        }
+
+        let mut doc_files = vec![];
+        for (i, docs) in self.docs.chunks(DOCS_PER_FILE).enumerate() {
+            let path = format!("search_index/docs-{}.json", i);
+
+            files.push((PathBuf::from(&path), to_json(&docs)));
+            doc_files.push(path);
+        }
+
+        manifest.docs_per_file = Some(DOCS_PER_FILE);
+        manifest.doc_files = Some(doc_files);
+        manifest.shards = Some(shard_files);
+
+        files.insert(0, (PathBuf::from("search_index.json"), to_json(&manifest)));
+        files
+    }
+}
+
+/// The words in some text, with where they start.
+fn words(text: &str) -> Vec<(usize, &str)> {
+    let mut words = vec![];
+    let mut start = None;
+
+    for (i, c) in text.char_indices() {
+        match (start, c.is_alphanumeric()) {
+            (None, true) => start = Some(i),
+            (Some(s), false) => {
+                words.push((s, &text[s..i]));
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    if let Some(s) = start {
+        words.push((s, &text[s..]));
+    }
+
+    words
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+fn to_json<T: Serialize>(value: &T) -> String {
+    serde_json::to_string(value).expect("search index could not be serialized")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn heading(title: &str, anchor: &str, level: u32) -> Heading {
+        Heading {
+            title: title.to_string(),
+            anchor: anchor.to_string(),
+            level,
+        }
+    }
+
+    #[test]
+    fn plain_text_of_html() {
+        assert_eq!(
+            plain_text(
+                "<p>Use <code>Vec&lt;T&gt;</code> &amp; <em>friends</em>.</p>\n<ul><li>One</li><li>Two&#39;s</li></ul>"
+            ),
+            "Use Vec<T> & friends. One Two's"
+        );
+    }
+
+    #[test]
+    fn plain_text_skips_scripts_and_styles() {
+        assert_eq!(
+            plain_text("<style>p { color: red; }</style><p>Text</p><script>if (a < b) {}</script>"),
+            "Text"
+        );
+    }
+
+    #[test]
+    fn unknown_entities_are_kept() {
+        assert_eq!(decode_entities("AT&T &bogus; &#x41;"), "AT&T &bogus; A");
+    }
+
+    #[test]
+    fn sections_at_headings() {
+        let html = "<h1 id=\"guide-1\">Guide</h1>\n<p>Intro</p>\n\
+                    <h2 id=\"install-2\">Install</h2>\n<p>Run <code>make</code></p>\n\
+                    <hr />\n<h3 id=\"linux-3\">Linux</h3>\n";
+
+        let headings = vec![
+            heading("Guide", "guide-1", 1),
+            heading("Install", "install-2", 2),
+            heading("Linux", "linux-3", 3),
+        ];
+
+        let sections = sections("The guide", html, &headings);
+
+        assert_eq!(
+            sections
+                .iter()
+                .map(|s| (s.title.as_str(), s.anchor.as_deref(), s.body.as_str()))
+                .collect::<Vec<_>>(),
+            vec![
+                ("Guide", None, "Intro"),
+                ("Install", Some("install-2"), "Run make"),
+                ("Linux", Some("linux-3"), ""),
+            ]
+        );
+    }
+
+    #[test]
+    fn headings_in_raw_html_are_text() {
+        let html = "<h2 id=\"usage-1\">Usage</h2>\n<p>Like this</p>\n\
+                    <h2 class=\"banner\">New release</h2>\n\
+                    <h2 id=\"install-2\">Install</h2>\n<p>Install steps</p>\n";
+        let headings = vec![
+            heading("Usage", "usage-1", 2),
+            heading("Install", "install-2", 2),
+        ];
+
+        let sections = sections("Guide", html, &headings);
+
+        assert_eq!(
+            sections
+                .iter()
+                .map(|s| (s.title.as_str(), s.anchor.as_deref(), s.body.as_str()))
+                .collect::<Vec<_>>(),
+            vec![
+                ("Usage", None, "Like this New release"),
+                ("Install", Some("install-2"), "Install steps"),
+            ]
+        );
+    }
+
+    #[test]
+    fn text_before_the_first_heading() {
+        let html = "<p>Intro</p>\n<h2 id=\"usage-1\">Usage</h2>\n<p>Like this</p>\n";
+        let headings = vec![heading("Usage", "usage-1", 2)];
+
+        let sections = sections("Guide", html, &headings);
+
+        assert_eq!(sections[0].title, "Guide");
+        assert_eq!(sections[0].anchor, None);
+        assert_eq!(sections[0].body, "Intro");
+        assert_eq!(sections[1].title, "Usage");
+    }
+
+    #[test]
+    fn title_without_text() {
+        let html =
+            "<h1 id=\"guide-1\">Guide</h1>\n<h2 id=\"usage-2\">Usage</h2>\n<p>Like this</p>\n";
+        let headings = vec![
+            heading("Guide", "guide-1", 1),
+            heading("Usage", "usage-2", 2),
+        ];
+
+        let sections = sections("Guide", html, &headings);
+
+        assert_eq!(sections.len(), 2);
+        assert_eq!(sections[0].anchor, None);
+        assert_eq!(sections[0].body, "");
+        assert_eq!(sections[1].anchor.as_deref(), Some("usage-2"));
+    }
+
+    #[test]
+    fn page_without_headings() {
+        let sections = sections("Guide", "", &[]);
+
+        assert_eq!(sections.len(), 1);
+        assert_eq!(sections[0].title, "Guide");
+        assert_eq!(sections[0].body, "");
+    }
+
+    #[test]
+    fn excerpts_are_cut_at_words() {
+        let body = "word ".repeat(100);
+        let sections = sections("Guide", &format!("<p>{}</p>", body), &[]);
+
+        assert!(sections[0].excerpt.ends_with("word…"));
+        assert!(sections[0].excerpt.chars().count() <= EXCERPT_LENGTH + 1);
+    }
+
+    fn index(sharding: Option<Sharding>) -> SearchIndex {
+        let mut index = SearchIndex::new(SearchSettings {
+            enabled: true,
+            sharding,
+            stemming: true,
+            stop_words: vec!["The".to_string()],
+            boosts: Boosts::default(),
+            exclude: vec![],
+        });
+
+        for (directory, title, body) in &[
+            ("", "Welcome", "The installation guide."),
+            ("guides", "Installing", "Run make."),
+        ] {
+            index.add(
+                directory,
+                SearchDoc {
+                    title: title.to_string(),
+                    page: title.to_string(),
+                    uri: format!("/{}", directory),
+                    excerpt: body.to_string(),
+                },
+                body,
+            );
+        }
+
+        index
+    }
+
+    #[test]
+    fn terms_are_stemmed_without_stop_words() {
+        let mut index = index(None);
+
+        assert_eq!(
+            index.terms("The Installation, of Vec<T>!"),
+            vec!["instal", "of", "vec", "t"]
+        );
+    }
+
+    #[test]
+    fn titles_and_text_are_scored_separately() {
+        let index = index(None);
+
+        let postings = index.postings(&index.terms["instal"], |_| true);
+
+        assert_eq!(postings.len(), 2);
+        assert_eq!((postings[0].0, postings[0].1), (0, 0.0));
+        assert!(postings[0].2 > 0.0);
+        assert_eq!((postings[1].0, postings[1].2), (1, 0.0));
+        assert!(postings[1].1 > postings[0].2);
+    }
+
+    #[test]
+    fn unsharded_index_is_one_file() {
+        let files = index(None).files();
+
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].0, PathBuf::from("search_index.json"));
+        assert!(files[0].1.contains("\"stop_words\":[\"the\"]"));
+        assert!(files[0].1.contains("\"instal\":[[0,0.0,"));
+        assert!(files[0].1.contains("\"uri\":\"/guides\""));
+        assert!(!files[0].1.contains("shards"));
+    }
+
+    #[test]
+    fn shards_by_section() {
+        let files = index(Some(Sharding::Section)).files();
+
+        let paths = files
+            .iter()
+            .map(|(path, _)| path.to_str().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            paths,
+            vec![
+                "search_index.json",
+                "search_index/terms-0.json",
+                "search_index/terms-1.json",
+                "search_index/docs-0.json"
+            ]
+        );
+
+        assert!(files[0].1.contains(
+            "\"shards\":{\"\":\"search_index/terms-0.json\",\"guides\":\"search_index/terms-1.json\"}"
+        ));
+        assert!(files[1].1.contains("\"instal\":[[0,"));
+        assert!(!files[1].1.contains("make"));
+        assert!(files[2].1.contains("\"instal\":[[1,"));
+        assert!(files[2].1.contains("\"make\""));
+    }
+
+    #[test]
+    fn search_ranks_titles_first() {
+        let results = index(None).search("install", 10);
+
+        let titles = results
+            .iter()
+            .map(|r| r.doc.title.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(titles, vec!["Installing", "Welcome"]);
+        assert!(results[0].score > results[1].score);
+
+        assert_eq!(results[0].highlights.title, "<mark>Installing</mark>");
+        assert_eq!(results[0].highlights.excerpt, "Run make.");
+        assert_eq!(
+            results[1].highlights.excerpt,
+            "The <mark>installation</mark> guide."
+        );
+    }
+
+    #[test]
+    fn search_matches_the_start_of_words() {
+        let index = index(None);
+
+        assert_eq!(index.search("mak", 10)[0].doc.title, "Installing");
+        assert!(index.search("the", 10).is_empty());
+        assert!(index.search("", 10).is_empty());
+        assert_eq!(index.search("install", 1).len(), 1);
+    }
+
+    #[test]
+    fn snippets_start_near_the_first_match() {
+        let mut index = index(None);
+        let body = format!("{} Finally, <b>deploy</b> it.", "Lorem ipsum. ".repeat(20));
+
+        index.add(
+            "",
+            SearchDoc {
+                title: "Deploying".to_string(),
+                page: "Deploying".to_string(),
+                uri: "/deploying".to_string(),
+                excerpt: "Lorem ipsum.".to_string(),
+            },
+            &body,
+        );
+
+        let results = index.search("deploy", 10);
+
+        assert_eq!(
+            results[0].highlights.excerpt,
+            "…Lorem ipsum. Lorem ipsum. Finally, &lt;b&gt;<mark>deploy</mark>&lt;/b&gt; it."
+        );
+    }
+}
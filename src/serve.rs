@@ -1,19 +1,20 @@
+use std::sync::{Arc, RwLock};
 use std::thread;
 use std::time::Instant;
 
 use bunt::termcolor::{ColorChoice, StandardStream};
 use crossbeam_channel::bounded;
 
-use crate::config::Config;
+use crate::config::{self, Config, VersionSource};
 use crate::livereload_server::LivereloadServer;
-use crate::preview_server::PreviewServer;
+use crate::preview_server::{BuildError, PreviewServer};
 use crate::site::Site;
 use crate::watcher::Watcher;
-use crate::Result;
+use crate::{Error, Result};
 
 pub struct ServeCommand {
     config: Config,
-    site: Site,
+    site: Arc<Site>,
 }
 
 #[derive(Default)]
@@ -23,12 +24,21 @@ pub struct ServeOptions {
 
 impl ServeCommand {
     pub fn run(options: ServeOptions, config: Config) -> Result<()> {
+        // The watcher would see every build as a change, and build again
+        if config::is_inside(config.out_dir(), config.docs_dir()) {
+            return Err(Error::new(format!(
+                "Invalid output directory {}.\n\
+                 The serve command can't build the site into the docs directory.",
+                config.out_dir().display()
+            )));
+        }
+
         let mut stdout = if config.color_enabled() {
             StandardStream::stdout(ColorChoice::Auto)
         } else {
             StandardStream::stdout(ColorChoice::Never)
         };
-        let site = Site::new(config.clone());
+        let site = Arc::new(Site::new(config.clone()));
 
         let cmd = ServeCommand { config, site };
 
@@ -37,14 +47,37 @@ impl ServeCommand {
 
         // Do initial build ---------------------------
 
+        let build_error: BuildError = Arc::new(RwLock::new(None));
+
         let start = Instant::now();
-        cmd.site.build().unwrap();
+        if let Err(e) = cmd.site.build() {
+            bunt::writeln!(stdout, "{$red}Error:{/$} {}\n", e)?;
+            *build_error.write().unwrap() = Some(e.to_string());
+        }
         let duration = start.elapsed();
 
         // Watcher ------------------------------------
 
         let (watch_snd, watch_rcv) = bounded(128);
-        let watcher = Watcher::new(vec![cmd.config.project_root().join("docs")], watch_snd);
+        let mut watched_paths = vec![cmd.config.docs_dir().to_path_buf()];
+        for version in cmd.config.versions() {
+            if let VersionSource::Dir(path) = &version.source {
+                let path = cmd.config.project_root().join(path);
+
+                if !path.starts_with(cmd.config.docs_dir()) {
+                    watched_paths.push(path);
+                }
+            }
+        }
+        if !cmd
+            .config
+            .templates_dir()
+            .starts_with(cmd.config.docs_dir())
+        {
+            watched_paths.push(cmd.config.templates_dir().to_path_buf());
+        }
+
+        let watcher = Watcher::new(watched_paths, watch_snd);
         thread::Builder::new()
             .name("watcher".into())
             .spawn(move || watcher.run())
@@ -67,6 +100,12 @@ impl ServeCommand {
             &format!("0.0.0.0:{}", port),
             &cmd.config.out_dir(),
             cmd.config.color_enabled(),
+            build_error.clone(),
+            cmd.config.site_uri_prefix().to_string(),
+            // Versioned and translated sites only have assets under each
+            // version and language
+            cmd.config.default_uri_prefix(),
+            cmd.site.clone(),
         );
         thread::Builder::new()
             .name("http-server".into())
@@ -76,16 +115,52 @@ impl ServeCommand {
         // Listen for updates on from the watcher, rebuild the site,
         // and inform the websocket listeners.
 
-        for (path, msg) in watch_rcv {
-            bunt::writeln!(stdout, "    File {$bold}{}{/$} {}.", path.display(), msg)?;
+        for (path, msg) in watch_rcv.iter() {
+            // Editors often touch several files at once, so batch up
+            // anything else that is already waiting for us.
+            let mut changes = vec![(path, msg)];
+            changes.extend(watch_rcv.try_iter());
+
+            for (path, msg) in &changes {
+                bunt::writeln!(stdout, "    File {$bold}{}{/$} {}.", path.display(), msg)?;
+            }
+
+            let paths = changes.into_iter().map(|(p, _)| p).collect::<Vec<_>>();
 
             let start = Instant::now();
-            cmd.site.build().unwrap();
+            let result = cmd.site.rebuild(&paths);
             let duration = start.elapsed();
 
-            bunt::writeln!(stdout, "    Site rebuilt in {$bold}{:?}{/$}\n", duration)?;
-
-            reload_send.send(()).unwrap();
+            let had_error = build_error.read().unwrap().is_some();
+
+            match result {
+                Ok(mut updated) => {
+                    *build_error.write().unwrap() = None;
+
+                    bunt::writeln!(
+                        stdout,
+                        "    Site rebuilt in {$bold}{:?}{/$} ({} updated)\n",
+                        duration,
+                        updated.len()
+                    )?;
+
+                    // The browser is showing the error page, so it needs
+                    // to reload even if no pages changed.
+                    if had_error && updated.is_empty() {
+                        updated.push(String::from("/"));
+                    }
+
+                    if !updated.is_empty() {
+                        reload_send.send(updated).unwrap();
+                    }
+                }
+                Err(e) => {
+                    bunt::writeln!(stdout, "{$red}Error:{/$} {}\n", e)?;
+
+                    *build_error.write().unwrap() = Some(e.to_string());
+                    reload_send.send(vec![String::from("/")]).unwrap();
+                }
+            }
         }
 
         Ok(())
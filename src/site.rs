@@ -1,9 +1,21 @@
+use std::collections::{HashMap, HashSet};
 use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
 
-use crate::config::Config;
-use crate::site_generator::SiteGenerator;
+use crate::check::BrokenLink;
+use crate::config::{Config, Language, Version, VersionSource, LATEST_VERSION_ALIAS, SITE_MARKER};
+use crate::relative_links::relative_path;
+use crate::search::{IndexSize, SearchResult};
+use crate::site_generator::{BuildState, SiteGenerator, Translations};
+use crate::sitemap;
+use crate::templates::Templates;
+use crate::versions;
 use crate::{Error, Result};
 
+static SITE_ID: AtomicUsize = AtomicUsize::new(0);
+
 #[derive(Debug, Clone, Copy, PartialEq)]
 /// Describes the mode we should build the site in, meaning
 /// which assets we want to include/exclude for development.
@@ -23,16 +35,31 @@ impl std::fmt::Display for BuildMode {
 
 /// A handle to the output directory where the site will be generated.
 ///
-/// Completely agnostic about where the original Markdown files are
-/// located. Only cares about the destination directory.
+/// If the project has several versions, each one is built into its own
+/// subdirectory with its own config, as if it was a separate site.
 pub struct Site {
     config: Config,
+    /// The config and build state of each version, or of the whole site if
+    /// it is not versioned
+    state: Mutex<Option<Vec<(Config, BuildState)>>>,
+    /// Where versions from git are checked out to while building
+    checkout_dir: PathBuf,
 }
 
 impl Site {
     /// Create a new handle to a site output directory.
     pub fn new(config: Config) -> Site {
-        Site { config }
+        let checkout_dir = std::env::temp_dir().join(format!(
+            "doctave-{}-{}",
+            std::process::id(),
+            SITE_ID.fetch_add(1, Ordering::Relaxed)
+        ));
+
+        Site {
+            config,
+            state: Mutex::new(None),
+            checkout_dir,
+        }
     }
 
     pub fn create_dir(&self) -> Result<()> {
@@ -44,7 +71,13 @@ impl Site {
                     self.config.out_dir().display()
                 ),
             )
-        })
+        })?;
+
+        fs::write(
+            self.config.out_dir().join(SITE_MARKER),
+            "Built by Doctave. This directory is deleted on every build.\n",
+        )
+        .map_err(|e| Error::io(e, "Could not mark the site directory as built by Doctave"))
     }
 
     pub fn delete_dir(&self) -> Result<()> {
@@ -70,9 +103,530 @@ impl Site {
         Ok(())
     }
 
+    /// Does a clean build of the whole site.
     pub fn build(&self) -> Result<()> {
-        let generator = SiteGenerator::new(&self.config, &self);
+        let parts = self.build_parts()?;
+
+        *self.state.lock().unwrap() = Some(parts);
+
+        Ok(())
+    }
+
+    fn build_parts(&self) -> Result<Vec<(Config, BuildState)>> {
+        self.reset()?;
+
+        let configs = self.configs(true)?;
+
+        // Read all the documents before rendering anything, so that pages
+        // can link to their translations.
+        let mut sources = vec![];
+        let mut pages = vec![];
+        for config in &configs {
+            let generator = SiteGenerator::new(config)?;
+            let docs = generator.find_docs()?;
+
+            pages.push(generator.page_uris(&docs));
+            sources.push(docs);
+        }
+
+        let translations = Self::translations(&configs.iter().collect::<Vec<_>>(), &pages);
+
+        let mut parts = vec![];
+
+        for ((config, docs), translations) in configs.into_iter().zip(sources).zip(translations) {
+            let state = SiteGenerator::new(&config)?
+                .with_translations(translations)
+                .run(docs)?;
+
+            if self.is_latest(&config) {
+                self.build_latest_alias(&config, &state)?;
+            }
+
+            parts.push((config, state));
+        }
+
+        self.build_root_redirects()?;
+
+        if let (BuildMode::Release, Some(url)) = (self.config.build_mode(), self.config.url()) {
+            self.build_sitemap(url, &parts)?;
+        }
+
+        Ok(parts)
+    }
+
+    /// The configs to build the site with. One for each version and
+    /// language, or just the project's config if it has neither.
+    fn configs(&self, include_git: bool) -> Result<Vec<Config>> {
+        let mut configs = vec![];
+
+        if self.config.versions().is_empty() {
+            configs.push(self.config.clone());
+        }
+
+        for version in self.config.versions() {
+            let docs_dir = match &version.source {
+                VersionSource::Dir(path) => self.config.project_root().join(path),
+                VersionSource::Git(..) if !include_git => continue,
+                VersionSource::Git(reference, path) => self.checkout(version, reference, path)?,
+            };
+
+            configs.push(self.config.for_version(version, docs_dir));
+        }
+
+        if self.config.languages().is_empty() {
+            return Ok(configs);
+        }
+
+        Ok(configs
+            .iter()
+            .flat_map(|config| {
+                self.config
+                    .languages()
+                    .iter()
+                    .map(move |lang| config.for_language(lang))
+            })
+            .collect())
+    }
+
+    /// For each config, the pages in every language of the same version.
+    fn translations(configs: &[&Config], pages: &[HashSet<String>]) -> Vec<Translations> {
+        configs
+            .iter()
+            .map(|config| {
+                if config.language().is_none() {
+                    return Translations::new();
+                }
+
+                configs
+                    .iter()
+                    .zip(pages)
+                    .filter(|(other, _)| other.version() == config.version())
+                    .filter_map(|(other, pages)| {
+                        other.language().map(|l| (l.code.clone(), pages.clone()))
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn is_latest(&self, config: &Config) -> bool {
+        config.version().is_some() && config.version() == self.config.latest_version()
+    }
+
+    /// Writes out the docs of a version from git, and returns the directory
+    /// they were written to.
+    fn checkout(&self, version: &Version, reference: &str, path: &Path) -> Result<PathBuf> {
+        let destination = self.checkout_dir.join(&version.name);
+
+        if destination.exists() {
+            fs::remove_dir_all(&destination)
+                .map_err(|e| Error::io(e, "Could not clear previous checkout"))?;
+        }
+
+        versions::checkout(self.config.project_root(), reference, path, &destination)?;
+
+        Ok(destination)
+    }
+
+    /// Makes every page of the latest version also available under
+    /// `/latest`.
+    ///
+    /// The pages under `/latest` redirect to the versioned page, so that
+    /// there is only one copy of each page.
+    fn build_latest_alias(&self, latest: &Config, state: &BuildState) -> Result<()> {
+        let mut alias_dir = self.config.out_dir().join(LATEST_VERSION_ALIAS);
+        if let Some(lang) = latest.language() {
+            alias_dir = alias_dir.join(&lang.code);
+        }
+
+        if alias_dir.exists() {
+            fs::remove_dir_all(&alias_dir)
+                .map_err(|e| Error::io(e, "Could not clear latest version directory"))?;
+        }
+
+        let redirects = state
+            .root()
+            .docs_recursive()
+            .into_iter()
+            .map(|doc| {
+                let uri_path = doc.uri_path();
+                let target = if uri_path == "/" {
+                    format!("{}/", latest.uri_prefix())
+                } else {
+                    format!("{}{}", latest.uri_prefix(), uri_path)
+                };
+
+                (
+                    doc.destination(&alias_dir),
+                    target,
+                    doc.destination(latest.out_dir()),
+                )
+            })
+            .collect();
+
+        self.write_redirects(redirects)
+    }
+
+    /// Points the root of the site, and of each version, at the pages they
+    /// should show when the site is split into versions or languages.
+    fn build_root_redirects(&self) -> Result<()> {
+        let out_dir = self.config.out_dir();
+        let mut redirects = vec![];
+
+        if !self.config.versions().is_empty() || !self.config.languages().is_empty() {
+            redirects.push((
+                out_dir.join("index.html"),
+                format!("{}/", self.config.default_uri_prefix()),
+                self.part_dir(self.config.latest_version(), self.config.default_language())
+                    .join("index.html"),
+            ));
+        }
+
+        if let Some(lang) = self.config.default_language() {
+            let mut versions = self
+                .config
+                .versions()
+                .iter()
+                .map(|v| (v.name.as_str(), v))
+                .collect::<Vec<_>>();
+            if let Some(latest) = self.config.latest_version() {
+                versions.push((LATEST_VERSION_ALIAS, latest));
+            }
+
+            for (dir, version) in versions {
+                redirects.push((
+                    out_dir.join(dir).join("index.html"),
+                    format!(
+                        "{}{}/{}/",
+                        self.config.site_uri_prefix(),
+                        version.uri_prefix(),
+                        lang.code
+                    ),
+                    self.part_dir(Some(version), Some(lang)).join("index.html"),
+                ));
+            }
+        }
+
+        self.write_redirects(redirects)
+    }
+
+    /// The directory a version and language of the site is built into.
+    fn part_dir(&self, version: Option<&Version>, language: Option<&Language>) -> PathBuf {
+        let mut dir = self.config.out_dir().to_path_buf();
+
+        if let Some(version) = version {
+            dir = dir.join(&version.name);
+        }
+        if let Some(language) = language {
+            dir = dir.join(&language.code);
+        }
+
+        dir
+    }
+
+    /// Writes pages that redirect to other pages. Each redirect is given as
+    /// the page to write, and the URI and file of the page to redirect to.
+    fn write_redirects(&self, redirects: Vec<(PathBuf, String, PathBuf)>) -> Result<()> {
+        if redirects.is_empty() {
+            return Ok(());
+        }
+
+        let templates = Templates::load(&self.config)?;
+        let out_dir = self.config.out_dir();
+
+        for (destination, uri, file) in redirects {
+            let target = if self.config.relative_links() {
+                relative_path(
+                    destination.strip_prefix(out_dir).unwrap(),
+                    file.strip_prefix(out_dir).unwrap(),
+                )
+            } else {
+                uri
+            };
+
+            let mut data = serde_json::Map::new();
+            data.insert("target".to_string(), serde_json::Value::String(target));
+            data.insert(
+                "project_title".to_string(),
+                serde_json::Value::String(self.config.title().to_string()),
+            );
+
+            let page = templates.render("redirect", &data)?;
 
-        generator.run()
+            fs::create_dir_all(destination.parent().unwrap())
+                .map_err(|e| Error::io(e, "Could not create redirect directory"))?;
+            fs::write(&destination, page).map_err(|e| {
+                Error::io(
+                    e,
+                    format!("Could not create page {}", destination.display()),
+                )
+            })?;
+        }
+
+        Ok(())
+    }
+
+    /// Writes a sitemap listing every page of every version and language,
+    /// and a robots.txt pointing to it, unless the project provides its own.
+    ///
+    /// Pages that are drafts or marked `noindex` are left out.
+    fn build_sitemap(&self, url: &str, parts: &[(Config, BuildState)]) -> Result<()> {
+        // Dates from git for each git ref, if the project is in a repository
+        let mut git_dates = HashMap::new();
+        let mut entries = vec![];
+
+        for (config, state) in parts {
+            let (reference, checkout) = match config.version().map(|v| (v, &v.source)) {
+                Some((version, VersionSource::Git(reference, path))) => (
+                    Some(reference.as_str()),
+                    Some((self.checkout_dir.join(&version.name), path)),
+                ),
+                _ => (None, None),
+            };
+
+            let dates = git_dates.entry(reference).or_insert_with(|| {
+                versions::last_modified(self.config.project_root(), reference, Path::new("."))
+                    .unwrap_or_default()
+            });
+
+            for doc in state.root().docs_recursive() {
+                if doc.frontmatter.draft || doc.frontmatter.noindex {
+                    continue;
+                }
+
+                let file = config.docs_dir().join(&doc.source);
+
+                let last_modified = match &checkout {
+                    Some((dir, path)) => file
+                        .strip_prefix(dir)
+                        .ok()
+                        .and_then(|f| dates.get(&path.join(f)))
+                        .cloned(),
+                    None => file
+                        .strip_prefix(self.config.project_root())
+                        .ok()
+                        .and_then(|f| dates.get(f))
+                        .cloned()
+                        .or_else(|| {
+                            fs::metadata(&file)
+                                .and_then(|m| m.modified())
+                                .ok()
+                                .map(sitemap::format_time)
+                        }),
+                };
+
+                let uri_path = doc.uri_path();
+                let uri = if uri_path == "/" {
+                    format!("{}/", config.uri_prefix())
+                } else {
+                    format!("{}{}", config.uri_prefix(), uri_path)
+                };
+
+                entries.push(sitemap::Entry { uri, last_modified });
+            }
+        }
+
+        let out_dir = self.config.out_dir();
+
+        let sitemap_path = out_dir.join("sitemap.xml");
+        fs::write(&sitemap_path, sitemap::render(url, &entries)).map_err(|e| {
+            Error::io(
+                e,
+                format!("Could not write sitemap to {}", sitemap_path.display()),
+            )
+        })?;
+
+        // A robots.txt in the _include directory takes precedence
+        let robots_path = out_dir.join("robots.txt");
+        if !robots_path.exists() {
+            let sitemap_url = format!("{}{}/sitemap.xml", url, self.config.site_uri_prefix());
+
+            fs::write(
+                &robots_path,
+                sitemap::robots(self.config.robots(), &sitemap_url),
+            )
+            .map_err(|e| {
+                Error::io(
+                    e,
+                    format!("Could not write robots.txt to {}", robots_path.display()),
+                )
+            })?;
+        }
+
+        Ok(())
+    }
+
+    /// Incrementally rebuilds the site after the given files have changed.
+    /// Falls back to a clean build if the site has not been built yet.
+    ///
+    /// Returns the URIs of the pages and files that were updated.
+    pub fn rebuild(&self, changed: &[PathBuf]) -> Result<Vec<String>> {
+        let mut state = self.state.lock().unwrap();
+
+        let parts = match state.as_mut() {
+            Some(parts) => parts,
+            None => {
+                *state = Some(self.build_parts()?);
+
+                return Ok(vec![String::from("/")]);
+            }
+        };
+
+        let mut updated = vec![];
+        let mut translations = Self::current_translations(parts);
+
+        for ((config, state), translations) in parts.iter_mut().zip(translations) {
+            let mut part_updated = SiteGenerator::new(config)?
+                .with_translations(translations)
+                .rebuild(state, changed)?;
+
+            if self.is_latest(config) && !part_updated.is_empty() {
+                self.build_latest_alias(config, state)?;
+            }
+
+            updated.append(&mut part_updated);
+        }
+
+        // Pages may have been added to or removed from a language, so other
+        // languages need to update their links to them.
+        translations = Self::current_translations(parts);
+
+        for ((config, state), translations) in parts.iter_mut().zip(translations) {
+            if config.language().is_some() && translations != *state.translations() {
+                updated.append(
+                    &mut SiteGenerator::new(config)?
+                        .with_translations(translations)
+                        .rebuild(state, &[])?,
+                );
+            }
+        }
+
+        Ok(updated)
+    }
+
+    fn current_translations(parts: &[(Config, BuildState)]) -> Vec<Translations> {
+        let configs = parts.iter().map(|(config, _)| config).collect::<Vec<_>>();
+        let pages = parts
+            .iter()
+            .map(|(config, state)| match config.language() {
+                Some(_) => state.page_uris(),
+                None => HashSet::new(),
+            })
+            .collect::<Vec<_>>();
+
+        Self::translations(&configs, &pages)
+    }
+
+    /// How much the search indices of every version and language add to
+    /// the site, as of the last build.
+    pub fn search_index_size(&self) -> IndexSize {
+        let state = self.state.lock().unwrap();
+
+        let mut size = IndexSize::default();
+        for (_, state) in state.iter().flatten() {
+            size.bytes += state.search_index_size().bytes;
+            size.files += state.search_index_size().files;
+        }
+
+        size
+    }
+
+    /// Searches the version and language of the site served under a URI
+    /// prefix, using the index from the last build. The root of the site
+    /// searches the default version and language.
+    ///
+    /// Returns `None` if there is no such part of the site, or it has no
+    /// search.
+    pub fn search(&self, uri_prefix: &str, query: &str, limit: usize) -> Option<Vec<SearchResult>> {
+        let state = self.state.lock().unwrap();
+
+        let uri_prefix = if uri_prefix == self.config.site_uri_prefix() {
+            self.config.default_uri_prefix()
+        } else {
+            uri_prefix.to_string()
+        };
+
+        state
+            .iter()
+            .flatten()
+            .find(|(config, _)| config.uri_prefix() == uri_prefix)
+            .and_then(|(_, state)| state.search_index())
+            .map(|index| index.search(query, limit))
+    }
+
+    /// The URI prefixes that can be searched: the root of the site, and
+    /// each version and language of it from the last build.
+    pub fn search_uri_prefixes(&self) -> Vec<String> {
+        let state = self.state.lock().unwrap();
+        let parts = state.iter().flatten();
+
+        std::iter::once(self.config.site_uri_prefix().to_string())
+            .chain(parts.map(|(config, _)| config.uri_prefix()))
+            .collect()
+    }
+
+    /// Checks the links in every document. Uses the documents from the
+    /// last build if there was one.
+    ///
+    /// Versions from git are not checked, since their documents can't be
+    /// changed anymore.
+    pub fn check(&self) -> Result<Vec<BrokenLink>> {
+        let state = self.state.lock().unwrap();
+
+        let mut broken_links = vec![];
+
+        match state.as_ref() {
+            Some(parts) => {
+                for (config, state) in parts {
+                    if let Some(VersionSource::Git(..)) = config.version().map(|v| &v.source) {
+                        continue;
+                    }
+
+                    broken_links.append(&mut SiteGenerator::new(config)?.check(Some(state))?);
+                }
+            }
+            None => {
+                for config in self.configs(false)? {
+                    broken_links.append(&mut SiteGenerator::new(&config)?.check(None)?);
+                }
+            }
+        }
+
+        Ok(broken_links)
+    }
+
+    /// Finds links to draft pages, which are left out of release builds.
+    /// Uses the documents from the last build if there was one.
+    pub fn draft_links(&self) -> Result<Vec<BrokenLink>> {
+        let state = self.state.lock().unwrap();
+
+        let mut draft_links = vec![];
+
+        match state.as_ref() {
+            Some(parts) => {
+                for (config, state) in parts {
+                    if let Some(VersionSource::Git(..)) = config.version().map(|v| &v.source) {
+                        continue;
+                    }
+
+                    draft_links.append(&mut SiteGenerator::new(config)?.draft_links(Some(state))?);
+                }
+            }
+            None => {
+                for config in self.configs(false)? {
+                    draft_links.append(&mut SiteGenerator::new(&config)?.draft_links(None)?);
+                }
+            }
+        }
+
+        Ok(draft_links)
+    }
+}
+
+impl Drop for Site {
+    fn drop(&mut self) {
+        if self.checkout_dir.exists() {
+            let _ = fs::remove_dir_all(&self.checkout_dir);
+        }
     }
 }
@@ -1,100 +1,566 @@
-use std::collections::BTreeMap;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::ffi::OsStr;
 use std::fs::{self, File};
-use std::path::Path;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
 use std::time::{SystemTime, UNIX_EPOCH};
 
-use elasticlunr::Index;
+use glob::Pattern;
 use rayon::prelude::*;
 use serde::Serialize;
 use walkdir::WalkDir;
 
-use crate::config::Config;
-use crate::navigation::{Link, Navigation};
-use crate::site::{BuildMode, Site};
+use crate::check::{BrokenLink, LinkChecker};
+use crate::config::{self, Config, LATEST_VERSION_ALIAS};
+use crate::frontmatter::Frontmatter;
+use crate::navigation::{Link, LinkKind, Navigation};
+use crate::relative_links::RelativeLinks;
+use crate::search::{self, IndexSize, SearchDoc, SearchIndex};
+use crate::site::BuildMode;
+use crate::templates::Templates;
+use crate::toc::TocEntry;
 use crate::{Directory, Document};
 use crate::{Error, Result};
 
-static INCLUDE_DIR: &str = "_include";
 static HEAD_FILE: &str = "_head.html";
 
+/// The URI paths of the pages in each language, by language code.
+pub type Translations = HashMap<String, HashSet<String>>;
+
 pub struct SiteGenerator<'a> {
     config: &'a Config,
-    site: &'a Site,
+    templates: Templates,
     timestamp: String,
+    uri_prefix: String,
+    translations: Translations,
 }
 
 impl<'a> SiteGenerator<'a> {
-    pub fn new(config: &'a Config, site: &'a Site) -> Self {
+    pub fn new(config: &'a Config) -> Result<Self> {
         let start = SystemTime::now();
 
         let since_the_epoch = start
             .duration_since(UNIX_EPOCH)
             .expect("Time went backwards");
 
-        SiteGenerator { config, site, timestamp: format!("{}", since_the_epoch.as_secs()) }
+        Ok(SiteGenerator {
+            config,
+            templates: Templates::load(config)?,
+            timestamp: format!("{}", since_the_epoch.as_secs()),
+            uri_prefix: config.uri_prefix(),
+            translations: Translations::new(),
+        })
     }
 
-    pub fn run(&self) -> Result<()> {
-        let root = self.find_docs(self.config.project_root());
-        let nav_builder = Navigation::new(&self.config);
-        let navigation = nav_builder.build_for(&root);
+    /// Sets which pages exist in the other languages of the site, so pages
+    /// can link to their translations.
+    pub fn with_translations(mut self, translations: Translations) -> Self {
+        self.translations = translations;
+        self
+    }
 
-        self.site.reset()?;
+    /// The URI paths of the pages that would be built from the given
+    /// documents, without the URI prefix.
+    pub fn page_uris(&self, sources: &Directory) -> HashSet<String> {
+        self.pages(sources)
+            .docs_recursive()
+            .iter()
+            .map(|doc| doc.uri_path())
+            .collect()
+    }
 
-        let head_include = self.read_head_include()?;
+    /// Does a clean build of the documents read with `find_docs` into the
+    /// output directory, which is expected to be empty.
+    ///
+    /// Returns the state of the build, which can be passed to `rebuild` to
+    /// incrementally update the site later.
+    pub fn run(&self, sources: Directory) -> Result<BuildState> {
+        fs::create_dir_all(self.config.out_dir())
+            .map_err(|e| Error::io(e, "Could not create site directory"))?;
 
         self.build_includes()?;
         self.build_assets()?;
-        self.build_directory(&root, &navigation, head_include.as_deref())?;
-        self.build_search_index(&root)?;
 
-        Ok(())
+        let root = self.pages(&sources);
+
+        let navigation = self.build_navigation(&root, &sources)?;
+
+        let head_include = self.read_head_include()?;
+
+        let digests = self.build_pages(
+            &root.docs_recursive(),
+            &navigation,
+            head_include.as_deref(),
+            self.relative_links(&root).as_ref(),
+            &self.timestamp,
+            &HashMap::new(),
+        )?;
+        let (search_index, search_index_size) = self.build_search_index(&root)?;
+
+        Ok(BuildState {
+            sources,
+            root,
+            navigation,
+            head_include,
+            translations: self.translations.clone(),
+            timestamp: self.timestamp.clone(),
+            digests: digests.into_iter().collect(),
+            search_index,
+            search_index_size,
+        })
     }
 
-    fn read_head_include(&self) -> Result<Option<String>> {
-        let custom_head = self.config.docs_dir().join(INCLUDE_DIR).join(HEAD_FILE);
+    /// Incrementally updates a previously built site, given a list of files
+    /// that have changed in the docs directory.
+    ///
+    /// Only the changed documents are parsed again. Pages are re-rendered if
+    /// their document changed, or if something shared by every page (like
+    /// the navigation) changed. Pages are only written to disk if their
+    /// rendered output differs from what is already there.
+    ///
+    /// Returns the URIs of the pages and files that were updated.
+    pub fn rebuild(&self, state: &mut BuildState, changed: &[PathBuf]) -> Result<Vec<String>> {
+        let mut updated = vec![];
+        let mut errors = vec![];
+        let mut full_rescan = false;
+
+        let templates_changed = changed
+            .iter()
+            .any(|path| path.starts_with(self.config.templates_dir()));
+
+        let include_dirs = self.config.include_dirs();
+
+        for path in changed {
+            if let Some(include_dir) = include_dirs.iter().find(|dir| path.starts_with(dir)) {
+                if path.file_name() != Some(OsStr::new(HEAD_FILE)) {
+                    let relative = path.strip_prefix(include_dir).unwrap();
+                    self.build_include(relative)?;
+
+                    updated.push(self.uri(Link::path_to_uri_with_extension(relative)));
+                }
+
+                continue;
+            }
+
+            let relative = match path.strip_prefix(self.config.docs_dir()) {
+                Ok(relative) => relative,
+                Err(_) => continue,
+            };
+
+            if path.extension() == Some(OsStr::new("md")) {
+                let page = match self.localized_path(relative) {
+                    Some(page) => page,
+                    None => continue,
+                };
+
+                if path.is_file() {
+                    match Document::load(path, &page) {
+                        Ok(doc) => state.sources.upsert(doc.with_source(relative)),
+                        Err(e) => errors.push(e),
+                    }
+                } else if page != relative {
+                    // A translation was removed, so the original page
+                    // should take its place again.
+                    full_rescan = true;
+                } else {
+                    state.sources.remove(relative);
+                }
+            } else if path.extension().is_none() {
+                // Most likely a directory that was created, moved, or removed.
+                // Easiest to just look at everything again.
+                full_rescan = true;
+            }
+        }
+
+        if full_rescan {
+            state.sources = self.find_docs()?;
+            self.build_includes()?;
+        } else if !errors.is_empty() {
+            return Err(Self::broken_documents(errors));
+        }
+
+        let root = self.pages(&state.sources);
+
+        let navigation = self.build_navigation(&root, &state.sources)?;
+        let head_include = self.read_head_include()?;
+
+        let rerender_all = templates_changed
+            || navigation != state.navigation
+            || head_include != state.head_include
+            || self.translations != state.translations;
+
+        let previous_docs = state
+            .root
+            .docs_recursive()
+            .into_iter()
+            .map(|doc| (doc.path.as_path(), doc))
+            .collect::<HashMap<_, _>>();
+
+        let current_docs = root.docs_recursive();
+
+        let stale_docs = current_docs
+            .iter()
+            .copied()
+            .filter(|doc| {
+                rerender_all
+                    || previous_docs
+                        .get(doc.path.as_path())
+                        .map(|previous| previous.raw != doc.raw)
+                        .unwrap_or(true)
+            })
+            .collect::<Vec<_>>();
+
+        let written = self.build_pages(
+            &stale_docs,
+            &navigation,
+            head_include.as_deref(),
+            self.relative_links(&root).as_ref(),
+            &state.timestamp,
+            &state.digests,
+        )?;
+
+        for (html_path, digest) in &written {
+            updated.push(self.uri(Link::path_to_uri(html_path)));
+            state.digests.insert(html_path.clone(), *digest);
+        }
+
+        let current_paths = current_docs
+            .iter()
+            .map(|doc| doc.path.as_path())
+            .collect::<HashSet<_>>();
+
+        let removed_docs = previous_docs
+            .values()
+            .filter(|doc| !current_paths.contains(doc.path.as_path()))
+            .collect::<Vec<_>>();
+
+        for doc in &removed_docs {
+            let destination = doc.destination(self.config.out_dir());
+
+            if destination.exists() {
+                fs::remove_file(&destination).map_err(|e| {
+                    Error::io(
+                        e,
+                        format!("Could not remove page {}", destination.display()),
+                    )
+                })?;
+            }
+
+            state.digests.remove(&doc.html_path());
+            updated.push(self.uri(doc.uri_path()));
+        }
+
+        if !written.is_empty() || !removed_docs.is_empty() {
+            let (search_index, search_index_size) = self.build_search_index(&root)?;
+            state.search_index = search_index;
+            state.search_index_size = search_index_size;
+        }
+
+        state.root = root;
+        state.navigation = navigation;
+        state.head_include = head_include;
+        state.translations = self.translations.clone();
+
+        Ok(updated)
+    }
+
+    /// Checks the links in every document, either from a previous build,
+    /// or by reading the docs directory if there has not been one.
+    pub fn check(&self, state: Option<&BuildState>) -> Result<Vec<BrokenLink>> {
+        match state {
+            Some(state) => Ok(LinkChecker::new(&self.config, &state.root).run()),
+            None => Ok(LinkChecker::new(self.config, &self.pages(&self.find_docs()?)).run()),
+        }
+    }
+
+    /// Finds links in published documents that point to drafts, which
+    /// will be broken in release builds.
+    pub fn draft_links(&self, state: Option<&BuildState>) -> Result<Vec<BrokenLink>> {
+        match state {
+            Some(state) => Ok(LinkChecker::new(self.config, &state.sources).draft_links()),
+            None => Ok(LinkChecker::new(self.config, &self.find_docs()?).draft_links()),
+        }
+    }
 
-        if custom_head.exists() {
-            let content = fs::read_to_string(custom_head)
-                .map_err(|e| Error::io(e, "Could not read custom head include file"))?;
+    /// Adds the URI prefix of this part of the site to a URI path.
+    fn uri(&self, uri_path: String) -> String {
+        format!("{}{}", self.uri_prefix, uri_path)
+    }
+
+    /// The full URL of a URI on this site, if the site's `url` is known.
+    /// Otherwise the URI is left as is.
+    fn absolute_url(&self, uri: &str) -> String {
+        format!("{}{}", self.config.url().unwrap_or(""), uri)
+    }
+
+    /// Describes the breadcrumbs of a page for search engines. Labels are
+    /// left out, since they don't point anywhere. `None` for pages at the
+    /// top of the navigation, which have no breadcrumbs to show, and when
+    /// the site's `url` isn't known, since the list needs full URLs.
+    fn breadcrumb_list(&self, ancestors: &[&Link], title: &str, path: &str) -> Option<String> {
+        let url = self.config.url()?;
+
+        if ancestors.is_empty() {
+            return None;
+        }
+
+        let items = ancestors
+            .iter()
+            .filter(|link| link.kind == LinkKind::Page)
+            .map(|link| (link.title.as_str(), link.path.as_str()))
+            .chain(std::iter::once((title, path)))
+            .enumerate()
+            .map(|(i, (name, path))| {
+                serde_json::json!({
+                    "@type": "ListItem",
+                    "position": i + 1,
+                    "name": name,
+                    "item": format!("{}{}", url, path),
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let list = serde_json::json!({
+            "@context": "https://schema.org",
+            "@type": "BreadcrumbList",
+            "itemListElement": items,
+        });
+
+        // Keeps titles from closing the script tag the list is put into
+        Some(list.to_string().replace("</", "<\\/"))
+    }
 
-            Ok(Some(content))
+    /// The URL of an image for link previews, given either as a full URL,
+    /// or as a path in the _include directory.
+    fn image_url(&self, image: &str) -> String {
+        if config::is_url(image) {
+            image.to_string()
         } else {
-            Ok(None)
+            self.absolute_url(&self.uri(Link::path_to_uri_with_extension(Path::new(image))))
+        }
+    }
+
+    /// Rewrites links to be relative to each page, if the site is built with
+    /// relative links.
+    fn relative_links(&self, root: &Directory) -> Option<RelativeLinks> {
+        if !self.config.relative_links() {
+            return None;
+        }
+
+        let dir_indices = root
+            .docs_recursive()
+            .iter()
+            .filter(|doc| doc.html_path().file_name() == Some(OsStr::new("index.html")))
+            .map(|doc| doc.uri_path())
+            .collect();
+
+        Some(RelativeLinks::new(self.config, dir_indices))
+    }
+
+    /// Where a page ends up, relative to the root of the whole site.
+    fn site_path(&self, html_path: &Path) -> PathBuf {
+        let part_dir = &self.uri_prefix[self.config.site_uri_prefix().len()..];
+
+        Path::new(part_dir.trim_start_matches('/')).join(html_path)
+    }
+
+    fn build_navigation(&self, root: &Directory, sources: &Directory) -> Result<Vec<Link>> {
+        Ok(Navigation::new(&self.config)
+            .build_for(root, sources)?
+            .into_iter()
+            .map(|link| link.with_prefix(&self.uri_prefix))
+            .collect())
+    }
+
+    /// The language part of the URI prefix, if the site is translated.
+    fn language_prefix(&self) -> String {
+        self.config
+            .language()
+            .map(|lang| format!("/{}", lang.code))
+            .unwrap_or_default()
+    }
+
+    /// Links to the root of every version of the site, in the current
+    /// language, for the version switcher.
+    fn version_links(&self) -> Vec<VersionLink> {
+        let current = match self.config.version() {
+            Some(current) => current,
+            None => return vec![],
+        };
+
+        self.config
+            .versions()
+            .iter()
+            .enumerate()
+            .map(|(i, version)| VersionLink {
+                name: version.name.clone(),
+                path: format!(
+                    "{}{}{}/",
+                    self.config.site_uri_prefix(),
+                    version.uri_prefix(),
+                    self.language_prefix()
+                ),
+                current: version == current,
+                latest: i == 0,
+                outdated: i != 0,
+            })
+            .collect()
+    }
+
+    /// Links to the given page in every language of the site, for the
+    /// language switcher. Links to the root of a language if the page has
+    /// not been translated into it.
+    fn language_links(&self, uri_path: &str) -> Vec<LanguageLink> {
+        let current = match self.config.language() {
+            Some(current) => current,
+            None => return vec![],
+        };
+
+        let version_prefix = self
+            .config
+            .version()
+            .map(|v| v.uri_prefix())
+            .unwrap_or_default();
+
+        self.config
+            .languages()
+            .iter()
+            .map(|lang| {
+                let translated = self
+                    .translations
+                    .get(&lang.code)
+                    .map(|pages| pages.contains(uri_path))
+                    .unwrap_or(false);
+
+                LanguageLink {
+                    code: lang.code.clone(),
+                    name: lang.name.clone(),
+                    path: format!(
+                        "{}{}/{}{}",
+                        self.config.site_uri_prefix(),
+                        version_prefix,
+                        lang.code,
+                        if translated { uri_path } else { "/" }
+                    ),
+                    current: lang == current,
+                }
+            })
+            .collect()
+    }
+
+    /// Where a Markdown file in the docs directory ends up in the language
+    /// being built, if translations sit next to the original pages with the
+    /// language code in their name, like `tutorial.de.md`.
+    ///
+    /// Returns None if the file is a translation into another language, or
+    /// if it has been translated into the language being built, since the
+    /// translation takes its place.
+    fn localized_path(&self, relative: &Path) -> Option<PathBuf> {
+        let current = match self.config.language() {
+            Some(current) if !self.config.language_directories() => current,
+            _ => return Some(relative.to_path_buf()),
+        };
+
+        let stem = Path::new(relative.file_stem()?);
+        let suffix = stem
+            .extension()
+            .and_then(|s| s.to_str())
+            .filter(|s| self.config.languages().iter().any(|l| l.code == *s));
+
+        match suffix {
+            Some(code) if code == current.code => {
+                Some(relative.with_file_name(stem.with_extension("md")))
+            }
+            Some(_) => None,
+            None => {
+                let translation = relative.with_file_name(format!(
+                    "{}.{}.md",
+                    stem.to_string_lossy(),
+                    current.code
+                ));
+
+                if self.config.docs_dir().join(translation).is_file() {
+                    None
+                } else {
+                    Some(relative.to_path_buf())
+                }
+            }
+        }
+    }
+
+    fn read_head_include(&self) -> Result<Option<String>> {
+        let custom_head = self
+            .config
+            .include_dirs()
+            .into_iter()
+            .rev()
+            .map(|dir| dir.join(HEAD_FILE))
+            .find(|path| path.exists());
+
+        match custom_head {
+            Some(custom_head) => {
+                let content = fs::read_to_string(custom_head)
+                    .map_err(|e| Error::io(e, "Could not read custom head include file"))?;
+
+                Ok(Some(content))
+            }
+            None => Ok(None),
         }
     }
 
-    /// Copies over all custom includes from the _includes directory
+    /// Copies over all custom includes from the _includes directories
     fn build_includes(&self) -> Result<()> {
-        let custom_assets_dir = self.config.docs_dir().join(INCLUDE_DIR);
+        for custom_assets_dir in self.config.include_dirs() {
+            for asset in WalkDir::new(&custom_assets_dir)
+                .into_iter()
+                .filter_map(|e| e.ok())
+                .filter(|e| e.path().is_file())
+                .filter(|e| e.path().file_name() != Some(OsStr::new(HEAD_FILE)))
+            {
+                self.build_include(asset.path().strip_prefix(&custom_assets_dir).unwrap())?;
+            }
+        }
 
-        for asset in WalkDir::new(&custom_assets_dir)
+        Ok(())
+    }
+
+    /// Copies over a single file, given its path inside the _includes
+    /// directories, or removes it from the site if it no longer exists in
+    /// any of them.
+    fn build_include(&self, stripped_path: &Path) -> Result<()> {
+        let destination = self.config.out_dir().join(stripped_path);
+
+        let asset = match self
+            .config
+            .include_dirs()
             .into_iter()
-            .filter_map(|e| e.ok())
-            .filter(|e| e.path().is_file())
-            .filter(|e| e.path().file_name() != Some(OsStr::new(HEAD_FILE)))
+            .rev()
+            .map(|dir| dir.join(stripped_path))
+            .find(|path| path.is_file())
         {
-            let stripped_path = asset
-                .path()
-                .strip_prefix(&custom_assets_dir)
-                .expect("asset directory was not parent of found asset");
+            Some(asset) => asset,
+            None => {
+                if destination.is_file() {
+                    fs::remove_file(&destination)
+                        .map_err(|e| Error::io(e, "Could not remove custom asset"))?;
+                }
 
-            let destination = self.config.out_dir().join(stripped_path);
+                return Ok(());
+            }
+        };
 
-            fs::create_dir_all(
-                destination
-                    .parent()
-                    .expect("asset did not have parent directory"),
-            )
-            .map_err(|e| Error::io(e, "Could not create custom asset parent directory"))?;
+        fs::create_dir_all(
+            destination
+                .parent()
+                .expect("asset did not have parent directory"),
+        )
+        .map_err(|e| Error::io(e, "Could not create custom asset parent directory"))?;
 
-            File::create(&destination)
-                .map_err(|e| Error::io(e, "Could not create custom asset in assets directory"))?;
+        File::create(&destination)
+            .map_err(|e| Error::io(e, "Could not create custom asset in assets directory"))?;
 
-            fs::copy(asset.path(), destination)
-                .map_err(|e| Error::io(e, "Could not copy custom asset"))?;
-        }
+        fs::copy(asset, destination).map_err(|e| Error::io(e, "Could not copy custom asset"))?;
 
         Ok(())
     }
@@ -110,11 +576,13 @@ impl<'a> SiteGenerator<'a> {
             crate::MERMAID_JS,
         )
         .map_err(|e| Error::io(e, "Could not write mermaid.js to assets directory"))?;
-        fs::write(
-            self.config.out_dir().join("assets").join("elasticlunr.js"),
-            crate::ELASTIC_LUNR,
-        )
-        .map_err(|e| Error::io(e, "Could not write elasticlunr.js to assets directory"))?;
+        if self.config.search().enabled && self.config.search().stemming {
+            fs::write(
+                self.config.out_dir().join("assets").join("stemmer.js"),
+                crate::STEMMER_JS,
+            )
+            .map_err(|e| Error::io(e, "Could not write stemmer.js to assets directory"))?;
+        }
         if let BuildMode::Dev = self.config.build_mode() {
             // Livereload only in release mode
             fs::write(
@@ -175,119 +643,308 @@ impl<'a> SiteGenerator<'a> {
             serde_json::Value::String(self.config.main_color_dark().to_css_string()),
         );
 
-        crate::HANDLEBARS
+        self.templates
             .render_to_write("style.css", &data, &mut style)
-            .map_err(|e| Error::handlebars(e, "Could not write custom style sheet"))
     }
 
-    fn build_directory(
+    /// Renders the given documents into HTML pages.
+    ///
+    /// A page is only written to disk if the digest of its rendered output
+    /// differs from the one found in `previous`. Returns the paths and new
+    /// digests of the pages that were written.
+    fn build_pages(
         &self,
-        dir: &Directory,
+        docs: &[&Document],
         nav: &[Link],
         head_include: Option<&str>,
-    ) -> Result<()> {
-        fs::create_dir_all(dir.destination(self.config.out_dir()))
-            .map_err(|e| Error::io(e, "Could not create site directory"))?;
+        links: Option<&RelativeLinks>,
+        timestamp: &str,
+        previous: &HashMap<PathBuf, u64>,
+    ) -> Result<Vec<(PathBuf, u64)>> {
+        let versions = self.version_links();
+        let version = versions.iter().find(|v| v.current);
+        let latest_path = format!(
+            "{}/{}{}/",
+            self.config.site_uri_prefix(),
+            LATEST_VERSION_ALIAS,
+            self.language_prefix()
+        );
+        let strings = self.config.strings();
+        let lang = self
+            .config
+            .language()
+            .map(|l| l.code.as_str())
+            .unwrap_or("en");
+        let reading_order = reading_order(nav);
+        let search = self.config.search().enabled;
+        let stemming = search && self.config.search().stemming;
 
-        let results: Result<Vec<()>> = dir
-            .docs
+        let results: Result<Vec<Option<(PathBuf, u64)>>> = docs
             .par_iter()
             .map(|doc| {
-                let mut file =
-                    File::create(doc.destination(self.config.out_dir())).map_err(|e| {
-                        Error::io(
-                            e,
-                            format!(
-                                "Could not create page {}",
-                                doc.destination(self.config.out_dir()).display()
-                            ),
-                        )
-                    })?;
-
                 let page_title = if doc.uri_path() == "/" {
                     self.config.title().to_string()
                 } else {
                     doc.title().to_string()
                 };
 
+                // Scripts can't have their links rewritten, so they get a
+                // prefix relative to the page instead
+                let script_uri_prefix = match links {
+                    Some(_) => match doc.html_path().components().count() {
+                        1 => String::from("."),
+                        n => vec![".."; n - 1].join("/"),
+                    },
+                    None => self.uri_prefix.clone(),
+                };
+
+                let description = doc
+                    .description()
+                    .unwrap_or_else(|| format!("Documentation for {}", self.config.title()));
+                let og_image = doc
+                    .frontmatter
+                    .og_image
+                    .as_deref()
+                    .or_else(|| self.config.og_image())
+                    .map(|image| self.image_url(image));
+                let canonical_url = self
+                    .config
+                    .url()
+                    .map(|_| self.absolute_url(&self.uri(doc.uri_path())));
+
+                let current_path = self.uri(doc.uri_path());
+                let (previous_page, next_page) = match doc.frontmatter.pagination {
+                    Some(false) => (None, None),
+                    _ => match reading_order.iter().position(|l| l.path == current_path) {
+                        Some(i) => (
+                            i.checked_sub(1).map(|i| PageLink::from(reading_order[i])),
+                            reading_order.get(i + 1).map(|link| PageLink::from(*link)),
+                        ),
+                        None => (None, None),
+                    },
+                };
+                let toc = self.config.toc().with(doc.frontmatter.toc.as_ref());
+                let breadcrumbs = ancestors(nav, &current_path).unwrap_or_default();
+                let breadcrumb_list =
+                    self.breadcrumb_list(&breadcrumbs, &page_title, &current_path);
+
                 let data = TemplateData {
-                    content: doc.html().to_string(),
-                    headings: doc.headings().iter().map(|heading| {
-                        let mut map = BTreeMap::new();
-                        map.insert("title", heading.title.clone());
-                        map.insert("anchor", heading.anchor.clone());
-                        map.insert("level", heading.level.to_string());
-
-                        map
-                    }).collect::<Vec<_>>(),
+                    content: prefix_links(doc.html(), &self.uri_prefix),
+                    headings: toc.entries(doc.headings()),
+                    toc: toc.enabled,
                     navigation: &nav,
-                    current_path: doc.uri_path(),
+                    current_path,
+                    previous: previous_page,
+                    next: next_page,
+                    breadcrumbs,
+                    breadcrumb_list,
+                    uri_prefix: &self.uri_prefix,
+                    script_uri_prefix,
+                    relative_links: links.is_some(),
+                    search,
+                    stemming,
+                    versions: &versions,
+                    version,
+                    latest_path: &latest_path,
+                    languages: self.language_links(&doc.uri_path()),
+                    lang,
+                    strings: &strings,
                     project_title: self.config.title().to_string(),
                     logo: self.config.logo().map(|l| l.to_string()),
                     build_mode: self.config.build_mode().to_string(),
-                    timestamp: &self.timestamp,
+                    timestamp,
+                    page: &doc.frontmatter,
                     page_title,
+                    description,
+                    canonical_url,
+                    og_image,
                     head_include,
                 };
 
-                crate::HANDLEBARS
-                    .render_to_write("page", &data, &mut file)
-                    .map_err(|e| Error::handlebars(e, "Could not render template"))?;
+                let layout = doc.frontmatter.layout.as_deref().unwrap_or("page");
+                if !self.templates.has(layout) {
+                    return Err(Error::new(format!(
+                        "Unknown layout \"{}\" in {}",
+                        layout,
+                        self.config.docs_dir().join(&doc.source).display()
+                    )));
+                }
+
+                let mut page = self.templates.render(layout, &data)?;
+                if let Some(links) = links {
+                    page = links.rewrite(&page, &self.site_path(&doc.html_path()));
+                }
+
+                let mut hasher = DefaultHasher::new();
+                page.hash(&mut hasher);
+                let digest = hasher.finish();
+
+                if previous.get(&doc.html_path()) == Some(&digest) {
+                    return Ok(None);
+                }
 
-                Ok(())
+                let destination = doc.destination(self.config.out_dir());
+
+                fs::create_dir_all(destination.parent().unwrap())
+                    .map_err(|e| Error::io(e, "Could not create site directory"))?;
+
+                fs::write(&destination, page).map_err(|e| {
+                    Error::io(
+                        e,
+                        format!("Could not create page {}", destination.display()),
+                    )
+                })?;
+
+                Ok(Some((doc.html_path(), digest)))
             })
             .collect();
-        let _ok = results?;
 
-        dir.dirs
-            .par_iter()
-            .map(|d| self.build_directory(&d, &nav, head_include))
-            .collect()
+        Ok(results?.into_iter().flatten().collect())
     }
 
-    fn build_search_index(&self, root: &Directory) -> Result<()> {
-        let mut index = Index::new(&["title", "uri", "body"]);
+    /// Writes the search index of the site, and keeps it for the preview
+    /// server to search.
+    fn build_search_index(&self, root: &Directory) -> Result<(Option<SearchIndex>, IndexSize)> {
+        let mut settings = self.config.search();
+        if !settings.enabled {
+            return Ok((None, IndexSize::default()));
+        }
 
-        self.build_search_index_for_dir(root, &mut index);
+        // Pages opened from disk can't fetch the parts of a sharded index
+        if self.config.relative_links() {
+            settings.sharding = None;
+        }
 
-        fs::write(
-            self.config.out_dir().join("search_index.json"),
-            index.to_json().as_bytes(),
-        )
-        .map_err(|e| Error::io(e, "Could not create search index"))
+        let exclude = settings.exclude.clone();
+        let mut index = SearchIndex::new(settings);
+
+        self.build_search_index_for_dir(root, &exclude, &mut index);
+
+        let shard_dir = self.config.out_dir().join("search_index");
+        if shard_dir.exists() {
+            fs::remove_dir_all(&shard_dir)
+                .map_err(|e| Error::io(e, "Could not clear search index directory"))?;
+        }
+
+        let mut size = IndexSize::default();
+
+        for (path, contents) in index.files() {
+            let destination = self.config.out_dir().join(path);
+
+            if let Some(parent) = destination.parent() {
+                fs::create_dir_all(parent)
+                    .map_err(|e| Error::io(e, "Could not create search index directory"))?;
+            }
+
+            // Browsers don't let pages opened from disk fetch the index, but
+            // they can still load it as a script
+            if self.config.relative_links() {
+                fs::write(
+                    destination.with_extension("js"),
+                    format!("var DOCTAVE_SEARCH_INDEX = {};\n", contents),
+                )
+                .map_err(|e| Error::io(e, "Could not create search index"))?;
+            }
+
+            fs::write(&destination, contents.as_bytes())
+                .map_err(|e| Error::io(e, "Could not create search index"))?;
+
+            size.bytes += contents.len() as u64;
+            size.files += 1;
+        }
+
+        Ok((Some(index), size))
     }
 
-    fn build_search_index_for_dir(&self, root: &Directory, index: &mut Index) {
+    fn build_search_index_for_dir(
+        &self,
+        root: &Directory,
+        exclude: &[Pattern],
+        index: &mut SearchIndex,
+    ) {
         for doc in &root.docs {
-            index.add_doc(
-                &doc.id.to_string(),
-                &[
-                    &doc.title(),
-                    &doc.uri_path().as_str(),
-                    doc.markdown_section(),
-                ],
-            );
+            if doc.frontmatter.search == Some(false)
+                || exclude.iter().any(|p| config::glob_matches(p, &doc.path))
+            {
+                continue;
+            }
+
+            // With relative links, the search script resolves URIs against
+            // the root of this part of the site
+            let uri = if self.config.relative_links() {
+                Link::path_to_uri_with_extension(&doc.html_path())
+                    .trim_start_matches('/')
+                    .to_string()
+            } else {
+                self.uri(doc.uri_path())
+            };
+
+            // Sharding by section puts each top-level directory in its own
+            // file
+            let html_path = doc.html_path();
+            let directory = match html_path.parent().and_then(|p| p.iter().next()) {
+                Some(dir) => dir.to_string_lossy().into_owned(),
+                None => String::new(),
+            };
+
+            for section in search::sections(doc.title(), doc.html(), doc.headings()) {
+                let uri = match &section.anchor {
+                    Some(anchor) => format!("{}#{}", uri, anchor),
+                    None => uri.clone(),
+                };
+
+                index.add(
+                    &directory,
+                    SearchDoc {
+                        title: section.title,
+                        page: doc.title().to_string(),
+                        uri,
+                        excerpt: section.excerpt,
+                    },
+                    &section.body,
+                );
+            }
         }
         for dir in &root.dirs {
-            self.build_search_index_for_dir(&dir, index);
+            self.build_search_index_for_dir(&dir, exclude, index);
         }
     }
 
-    fn find_docs(&self, project_root: &Path) -> Directory {
-        let mut root_dir = self
-            .walk_dir(project_root.join("docs"))
+    /// Reads and parses all the documents in the docs directory.
+    ///
+    /// Keeps going if a document can't be loaded, so that all broken
+    /// documents can be reported at once.
+    pub fn find_docs(&self) -> Result<Directory> {
+        let mut errors = vec![];
+
+        let root = self
+            .walk_dir(self.config.docs_dir(), &mut errors)
             .unwrap_or(Directory {
-                path: project_root.join("docs"),
+                path: self.config.docs_dir().to_path_buf(),
                 docs: vec![],
                 dirs: vec![],
             });
 
-        self.generate_missing_indices(&mut root_dir);
+        if errors.is_empty() {
+            Ok(root)
+        } else {
+            Err(Self::broken_documents(errors))
+        }
+    }
 
-        root_dir
+    fn broken_documents(errors: Vec<Error>) -> Error {
+        Error::new(format!(
+            "Found {} document(s) that could not be loaded:\n\n{}",
+            errors.len(),
+            errors
+                .iter()
+                .map(|e| e.to_string())
+                .collect::<Vec<_>>()
+                .join("\n\n")
+        ))
     }
 
-    fn walk_dir<P: AsRef<Path>>(&self, dir: P) -> Option<Directory> {
+    fn walk_dir<P: AsRef<Path>>(&self, dir: P, errors: &mut Vec<Error>) -> Option<Directory> {
         let mut docs = vec![];
         let mut dirs = vec![];
 
@@ -301,7 +958,15 @@ impl<'a> SiteGenerator<'a> {
             if entry.file_type().is_file() && entry.path().extension() == Some(OsStr::new("md")) {
                 let path = entry.path().strip_prefix(self.config.docs_dir()).unwrap();
 
-                docs.push(Document::load(entry.path(), path));
+                let page = match self.localized_path(path) {
+                    Some(page) => page,
+                    None => continue,
+                };
+
+                match Document::load(entry.path(), &page) {
+                    Ok(doc) => docs.push(doc.with_source(path)),
+                    Err(e) => errors.push(e),
+                }
             } else {
                 let path = entry.into_path();
 
@@ -309,7 +974,7 @@ impl<'a> SiteGenerator<'a> {
                     continue;
                 }
 
-                if let Some(dir) = self.walk_dir(path) {
+                if let Some(dir) = self.walk_dir(path, errors) {
                     dirs.push(dir);
                 }
             }
@@ -326,6 +991,20 @@ impl<'a> SiteGenerator<'a> {
         }
     }
 
+    /// The documents to render from the ones found in the docs directory.
+    /// Drafts are left out of release builds, and directories without an
+    /// index get a generated one.
+    fn pages(&self, sources: &Directory) -> Directory {
+        let mut root = sources.clone();
+
+        if let BuildMode::Release = self.config.build_mode() {
+            root.remove_drafts();
+        }
+        self.generate_missing_indices(&mut root);
+
+        root
+    }
+
     fn generate_missing_indices(&self, dir: &mut Directory) {
         if dir
             .docs
@@ -350,11 +1029,8 @@ impl<'a> SiteGenerator<'a> {
             .collect::<Vec<_>>()
             .join("\n");
 
-        let mut frontmatter = BTreeMap::new();
-        frontmatter.insert(
-            "title".to_string(),
-            format!("{}", dir.path().file_name().unwrap().to_string_lossy()),
-        );
+        let frontmatter =
+            Frontmatter::with_title(dir.path().file_name().unwrap().to_string_lossy());
 
         let tmp = dir.path().join("README.md");
         let path = tmp.strip_prefix(self.config.docs_dir()).unwrap();
@@ -383,16 +1059,199 @@ impl<'a> SiteGenerator<'a> {
     }
 }
 
+/// Everything from a previous build needed to incrementally update the site.
+#[derive(Debug, Clone)]
+pub struct BuildState {
+    /// The documents as found in the docs folder
+    sources: Directory,
+    /// The documents that were rendered, including generated indices
+    root: Directory,
+    navigation: Vec<Link>,
+    head_include: Option<String>,
+    /// The translations the pages were rendered with
+    translations: Translations,
+    /// The timestamp the pages were first rendered with. Rebuilds keep
+    /// using it, so pages that did not change are not written again.
+    timestamp: String,
+    /// Digests of each rendered page, keyed by their HTML path
+    digests: HashMap<PathBuf, u64>,
+    /// The search index, unless the site has no search
+    search_index: Option<SearchIndex>,
+    search_index_size: IndexSize,
+}
+
+impl BuildState {
+    /// The documents that were rendered
+    pub fn root(&self) -> &Directory {
+        &self.root
+    }
+
+    pub fn translations(&self) -> &Translations {
+        &self.translations
+    }
+
+    pub fn search_index(&self) -> Option<&SearchIndex> {
+        self.search_index.as_ref()
+    }
+
+    pub fn search_index_size(&self) -> IndexSize {
+        self.search_index_size
+    }
+
+    /// The URI paths of every rendered page, without the URI prefix.
+    pub fn page_uris(&self) -> HashSet<String> {
+        self.root
+            .docs_recursive()
+            .iter()
+            .map(|doc| doc.uri_path())
+            .collect()
+    }
+}
+
 #[derive(Debug, Clone, Serialize)]
 pub struct TemplateData<'a> {
     pub content: String,
-    pub headings: Vec<BTreeMap<&'static str, String>>,
+    /// The headings to list under "On this page", nested under the ones
+    /// they are in
+    pub headings: Vec<TocEntry>,
+    /// Whether to show the "On this page" sidebar
+    pub toc: bool,
     pub navigation: &'a [Link],
+    /// The pages before and after this one in the navigation
+    pub previous: Option<PageLink<'a>>,
+    pub next: Option<PageLink<'a>>,
+    /// The links leading to this page in the navigation, from the top
+    pub breadcrumbs: Vec<&'a Link>,
+    /// The breadcrumbs as a schema.org `BreadcrumbList` in JSON-LD
+    pub breadcrumb_list: Option<String>,
     pub head_include: Option<&'a str>,
+    pub page: &'a Frontmatter,
     pub current_path: String,
+    pub uri_prefix: &'a str,
+    /// The URI prefix for scripts to use
+    pub script_uri_prefix: String,
+    pub relative_links: bool,
+    /// Whether the site has search
+    pub search: bool,
+    /// Whether search terms are stemmed in the browser
+    pub stemming: bool,
+    pub versions: &'a [VersionLink],
+    pub version: Option<&'a VersionLink>,
+    pub latest_path: &'a str,
+    pub languages: Vec<LanguageLink>,
+    pub lang: &'a str,
+    pub strings: &'a BTreeMap<String, String>,
     pub page_title: String,
+    /// A short description of the page for search engines and link previews
+    pub description: String,
+    /// The full URL of the page, if the site's URL is known
+    pub canonical_url: Option<String>,
+    pub og_image: Option<String>,
     pub logo: Option<String>,
     pub project_title: String,
     pub build_mode: String,
     pub timestamp: &'a str,
 }
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PageLink<'a> {
+    pub title: &'a str,
+    pub path: &'a str,
+}
+
+impl<'a> From<&'a Link> for PageLink<'a> {
+    fn from(link: &'a Link) -> Self {
+        PageLink {
+            title: &link.title,
+            path: &link.path,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct VersionLink {
+    pub name: String,
+    pub path: String,
+    pub current: bool,
+    pub latest: bool,
+    pub outdated: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LanguageLink {
+    pub code: String,
+    pub name: String,
+    pub path: String,
+    pub current: bool,
+}
+
+/// The pages in the navigation in the order they are read in, with each
+/// link followed by its children. Labels and links to other sites are left
+/// out, as are pages after the first time they show up.
+fn reading_order(links: &[Link]) -> Vec<&Link> {
+    fn visit<'a>(links: &'a [Link], seen: &mut HashSet<&'a str>, order: &mut Vec<&'a Link>) {
+        for link in links {
+            if link.kind == LinkKind::Page && seen.insert(&link.path) {
+                order.push(link);
+            }
+
+            visit(&link.children, seen, order);
+        }
+    }
+
+    let mut order = vec![];
+    visit(links, &mut HashSet::new(), &mut order);
+
+    order
+}
+
+/// The links leading to the page at `path` in the navigation, starting
+/// from the top level. `None` if the page isn't in the navigation.
+fn ancestors<'a>(links: &'a [Link], path: &str) -> Option<Vec<&'a Link>> {
+    for link in links {
+        if link.kind == LinkKind::Page && link.path == path {
+            return Some(vec![]);
+        }
+
+        if let Some(mut chain) = ancestors(&link.children, path) {
+            chain.insert(0, link);
+            return Some(chain);
+        }
+    }
+
+    None
+}
+
+/// Adds a prefix to every root-relative link and image in some rendered
+/// HTML, so that they keep pointing inside the site when it is not served
+/// from the root.
+pub fn prefix_links(html: &str, prefix: &str) -> String {
+    static ATTRIBUTES: &[&str] = &[" href=\"/", " src=\"/", " href='/", " src='/"];
+
+    if prefix.is_empty() {
+        return html.to_string();
+    }
+
+    let mut result = String::with_capacity(html.len());
+    let mut rest = html;
+
+    while let Some((start, attribute)) = ATTRIBUTES
+        .iter()
+        .filter_map(|a| rest.find(a).map(|start| (start, a)))
+        .min()
+    {
+        let slash = start + attribute.len() - 1;
+        result.push_str(&rest[..slash]);
+
+        // Leave protocol-relative URLs like //example.com alone
+        if !rest[slash + 1..].starts_with('/') {
+            result.push_str(prefix);
+        }
+
+        result.push('/');
+        rest = &rest[slash + 1..];
+    }
+
+    result.push_str(rest);
+    result
+}
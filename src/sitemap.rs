@@ -0,0 +1,145 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// A page to list in the sitemap.
+pub struct Entry {
+    /// The URI of the page, including any prefix
+    pub uri: String,
+    /// When the page last changed, as a W3C datetime
+    pub last_modified: Option<String>,
+}
+
+/// Renders a sitemap listing the pages of the site hosted at `url`.
+pub fn render(url: &str, entries: &[Entry]) -> String {
+    let mut sitemap = String::from(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\">\n",
+    );
+
+    for entry in entries {
+        sitemap.push_str("  <url>\n");
+        sitemap.push_str(&format!(
+            "    <loc>{}</loc>\n",
+            escape(&format!("{}{}", url, entry.uri))
+        ));
+        if let Some(last_modified) = &entry.last_modified {
+            sitemap.push_str(&format!(
+                "    <lastmod>{}</lastmod>\n",
+                escape(last_modified)
+            ));
+        }
+        sitemap.push_str("  </url>\n");
+    }
+
+    sitemap.push_str("</urlset>\n");
+    sitemap
+}
+
+/// Renders a robots.txt with the given rules, that also points crawlers
+/// to the sitemap.
+pub fn robots(rules: &str, sitemap_url: &str) -> String {
+    format!("{}\n\nSitemap: {}\n", rules.trim_end(), sitemap_url)
+}
+
+/// Formats a time as a W3C datetime in UTC, like `2020-11-05T09:30:00Z`.
+pub fn format_time(time: SystemTime) -> String {
+    let seconds = time
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+
+    let (year, month, day) = civil_from_days((seconds / 86400) as i64);
+    let time_of_day = seconds % 86400;
+
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        time_of_day / 3600,
+        time_of_day % 3600 / 60,
+        time_of_day % 60
+    )
+}
+
+/// Turns a number of days since 1970-01-01 into a year, month and day.
+///
+/// See http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    (year, month as u32, day as u32)
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn formats_times_in_utc() {
+        assert_eq!(format_time(UNIX_EPOCH), "1970-01-01T00:00:00Z");
+        assert_eq!(
+            format_time(UNIX_EPOCH + Duration::from_secs(951_827_696)),
+            "2000-02-29T12:34:56Z"
+        );
+        assert_eq!(
+            format_time(UNIX_EPOCH + Duration::from_secs(1_609_459_199)),
+            "2020-12-31T23:59:59Z"
+        );
+    }
+
+    #[test]
+    fn renders_entries() {
+        let sitemap = render(
+            "https://example.com",
+            &[
+                Entry {
+                    uri: String::from("/"),
+                    last_modified: Some(String::from("2020-11-05T09:30:00Z")),
+                },
+                Entry {
+                    uri: String::from("/search?q=a&b"),
+                    last_modified: None,
+                },
+            ],
+        );
+
+        assert!(sitemap.contains(
+            "<url>\n    <loc>https://example.com/</loc>\n    <lastmod>2020-11-05T09:30:00Z</lastmod>\n  </url>"
+        ));
+        assert!(sitemap
+            .contains("<url>\n    <loc>https://example.com/search?q=a&amp;b</loc>\n  </url>"));
+    }
+
+    #[test]
+    fn robots_points_to_sitemap() {
+        assert_eq!(
+            robots(
+                "User-agent: *\nDisallow: /private\n\n",
+                "https://example.com/sitemap.xml"
+            ),
+            "User-agent: *\nDisallow: /private\n\nSitemap: https://example.com/sitemap.xml\n"
+        );
+    }
+}
@@ -0,0 +1,325 @@
+/// Stems a lowercase word with the Porter stemmer, as implemented by
+/// elasticlunr.js.
+///
+/// Words in the search index are stemmed here, and words in search queries
+/// are stemmed in the browser by elasticlunr.js, so the two have to agree
+/// on every word. This follows the JavaScript implementation step by step
+/// rather than the original algorithm, including how it treats `y`.
+pub fn stem(word: &str) -> String {
+    if word.encode_utf16().count() < 3 {
+        return word.to_string();
+    }
+
+    let starts_with_y = word.starts_with('y');
+    let mut w = if starts_with_y {
+        format!("Y{}", &word[1..])
+    } else {
+        word.to_string()
+    };
+
+    // Step 1a
+    if strip(&w, "sses").is_some() || strip(&w, "ies").is_some() {
+        w.truncate(w.len() - 2);
+    } else if let Some(stem) = strip(&w, "s") {
+        if stem.chars().count() > 1 && !stem.ends_with('s') {
+            w.pop();
+        }
+    }
+
+    // Step 1b
+    if let Some(stem) = strip(&w, "eed") {
+        if measure_above(stem, 0) {
+            w.pop();
+        }
+    } else if let Some(stem) = strip(&w, "ed").or_else(|| strip(&w, "ing")) {
+        if has_vowel(stem) {
+            w = stem.to_string();
+
+            if w.ends_with("at") || w.ends_with("bl") || w.ends_with("iz") {
+                w.push('e');
+            } else if ends_with_double_consonant(&w) {
+                w.pop();
+            } else if ends_with_short_syllable(&w) {
+                w.push('e');
+            }
+        }
+    }
+
+    // Step 1c
+    if let Some(stem) = strip(&w, "y") {
+        if stem.chars().count() > 1 && !stem.ends_with(is_vowel) {
+            w = format!("{}i", stem);
+        }
+    }
+
+    // Step 2
+    if let Some((stem, replacement)) = longest_suffix(&w, STEP_2) {
+        if measure_above(stem, 0) {
+            w = format!("{}{}", stem, replacement);
+        }
+    }
+
+    // Step 3
+    if let Some((stem, replacement)) = longest_suffix(&w, STEP_3) {
+        if measure_above(stem, 0) {
+            w = format!("{}{}", stem, replacement);
+        }
+    }
+
+    // Step 4
+    if let Some((stem, _)) = longest_suffix(&w, STEP_4) {
+        if measure_above(stem, 1) {
+            w = stem.to_string();
+        }
+    } else if let Some(stem) = strip(&w, "sion").or_else(|| strip(&w, "tion")) {
+        let stem = format!("{}{}", stem, &w[stem.len()..stem.len() + 1]);
+        if measure_above(&stem, 1) {
+            w = stem;
+        }
+    }
+
+    // Step 5
+    if let Some(stem) = strip(&w, "e") {
+        if measure_above(stem, 1) || (measure_is_one(stem) && !ends_with_short_syllable(stem)) {
+            w = stem.to_string();
+        }
+    }
+
+    if w.ends_with("ll") && measure_above(&w, 1) {
+        w.pop();
+    }
+
+    if starts_with_y {
+        w.replace_range(..1, "y");
+    }
+
+    w
+}
+
+static STEP_2: &[(&str, &str)] = &[
+    ("ational", "ate"),
+    ("tional", "tion"),
+    ("enci", "ence"),
+    ("anci", "ance"),
+    ("izer", "ize"),
+    ("bli", "ble"),
+    ("alli", "al"),
+    ("entli", "ent"),
+    ("eli", "e"),
+    ("ousli", "ous"),
+    ("ization", "ize"),
+    ("ation", "ate"),
+    ("ator", "ate"),
+    ("alism", "al"),
+    ("iveness", "ive"),
+    ("fulness", "ful"),
+    ("ousness", "ous"),
+    ("aliti", "al"),
+    ("iviti", "ive"),
+    ("biliti", "ble"),
+    ("logi", "log"),
+];
+
+static STEP_3: &[(&str, &str)] = &[
+    ("icate", "ic"),
+    ("ative", ""),
+    ("alize", "al"),
+    ("iciti", "ic"),
+    ("ical", "ic"),
+    ("ful", ""),
+    ("ness", ""),
+];
+
+static STEP_4: &[(&str, &str)] = &[
+    ("al", ""),
+    ("ance", ""),
+    ("ence", ""),
+    ("er", ""),
+    ("ic", ""),
+    ("able", ""),
+    ("ible", ""),
+    ("ant", ""),
+    ("ement", ""),
+    ("ment", ""),
+    ("ent", ""),
+    ("ou", ""),
+    ("ism", ""),
+    ("ate", ""),
+    ("iti", ""),
+    ("ous", ""),
+    ("ive", ""),
+    ("ize", ""),
+];
+
+/// The rest of a word before a suffix, as long as there is something left.
+fn strip<'a>(word: &'a str, suffix: &str) -> Option<&'a str> {
+    word.strip_suffix(suffix).filter(|stem| !stem.is_empty())
+}
+
+/// The longest of the suffixes the word ends with, as the rest of the word
+/// and the suffix's replacement.
+fn longest_suffix<'a>(
+    word: &'a str,
+    suffixes: &[(&str, &'static str)],
+) -> Option<(&'a str, &'static str)> {
+    suffixes
+        .iter()
+        .filter_map(|(suffix, replacement)| Some((strip(word, suffix)?, *replacement)))
+        .min_by_key(|(stem, _)| stem.len())
+}
+
+fn is_vowel(c: char) -> bool {
+    "aeiou".contains(c)
+}
+
+/// A vowel or `y`.
+fn is_vowel_or_y(c: char) -> bool {
+    "aeiouy".contains(c)
+}
+
+/// Whether a word ends with the same consonant twice, other than `l`, `s`
+/// or `z`.
+fn ends_with_double_consonant(word: &str) -> bool {
+    let mut chars = word.chars().rev();
+
+    match (chars.next(), chars.next()) {
+        (Some(last), Some(before)) => last == before && !"aeiouylsz".contains(last),
+        _ => false,
+    }
+}
+
+/// The parts of a word that its measure is counted from.
+#[derive(Debug, Clone, Copy)]
+enum Part {
+    /// A consonant, followed by any consonants other than `y`
+    Consonants,
+    /// `Consonants`, or nothing
+    MaybeConsonants,
+    /// A vowel or `y`, followed by any vowels
+    Vowels,
+    /// `Vowels`, or nothing
+    MaybeVowels,
+    /// A single vowel or `y`
+    Vowel,
+    /// A single consonant other than `w`, `x` or `y`
+    ShortConsonant,
+}
+
+impl Part {
+    /// How many characters from the start of the word this part can cover.
+    fn lengths(self, word: &[char]) -> Vec<usize> {
+        let repeated = |first: fn(char) -> bool, more: fn(char) -> bool| match word.first() {
+            Some(c) if first(*c) => {
+                let rest = word[1..].iter().take_while(|c| more(**c)).count();
+                (1..=1 + rest).collect()
+            }
+            _ => vec![],
+        };
+        let single = |matches: fn(char) -> bool| match word.first() {
+            Some(c) if matches(*c) => vec![1],
+            _ => vec![],
+        };
+
+        match self {
+            Part::Consonants => repeated(|c| !is_vowel(c), |c| !is_vowel_or_y(c)),
+            Part::Vowels => repeated(is_vowel_or_y, is_vowel),
+            Part::MaybeConsonants => [vec![0], Part::Consonants.lengths(word)].concat(),
+            Part::MaybeVowels => [vec![0], Part::Vowels.lengths(word)].concat(),
+            Part::Vowel => single(is_vowel_or_y),
+            Part::ShortConsonant => single(|c| !"aeiouwxy".contains(c)),
+        }
+    }
+}
+
+/// Whether the start of a word, or the whole word if `whole` is set, is
+/// made of the given parts.
+fn starts_with_parts(word: &[char], parts: &[Part], whole: bool) -> bool {
+    match parts.split_first() {
+        None => !whole || word.is_empty(),
+        Some((part, rest)) => part
+            .lengths(word)
+            .into_iter()
+            .any(|length| starts_with_parts(&word[length..], rest, whole)),
+    }
+}
+
+fn chars(word: &str) -> Vec<char> {
+    word.chars().collect()
+}
+
+/// Whether a stem has more than `measure` vowel-consonant sequences. Only
+/// 0 and 1 are needed.
+fn measure_above(stem: &str, measure: usize) -> bool {
+    let mut parts = vec![Part::MaybeConsonants];
+    for _ in 0..=measure {
+        parts.extend(&[Part::Vowels, Part::Consonants]);
+    }
+
+    starts_with_parts(&chars(stem), &parts, false)
+}
+
+/// Whether a stem has exactly one vowel-consonant sequence.
+fn measure_is_one(stem: &str) -> bool {
+    let parts = [
+        Part::MaybeConsonants,
+        Part::Vowels,
+        Part::Consonants,
+        Part::MaybeVowels,
+    ];
+
+    starts_with_parts(&chars(stem), &parts, true)
+}
+
+/// Whether a stem contains a vowel.
+fn has_vowel(stem: &str) -> bool {
+    starts_with_parts(&chars(stem), &[Part::MaybeConsonants, Part::Vowel], false)
+}
+
+/// Whether a word is a consonant, a vowel and a consonant other than `w`,
+/// `x` or `y`, like "hop".
+fn ends_with_short_syllable(word: &str) -> bool {
+    let parts = [Part::Consonants, Part::Vowel, Part::ShortConsonant];
+
+    starts_with_parts(&chars(word), &parts, true)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn stems_words() {
+        let words = vec![
+            ("installing", "instal"),
+            ("installation", "instal"),
+            ("caresses", "caress"),
+            ("ponies", "poni"),
+            ("cats", "cat"),
+            ("agreed", "agre"),
+            ("hopping", "hop"),
+            ("filing", "file"),
+            ("happy", "happi"),
+            ("relational", "relat"),
+            ("electrical", "electr"),
+            ("adjustment", "adjust"),
+            ("controlling", "control"),
+            ("yelling", "yell"),
+            ("is", "is"),
+            ("ties", "ti"),
+            ("sky", "ski"),
+            ("toy", "toy"),
+            ("feed", "feed"),
+            ("hopefulness", "hope"),
+            ("generalization", "gener"),
+            ("conditional", "condit"),
+            ("yearly", "yearli"),
+            ("adoption", "adopt"),
+            ("rate", "rate"),
+            ("roll", "roll"),
+        ];
+
+        for (word, expected) in words {
+            assert_eq!(stem(word), expected, "stemming {}", word);
+        }
+    }
+}
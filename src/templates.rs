@@ -0,0 +1,138 @@
+use std::collections::BTreeMap;
+use std::ffi::OsStr;
+use std::fs;
+use std::io::Write;
+use std::path::PathBuf;
+
+use handlebars::Handlebars;
+use serde::Serialize;
+
+use crate::config::Config;
+use crate::{Error, Result};
+
+static BUILTIN_TEMPLATES: &[(&str, &str)] = &[
+    ("page", include_str!("../templates/page.html")),
+    ("landing", include_str!("../templates/landing.html")),
+    ("blank", include_str!("../templates/blank.html")),
+    ("head", include_str!("../templates/head.html")),
+    ("header", include_str!("../templates/header.html")),
+    ("scripts", include_str!("../templates/scripts.html")),
+    ("wave_footer", include_str!("../templates/wave_footer.html")),
+    ("navigation", include_str!("../templates/navigation.html")),
+    ("search", include_str!("../templates/search.html")),
+    ("page_nav", include_str!("../templates/page_nav.html")),
+    (
+        "nested_navigation",
+        include_str!("../templates/nested_navigation.html"),
+    ),
+    ("style.css", include_str!("../templates/style.css")),
+    ("error", include_str!("../templates/error.html")),
+    ("redirect", include_str!("../templates/redirect.html")),
+];
+
+/// The Handlebars templates used to render a site.
+///
+/// Starts out with Doctave's built-in templates, which a project can
+/// replace by putting files with the same name, like `page.html`, into
+/// its templates directory. Any other `.html` files in that directory are
+/// registered as well, so they can be used as partials or page layouts.
+pub struct Templates {
+    handlebars: Handlebars<'static>,
+    /// Templates provided by the project, and the files they came from
+    overrides: BTreeMap<String, PathBuf>,
+}
+
+impl Templates {
+    /// Only the templates that ship with Doctave.
+    pub fn builtin() -> Self {
+        let mut handlebars = Handlebars::new();
+
+        for (name, template) in BUILTIN_TEMPLATES {
+            handlebars.register_template_string(name, template).unwrap();
+        }
+
+        Templates {
+            handlebars,
+            overrides: BTreeMap::new(),
+        }
+    }
+
+    /// The built-in templates, with any templates found in the project's
+    /// templates directory layered on top.
+    pub fn load(config: &Config) -> Result<Self> {
+        let mut templates = Self::builtin();
+        let dir = config.templates_dir();
+
+        if !dir.is_dir() {
+            return Ok(templates);
+        }
+
+        let entries = fs::read_dir(&dir).map_err(|e| {
+            Error::io(
+                e,
+                format!("Could not read templates directory {}", dir.display()),
+            )
+        })?;
+
+        for entry in entries.filter_map(|e| e.ok()) {
+            let path = entry.path();
+
+            if !path.is_file() || path.extension() != Some(OsStr::new("html")) {
+                continue;
+            }
+
+            let name = path.file_stem().unwrap().to_string_lossy().to_string();
+            let template = fs::read_to_string(&path)
+                .map_err(|e| Error::io(e, format!("Could not read template {}", path.display())))?;
+
+            templates
+                .handlebars
+                .register_template_string(&name, template)
+                .map_err(|e| {
+                    Error::template(e, format!("Could not parse template {}", path.display()))
+                })?;
+
+            templates.overrides.insert(name, path);
+        }
+
+        Ok(templates)
+    }
+
+    /// Whether a template with the given name exists. Any template can be
+    /// used as a page layout.
+    pub fn has(&self, name: &str) -> bool {
+        self.handlebars.has_template(name)
+    }
+
+    pub fn render<T: Serialize>(&self, name: &str, data: &T) -> Result<String> {
+        self.handlebars
+            .render(name, data)
+            .map_err(|e| self.render_error(e))
+    }
+
+    pub fn render_to_write<T: Serialize, W: Write>(
+        &self,
+        name: &str,
+        data: &T,
+        writer: W,
+    ) -> Result<()> {
+        self.handlebars
+            .render_to_write(name, data, writer)
+            .map_err(|e| self.render_error(e))
+    }
+
+    /// Points the error at the project's own template file, if the error
+    /// happened in one.
+    fn render_error(&self, error: handlebars::RenderError) -> Error {
+        let message = match error
+            .template_name
+            .as_ref()
+            .and_then(|name| self.overrides.get(name))
+        {
+            Some(path) => format!("Could not render template {}", path.display()),
+            None => String::from("Could not render template"),
+        };
+
+        Error::handlebars(error, message)
+    }
+}
@@ -0,0 +1,255 @@
+use serde::{Deserialize, Serialize};
+
+use crate::Heading;
+
+/// The `toc` setting in doctave.yaml or in the frontmatter of a page.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum TocYaml {
+    /// Whether to show the table of contents at all
+    Enabled(bool),
+    /// Which levels of headings to show, like 2 for `##`
+    Levels {
+        min_level: Option<u32>,
+        max_level: Option<u32>,
+    },
+}
+
+impl TocYaml {
+    /// Checks that the levels are ones Markdown headings can have.
+    pub fn validate(&self) -> std::result::Result<(), String> {
+        if let TocYaml::Levels {
+            min_level,
+            max_level,
+        } = self
+        {
+            for (name, level) in &[("min_level", min_level), ("max_level", max_level)] {
+                if let Some(level) = level {
+                    if !(1..=6).contains(level) {
+                        return Err(format!(
+                            "toc.{} has to be between 1 and 6. Found {}",
+                            name, level
+                        ));
+                    }
+                }
+            }
+
+            if let (Some(min), Some(max)) = (min_level, max_level) {
+                if min > max {
+                    return Err(format!(
+                        "toc.min_level can't be larger than toc.max_level. Found {} and {}",
+                        min, max
+                    ));
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Which headings of a page to list in its table of contents.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Toc {
+    pub enabled: bool,
+    pub min_level: u32,
+    pub max_level: u32,
+}
+
+impl Default for Toc {
+    /// Everything but the title of the page, down to `###` headings.
+    fn default() -> Self {
+        Toc {
+            enabled: true,
+            min_level: 2,
+            max_level: 3,
+        }
+    }
+}
+
+impl Toc {
+    /// Applies a setting on top of this one. Setting the levels also turns
+    /// the table of contents back on.
+    pub fn with(self, setting: Option<&TocYaml>) -> Self {
+        match setting {
+            None => self,
+            Some(TocYaml::Enabled(enabled)) => Toc {
+                enabled: *enabled,
+                ..self
+            },
+            Some(TocYaml::Levels {
+                min_level,
+                max_level,
+            }) => {
+                let min_level = min_level.unwrap_or(self.min_level);
+
+                Toc {
+                    enabled: true,
+                    min_level,
+                    max_level: max_level.unwrap_or(self.max_level).max(min_level),
+                }
+            }
+        }
+    }
+
+    /// The headings to list, each nested under the heading before it with
+    /// a lower level.
+    pub fn entries(&self, headings: &[Heading]) -> Vec<TocEntry> {
+        if !self.enabled {
+            return vec![];
+        }
+
+        let headings = headings
+            .iter()
+            .filter(|h| h.level >= self.min_level && h.level <= self.max_level)
+            .collect::<Vec<_>>();
+
+        nest(&headings)
+    }
+}
+
+/// A heading in the table of contents of a page.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TocEntry {
+    pub title: String,
+    pub anchor: String,
+    pub level: u32,
+    pub children: Vec<TocEntry>,
+}
+
+fn nest(headings: &[&Heading]) -> Vec<TocEntry> {
+    let mut entries = vec![];
+    let mut rest = headings;
+
+    while let Some((heading, tail)) = rest.split_first() {
+        let end = tail
+            .iter()
+            .position(|h| h.level <= heading.level)
+            .unwrap_or(tail.len());
+
+        entries.push(TocEntry {
+            title: heading.title.clone(),
+            anchor: heading.anchor.clone(),
+            level: heading.level,
+            children: nest(&tail[..end]),
+        });
+
+        rest = &tail[end..];
+    }
+
+    entries
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn heading(title: &str, level: u32) -> Heading {
+        Heading {
+            title: title.to_string(),
+            anchor: title.to_lowercase(),
+            level,
+        }
+    }
+
+    fn outline(entries: &[TocEntry]) -> String {
+        entries
+            .iter()
+            .map(|e| match e.children.as_slice() {
+                [] => e.title.clone(),
+                children => format!("{}({})", e.title, outline(children)),
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    #[test]
+    fn nests_headings_under_lower_levels() {
+        let headings = vec![
+            heading("Title", 1),
+            heading("Install", 2),
+            heading("Linux", 3),
+            heading("Mac", 3),
+            heading("Details", 4),
+            heading("Usage", 2),
+            heading("Skipped", 4),
+        ];
+
+        let toc = Toc {
+            max_level: 4,
+            ..Toc::default()
+        };
+
+        assert_eq!(
+            outline(&toc.entries(&headings)),
+            "Install(Linux, Mac(Details)), Usage(Skipped)"
+        );
+    }
+
+    #[test]
+    fn default_levels() {
+        let headings = vec![
+            heading("Title", 1),
+            heading("Install", 2),
+            heading("Mac", 4),
+        ];
+
+        assert_eq!(outline(&Toc::default().entries(&headings)), "Install");
+    }
+
+    #[test]
+    fn settings_on_top_of_each_other() {
+        let project = Toc::default().with(Some(&TocYaml::Levels {
+            min_level: None,
+            max_level: Some(4),
+        }));
+        assert_eq!(
+            project,
+            Toc {
+                enabled: true,
+                min_level: 2,
+                max_level: 4
+            }
+        );
+
+        let hidden = project.with(Some(&TocYaml::Enabled(false)));
+        assert!(!hidden.enabled);
+        assert!(hidden.entries(&[heading("Install", 2)]).is_empty());
+
+        let page = hidden.with(Some(&TocYaml::Levels {
+            min_level: Some(5),
+            max_level: None,
+        }));
+        assert_eq!(
+            page,
+            Toc {
+                enabled: true,
+                min_level: 5,
+                max_level: 5
+            }
+        );
+    }
+
+    #[test]
+    fn validate_levels() {
+        let levels = |min_level, max_level| TocYaml::Levels {
+            min_level,
+            max_level,
+        };
+
+        assert!(levels(Some(1), Some(6)).validate().is_ok());
+        assert!(TocYaml::Enabled(false).validate().is_ok());
+        assert_eq!(
+            levels(Some(0), None).validate().unwrap_err(),
+            "toc.min_level has to be between 1 and 6. Found 0"
+        );
+        assert_eq!(
+            levels(None, Some(7)).validate().unwrap_err(),
+            "toc.max_level has to be between 1 and 6. Found 7"
+        );
+        assert_eq!(
+            levels(Some(3), Some(2)).validate().unwrap_err(),
+            "toc.min_level can't be larger than toc.max_level. Found 3 and 2"
+        );
+    }
+}
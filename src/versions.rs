@@ -0,0 +1,150 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::{Error, Result};
+
+/// Writes the files under `path`, as they were at the given git tag, branch
+/// or commit, into `destination`.
+///
+/// Uses the `git` command in the project root, so the project has to be
+/// inside a git repository.
+pub fn checkout(
+    project_root: &Path,
+    reference: &str,
+    path: &Path,
+    destination: &Path,
+) -> Result<()> {
+    let pathspec = git_path(path);
+
+    let listing = git(
+        project_root,
+        &[
+            "ls-tree",
+            "-r",
+            "-z",
+            "--name-only",
+            reference,
+            "--",
+            &pathspec,
+        ],
+    )
+    .map_err(|e| checkout_error(reference, path, e))?;
+
+    let files = String::from_utf8_lossy(&listing)
+        .split('\0')
+        .filter(|f| !f.is_empty())
+        .map(|f| f.to_string())
+        .collect::<Vec<_>>();
+
+    if files.is_empty() {
+        return Err(checkout_error(
+            reference,
+            path,
+            String::from("No files found"),
+        ));
+    }
+
+    for file in files {
+        let contents = git(
+            project_root,
+            &["show", &format!("{}:./{}", reference, file)],
+        )
+        .map_err(|e| checkout_error(reference, path, e))?;
+
+        let relative = Path::new(&file)
+            .strip_prefix(&pathspec)
+            .unwrap_or(Path::new(&file));
+        let target = destination.join(relative);
+
+        fs::create_dir_all(target.parent().unwrap()).map_err(|e| {
+            Error::io(
+                e,
+                format!("Could not create directory {}", destination.display()),
+            )
+        })?;
+        fs::write(&target, contents)
+            .map_err(|e| Error::io(e, format!("Could not write {}", target.display())))?;
+    }
+
+    Ok(())
+}
+
+/// The date of the last commit that changed each file under `path`, as of
+/// the given git ref, or the current commit if there is none. Files are
+/// relative to the project root, and dates are in ISO 8601 format.
+pub fn last_modified(
+    project_root: &Path,
+    reference: Option<&str>,
+    path: &Path,
+) -> std::result::Result<HashMap<PathBuf, String>, String> {
+    let pathspec = git_path(path);
+
+    let mut args = vec![
+        "-c",
+        "core.quotePath=false",
+        "log",
+        "--relative",
+        "--name-only",
+        "--format=%x00%cI",
+    ];
+    args.extend(reference);
+    args.extend(&["--", &pathspec]);
+
+    let log = git(project_root, &args)?;
+
+    let mut dates = HashMap::new();
+    let mut date = "";
+
+    // Commits are listed newest first, each one a line with its date
+    // followed by the files it changed.
+    for line in std::str::from_utf8(&log)
+        .map_err(|e| e.to_string())?
+        .lines()
+    {
+        if let Some(commit_date) = line.strip_prefix('\0') {
+            date = commit_date;
+        } else if !line.is_empty() {
+            dates
+                .entry(PathBuf::from(line))
+                .or_insert_with(|| date.to_string());
+        }
+    }
+
+    Ok(dates)
+}
+
+/// Runs a git command, returning its output, or its error message if it
+/// failed.
+fn git(project_root: &Path, args: &[&str]) -> std::result::Result<Vec<u8>, String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(project_root)
+        .args(args)
+        .output()
+        .map_err(|e| format!("Could not run git: {}", e))?;
+
+    if output.status.success() {
+        Ok(output.stdout)
+    } else {
+        Err(String::from_utf8_lossy(&output.stderr).trim().to_string())
+    }
+}
+
+/// Git always uses forward slashes, even on Windows.
+fn git_path(path: &Path) -> String {
+    path.components()
+        .map(|c| c.as_os_str().to_string_lossy().to_string())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+fn checkout_error(reference: &str, path: &Path, reason: String) -> Error {
+    Error::new(format!(
+        "Could not read {} at git ref '{}':\n{}",
+        path.display(),
+        reference,
+        reason
+    ))
+}
@@ -36,6 +36,7 @@ impl Watcher {
                     DebouncedEvent::Remove(p) => self.notify(p, "deleted"),
                     DebouncedEvent::Rename(p, new) => {
                         self.notify(p, format!("renamed to {}", new.display()))
+                            && self.notify(new, "created")
                     }
                     _ => true,
                 },
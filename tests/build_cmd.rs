@@ -4,6 +4,7 @@ extern crate indoc;
 #[allow(dead_code)]
 mod support;
 
+use std::fs;
 use std::path::Path;
 use support::*;
 
@@ -83,6 +84,292 @@ integration_test!(build_navigation, |area| {
     );
 });
 
+integration_test!(build_navigation_weights, |area| {
+    area.create_config();
+    area.mkdir(Path::new("docs").join("guides"));
+    area.write_file(Path::new("docs").join("README.md"), b"# Some content");
+    area.write_file(
+        Path::new("docs").join("troubleshooting.md"),
+        b"---\ntitle: Troubleshooting\nweight: 3\n---\n",
+    );
+    area.write_file(
+        Path::new("docs").join("tutorial.md"),
+        b"---\ntitle: Tutorial\norder: 1\n---\n",
+    );
+    area.write_file(
+        Path::new("docs").join("guides").join("README.md"),
+        b"---\ntitle: Guides\nnav_order: 2\n---\n",
+    );
+    area.write_file(
+        Path::new("docs").join("about.md"),
+        b"---\ntitle: About\n---\n",
+    );
+
+    let result = area.cmd(&["build"]);
+    assert_success(&result);
+
+    let index = fs::read_to_string(area.path.join("site").join("index.html")).unwrap();
+    let position = |title: &str| index.find(&format!(">{}</a>", title)).unwrap();
+
+    assert!(position("Tutorial") < position("Guides"));
+    assert!(position("Guides") < position("Troubleshooting"));
+    assert!(position("Troubleshooting") < position("About"));
+});
+
+integration_test!(build_previous_and_next_links, |area| {
+    area.create_config();
+    area.mkdir(Path::new("docs").join("guides"));
+    area.write_file(Path::new("docs").join("README.md"), b"# Some content");
+    area.write_file(
+        Path::new("docs").join("tutorial.md"),
+        b"---\ntitle: Tutorial\nweight: 1\n---\n",
+    );
+    area.write_file(
+        Path::new("docs").join("guides").join("README.md"),
+        b"---\ntitle: Guides\nweight: 2\n---\n",
+    );
+    area.write_file(
+        Path::new("docs").join("guides").join("deploying.md"),
+        b"---\ntitle: Deploying\n---\n",
+    );
+    area.write_file(
+        Path::new("docs").join("changelog.md"),
+        b"---\ntitle: Changelog\nweight: 3\npagination: false\n---\n",
+    );
+
+    let result = area.cmd(&["build"]);
+    assert_success(&result);
+
+    let tutorial = Path::new("site").join("tutorial.html");
+    area.refute_contains(&tutorial, "class='pagination-previous'");
+    area.assert_contains(&tutorial, "<a class='pagination-next' href=\"/guides\">");
+
+    let deploying = Path::new("site").join("guides").join("deploying.html");
+    area.assert_contains(
+        &deploying,
+        "<a class='pagination-previous' href=\"/guides\">",
+    );
+    area.assert_contains(
+        &deploying,
+        "<a class='pagination-next' href=\"/changelog\">",
+    );
+    area.assert_contains(
+        &deploying,
+        "<span class='pagination-title'>Changelog</span>",
+    );
+
+    let changelog = Path::new("site").join("changelog.html");
+    area.refute_contains(&changelog, "class='pagination'");
+});
+
+integration_test!(build_breadcrumbs, |area| {
+    area.mkdir(Path::new("docs").join("guides"));
+    area.write_file(
+        Path::new("doctave.yaml"),
+        indoc! {"
+            ---
+            title: Breadcrumbs
+            url: https://docs.example.com
+            navigation:
+              - path: docs/tutorial.md
+              - label: Learn
+                children:
+                  - path: docs/guides
+                    children:
+                      - path: docs/guides/deploying.md
+        "}
+        .as_bytes(),
+    );
+    area.write_file(Path::new("docs").join("README.md"), b"# Docs");
+    area.write_file(Path::new("docs").join("tutorial.md"), b"# Tutorial");
+    area.write_file(
+        Path::new("docs").join("guides").join("README.md"),
+        b"---\ntitle: Guides\n---\n",
+    );
+    area.write_file(
+        Path::new("docs").join("guides").join("deploying.md"),
+        b"---\ntitle: Deploying\n---\n",
+    );
+
+    let result = area.cmd(&["build"]);
+    assert_success(&result);
+
+    let deploying = Path::new("site").join("guides").join("deploying.html");
+    area.assert_contains(&deploying, "<li><span>Learn</span></li>");
+    area.assert_contains(&deploying, "<li><a href=\"/guides\">Guides</a></li>");
+    area.assert_contains(&deploying, "<li aria-current='page'>Deploying</li>");
+    area.assert_contains(
+        &deploying,
+        r#"{"@context":"https://schema.org","@type":"BreadcrumbList","itemListElement":[{"@type":"ListItem","item":"https://docs.example.com/guides","name":"Guides","position":1},{"@type":"ListItem","item":"https://docs.example.com/guides/deploying","name":"Deploying","position":2}]}"#,
+    );
+
+    let tutorial = Path::new("site").join("tutorial.html");
+    area.refute_contains(&tutorial, "class='breadcrumbs'");
+    area.refute_contains(&tutorial, "application/ld+json");
+
+    // Search engines need full URLs
+    let config = fs::read_to_string(area.path.join("doctave.yaml")).unwrap();
+    area.write_file(
+        Path::new("doctave.yaml"),
+        config
+            .replace("url: https://docs.example.com\n", "")
+            .as_bytes(),
+    );
+
+    let result = area.cmd(&["build"]);
+    assert_success(&result);
+
+    area.assert_contains(&deploying, "<li><a href=\"/guides\">Guides</a></li>");
+    area.refute_contains(&deploying, "application/ld+json");
+});
+
+integration_test!(build_navigation_links_and_labels, |area| {
+    area.mkdir("docs");
+    area.write_file(
+        Path::new("doctave.yaml"),
+        indoc! {"
+            ---
+            title: Navigation
+            base_path: /project
+            navigation:
+              - path: docs/tutorial.md
+                title: Getting started
+              - label: Elsewhere
+                children:
+                  - url: https://github.com/Doctave/doctave
+                    title: GitHub
+        "}
+        .as_bytes(),
+    );
+    area.write_file(Path::new("docs").join("README.md"), b"# Docs");
+    area.write_file(
+        Path::new("docs").join("tutorial.md"),
+        b"---\ntitle: Tutorial\n---\n# Tutorial",
+    );
+
+    let result = area.cmd(&["build"]);
+    assert_success(&result);
+
+    let index = Path::new("site").join("index.html");
+    area.assert_contains(&index, "<a href=\"/project/tutorial\">Getting started</a>");
+    area.assert_contains(&index, "<span class='nav-label'>Elsewhere</span>");
+    area.assert_contains(
+        &index,
+        "<a class=\"external\" href=\"https://github.com/Doctave/doctave\" target=\"_blank\" rel=\"noopener noreferrer\">GitHub<span class='external-marker' aria-hidden='true'>&#8599;</span></a>",
+    );
+});
+
+integration_test!(build_navigation_patterns, |area| {
+    area.mkdir(Path::new("docs").join("guides"));
+    area.write_file(
+        Path::new("doctave.yaml"),
+        indoc! {"
+            ---
+            title: Navigation
+            navigation:
+              - path: docs/guides
+                children: \"*-howto.md\"
+                exclude:
+                  - internal-howto.md
+        "}
+        .as_bytes(),
+    );
+    area.write_file(Path::new("docs").join("README.md"), b"# Docs");
+    area.write_file(
+        Path::new("docs").join("guides").join("README.md"),
+        b"---\ntitle: Guides\n---\n",
+    );
+    for (file, title) in &[
+        ("deploy-howto.md", "Deploying"),
+        ("internal-howto.md", "Internal"),
+        ("reference.md", "Reference"),
+    ] {
+        area.write_file(
+            Path::new("docs").join("guides").join(file),
+            format!("---\ntitle: {}\n---\n", title).as_bytes(),
+        );
+    }
+
+    let result = area.cmd(&["build"]);
+    assert_success(&result);
+
+    let index = Path::new("site").join("index.html");
+    area.assert_contains(&index, "<a href=\"/guides/deploy-howto\">Deploying</a>");
+    area.refute_contains(&index, "Internal");
+    area.refute_contains(&index, "Reference");
+});
+
+integration_test!(build_navigation_unmatched_pattern, |area| {
+    area.mkdir("docs");
+    area.write_file(
+        Path::new("doctave.yaml"),
+        b"---\ntitle: Navigation\nnavigation:\n  - path: docs\n    children: \"*-howto.md\"\n",
+    );
+    area.write_file(Path::new("docs").join("README.md"), b"# Docs");
+
+    let result = area.cmd(&["build"]);
+    assert_failed(&result);
+    assert_output(
+        &result,
+        "Could not find the files for these entries in navigation:\n\n    \
+         doctave.yaml:4:11  docs: the pattern '*-howto.md' for the children does not match any \
+         pages",
+    );
+});
+
+integration_test!(build_navigation_entries_without_pages, |area| {
+    area.mkdir(Path::new("docs").join("_include"));
+    area.write_file(
+        Path::new("doctave.yaml"),
+        indoc! {"
+            ---
+            title: Navigation
+            navigation:
+              - path: docs/tutorial.md
+              - path: docs/_include/logo.png
+              - path: docs/missing.md
+        "}
+        .as_bytes(),
+    );
+    area.write_file(Path::new("docs").join("README.md"), b"# Docs");
+    area.write_file(Path::new("docs").join("tutorial.md"), b"# Tutorial");
+    area.write_file(Path::new("docs").join("_include").join("logo.png"), b"");
+
+    let result = area.cmd(&["build"]);
+    assert_failed(&result);
+    assert_output(
+        &result,
+        "Could not find the files for these entries in navigation:\n\n    \
+         doctave.yaml:6:11  docs/missing.md",
+    );
+
+    area.write_file(Path::new("docs").join("missing.md"), b"# Found");
+
+    let result = area.cmd(&["build"]);
+    assert_failed(&result);
+    assert_output(
+        &result,
+        "Could not find pages for these entries in navigation:\n\n    \
+         doctave.yaml:5:11  docs/_include/logo.png",
+    );
+});
+
+integration_test!(build_navigation_invalid_entry, |area| {
+    area.mkdir("docs");
+    area.write_file(
+        Path::new("doctave.yaml"),
+        b"---\ntitle: Navigation\nnavigation:\n  - url: https://example.com\n",
+    );
+    area.write_file(Path::new("docs").join("README.md"), b"# Docs");
+
+    let result = area.cmd(&["build"]);
+    assert_failed(&result);
+    assert_output(
+        &result,
+        "Missing title for the link to https://example.com in navigation",
+    );
+});
+
 integration_test!(build_navigation_nested, |area| {
     area.create_config();
     area.mkdir("docs");
@@ -174,11 +461,195 @@ integration_test!(search_index, |area| {
     "}
         .as_bytes(),
     );
+    area.write_file(
+        Path::new("docs").join("guide.md"),
+        indoc! {"
+        ---
+        title: Guide
+        ---
+
+        Read this **first**.
+
+        ## Installing
+
+        Run `make install` & wait.
+    "}
+        .as_bytes(),
+    );
+
+    let result = area.cmd(&["build"]);
+    assert_success(&result);
+
+    let index = Path::new("site").join("search_index.json");
+    area.assert_exists(&index);
+    area.assert_contains(&index, "\"uri\":\"/guide\"");
+    area.assert_contains(&index, "\"uri\":\"/guide#installing-1\"");
+    area.assert_contains(&index, "\"title\":\"Installing\"");
+    area.assert_contains(&index, "\"page\":\"Guide\"");
+    area.assert_contains(&index, "\"excerpt\":\"Run make install & wait.\"");
+
+    // Words are stemmed, and stop words are left out
+    area.assert_contains(&index, "\"instal\":[");
+    area.assert_contains(&index, "\"first\":[");
+    area.refute_contains(&index, "\"this\":[");
+    area.refute_exists(Path::new("site").join("search_index"));
+
+    // Browsers stem the search terms the same way
+    area.assert_exists(Path::new("site").join("assets").join("stemmer.js"));
+    let page = Path::new("site").join("guide.html");
+    area.assert_contains(&page, "assets/stemmer.js");
+    area.assert_contains(&page, "var DOCTAVE_SEARCH = true;");
+    area.refute_contains(&page, "elasticlunr");
+
+    assert_output(&result, "Search index is");
+    assert_output(&result, "in 1 file");
+});
+
+integration_test!(search_index_sharded_by_prefix, |area| {
+    area.mkdir("docs");
+    area.write_file(
+        "doctave.yaml",
+        indoc! {"
+        ---
+        title: Test Project
+        search:
+          shards: prefix
+          stemming: false
+          stop_words: [the]
+    "}
+        .as_bytes(),
+    );
+    area.write_file(
+        Path::new("docs").join("README.md"),
+        b"# Welcome\n\nThe apples and bananas.\n",
+    );
+
+    let result = area.cmd(&["build"]);
+    assert_success(&result);
+
+    // Only the stemmer is needed to search the index
+    area.refute_exists(Path::new("site").join("assets").join("stemmer.js"));
+    area.refute_contains(Path::new("site").join("index.html"), "stemmer.js");
+
+    let manifest = Path::new("site").join("search_index.json");
+    area.assert_contains(&manifest, "\"sharding\":\"prefix\"");
+    area.assert_contains(&manifest, "\"stop_words\":[\"the\"]");
+    area.assert_contains(&manifest, "\"a\":\"search_index/terms-0.json\"");
+    area.assert_contains(&manifest, "\"doc_files\":[\"search_index/docs-0.json\"]");
+    area.refute_contains(&manifest, "apples");
+
+    let shard = Path::new("site").join("search_index").join("terms-0.json");
+    area.assert_contains(&shard, "\"apples\":[");
+    area.assert_contains(&shard, "\"and\":[");
+    area.refute_contains(&shard, "bananas");
+    area.assert_contains(
+        Path::new("site").join("search_index").join("terms-1.json"),
+        "\"bananas\":[",
+    );
+    area.assert_contains(
+        Path::new("site").join("search_index").join("docs-0.json"),
+        "\"title\":\"Welcome\"",
+    );
+
+    assert_output(&result, "in 5 files");
+});
+
+integration_test!(search_index_sharded_by_section, |area| {
+    area.mkdir("docs");
+    area.mkdir(Path::new("docs").join("guides"));
+    area.write_file(
+        "doctave.yaml",
+        indoc! {"
+        ---
+        title: Test Project
+        search:
+          shards: section
+    "}
+        .as_bytes(),
+    );
+    area.write_file(
+        Path::new("docs").join("README.md"),
+        b"# Welcome\n\nDeploy.\n",
+    );
+    area.write_file(
+        Path::new("docs").join("guides").join("README.md"),
+        b"# Guides\n\nDeploying to production.\n",
+    );
+
+    let result = area.cmd(&["build"]);
+    assert_success(&result);
+
+    let manifest = Path::new("site").join("search_index.json");
+    area.assert_contains(&manifest, "\"sharding\":\"section\"");
+    area.assert_contains(&manifest, "\"\":\"search_index/terms-0.json\"");
+    area.assert_contains(&manifest, "\"guides\":\"search_index/terms-1.json\"");
+
+    let root = Path::new("site").join("search_index").join("terms-0.json");
+    let guides = Path::new("site").join("search_index").join("terms-1.json");
+    area.assert_contains(&root, "\"deploy\":[");
+    area.refute_contains(&root, "product");
+    area.assert_contains(&guides, "\"deploy\":[");
+    area.assert_contains(&guides, "\"product\":[");
+});
+
+integration_test!(search_settings, |area| {
+    area.mkdir("docs");
+    area.mkdir(Path::new("docs").join("internal"));
+    area.write_file(
+        "doctave.yaml",
+        indoc! {"
+        ---
+        title: Test Project
+        search:
+          boosts:
+            title: 5
+          exclude:
+            - internal/**
+    "}
+        .as_bytes(),
+    );
+    area.write_file(Path::new("docs").join("README.md"), b"# Welcome\n");
+    area.write_file(
+        Path::new("docs").join("secret.md"),
+        b"---\nsearch: false\n---\n\n# Secret\n\nHidden words.\n",
+    );
+    area.write_file(
+        Path::new("docs").join("internal").join("notes.md"),
+        b"# Notes\n\nPrivate thoughts.\n",
+    );
+
+    let result = area.cmd(&["build"]);
+    assert_success(&result);
+
+    let index = Path::new("site").join("search_index.json");
+    area.assert_contains(&index, "\"boosts\":{\"title\":5.0,\"body\":1.0}");
+    area.assert_contains(&index, "\"welcom\":[");
+    area.refute_contains(&index, "Secret");
+    area.refute_contains(&index, "Notes");
+    area.refute_contains(&index, "private");
+
+    // The pages are still built
+    area.assert_exists(Path::new("site").join("secret.html"));
+    area.assert_exists(Path::new("site").join("internal").join("notes.html"));
+});
+
+integration_test!(search_disabled, |area| {
+    area.mkdir("docs");
+    area.write_file("doctave.yaml", b"---\ntitle: Test Project\nsearch: false\n");
+    area.write_file(Path::new("docs").join("README.md"), b"# Welcome\n");
 
     let result = area.cmd(&["build"]);
     assert_success(&result);
+    refute_output(&result, "Search index");
+
+    area.refute_exists(Path::new("site").join("search_index.json"));
+    area.refute_exists(Path::new("site").join("assets").join("stemmer.js"));
 
-    area.assert_exists(Path::new("site").join("search_index.json"));
+    let index = Path::new("site").join("index.html");
+    area.refute_contains(&index, "search-box");
+    area.refute_contains(&index, "stemmer.js");
+    area.assert_contains(&index, "var DOCTAVE_SEARCH = false;");
+    area.assert_contains(&index, "doctave-app.js");
 });
 
 integration_test!(frontmatter, |area| {
@@ -214,34 +685,52 @@ integration_test!(frontmatter, |area| {
 integration_test!(page_nav, |area| {
     area.mkdir("docs");
     area.create_config();
-    area.write_file(
-        Path::new("docs").join("README.md"),
-        indoc! {"
-        # This
+    let content = indoc! {"
+        # Welcome
 
-        # Is
+        ## Install
 
-        # The
+        ### Linux
 
-        # End
-        ```
-    "}
+        #### Details
+
+        ## Usage
+    "};
+    area.write_file(Path::new("docs").join("README.md"), content.as_bytes());
+    area.write_file(
+        Path::new("docs").join("guide.md"),
+        format!(
+            "---\ntoc:\n  min_level: 1\n  max_level: 4\n---\n{}",
+            content
+        )
         .as_bytes(),
     );
-
-    let index = Path::new("site").join("index.html");
+    area.write_file(
+        Path::new("docs").join("tutorial.md"),
+        format!("---\ntoc: false\n---\n{}", content).as_bytes(),
+    );
 
     let result = area.cmd(&["build"]);
     assert_success(&result);
 
-    area.assert_contains(&index, "<li class='page-nav-level-1'>");
-    area.assert_contains(&index, "  <a href='#this-1'>This</a>");
-    area.assert_contains(&index, "<li class='page-nav-level-1'>");
-    area.assert_contains(&index, "  <a href='#is-2'>Is</a>");
-    area.assert_contains(&index, "<li class='page-nav-level-1'>");
-    area.assert_contains(&index, "  <a href='#the-3'>The</a>");
-    area.assert_contains(&index, "<li class='page-nav-level-1'>");
-    area.assert_contains(&index, "  <a href='#end-4'>End</a>");
+    let page_nav = |name: &str| {
+        let page = fs::read_to_string(area.path.join("site").join(name)).unwrap();
+        page.split_whitespace().collect::<String>()
+    };
+
+    assert!(page_nav("index.html").contains(
+        "<ul><li><ahref='#install-2'>Install</a>\
+         <ul><li><ahref='#linux-3'>Linux</a></li></ul></li>\
+         <li><ahref='#usage-5'>Usage</a></li></ul>"
+    ));
+    assert!(page_nav("guide.html").contains(
+        "<ul><li><ahref='#welcome-1'>Welcome</a>\
+         <ul><li><ahref='#install-2'>Install</a>\
+         <ul><li><ahref='#linux-3'>Linux</a>\
+         <ul><li><ahref='#details-4'>Details</a></li></ul></li></ul></li>\
+         <li><ahref='#usage-5'>Usage</a></li></ul></li></ul>"
+    ));
+    area.refute_contains(&Path::new("site").join("tutorial.html"), "id='page-nav'");
 });
 
 integration_test!(missing_directory_index, |area| {
@@ -384,6 +873,7 @@ integration_test!(release_mode, |area| {
 
     let index = area.path.join("site").join("index.html");
     area.refute_contains(&index, "livereload");
+    area.assert_contains(&index, "var DOCTAVE_SEARCH_API = false;");
 
     let livereload_js = area.path.join("site").join("assets").join("livereload.js");
     assert!(!livereload_js.exists());
@@ -458,3 +948,935 @@ integration_test!(cache_buster, |area| {
     // Famous last words ofc...
     area.assert_contains(&index, "doctave-style.css?v=1");
 });
+
+integration_test!(strict_mode_broken_links, |area| {
+    area.create_config();
+    area.mkdir("docs");
+    area.write_file(
+        Path::new("docs").join("README.md"),
+        b"# Hi\n\n[Broken](/not-a-page)",
+    );
+
+    let result = area.cmd(&["build"]);
+    assert_success(&result);
+
+    let result = area.cmd(&["build", "--strict"]);
+    assert_failed(&result);
+    assert_output(
+        &result,
+        "\"/not-a-page\" points to a page that does not exist",
+    );
+});
+
+integration_test!(invalid_frontmatter, |area| {
+    area.create_config();
+    area.mkdir("docs");
+    area.write_file(Path::new("docs").join("README.md"), b"# Hi");
+    area.write_file(
+        Path::new("docs").join("broken.md"),
+        indoc! {"
+        ---
+        title: Broken
+        :::blarg: @@!~
+        ---
+
+        # Broken
+    "}
+        .as_bytes(),
+    );
+    area.write_file(
+        Path::new("docs").join("also_broken.md"),
+        b"---\ntags: [one, two\n---\n\n# Also broken\n",
+    );
+
+    let result = area.cmd(&["build"]);
+    assert_failed(&result);
+
+    assert_output(&result, "Found 2 document(s) that could not be loaded");
+    assert_output(
+        &result,
+        &format!("docs{}broken.md:3:", std::path::MAIN_SEPARATOR),
+    );
+    assert_output(&result, "3 | :::blarg: @@!~");
+    assert_output(
+        &result,
+        &format!("docs{}also_broken.md", std::path::MAIN_SEPARATOR),
+    );
+});
+
+integration_test!(custom_templates, |area| {
+    area.create_config();
+    area.mkdir(Path::new("docs").join("_templates"));
+    area.write_file(Path::new("docs").join("README.md"), b"# Hi");
+    area.write_file(
+        Path::new("docs").join("other.md"),
+        b"---\ntitle: Other\nowner: Platform team\n---\n# Other",
+    );
+    area.write_file(
+        Path::new("docs").join("_templates").join("page.html"),
+        b"<main>{{{ content }}}</main>{{> footer }}",
+    );
+    area.write_file(
+        Path::new("docs").join("_templates").join("footer.html"),
+        b"<footer>Owned by {{ page.meta.owner }}</footer>",
+    );
+
+    let result = area.cmd(&["build"]);
+    assert_success(&result);
+
+    let other = Path::new("site").join("other.html");
+    area.assert_contains(&other, "<main>");
+    area.assert_contains(&other, "<footer>Owned by Platform team</footer>");
+    area.refute_contains(&other, "sidebar-left");
+
+    area.refute_exists(Path::new("site").join("_templates"));
+});
+
+integration_test!(custom_templates_directory, |area| {
+    area.mkdir("docs");
+    area.mkdir("theme");
+    area.write_file(
+        Path::new("doctave.yaml"),
+        b"---\ntitle: Custom templates\ntemplates: theme\n",
+    );
+    area.write_file(Path::new("docs").join("README.md"), b"# Hi");
+    area.write_file(
+        Path::new("theme").join("navigation.html"),
+        b"<nav class='custom-nav'></nav>",
+    );
+
+    let result = area.cmd(&["build"]);
+    assert_success(&result);
+
+    let index = Path::new("site").join("index.html");
+    area.assert_contains(&index, "<nav class='custom-nav'></nav>");
+    area.assert_contains(&index, "sidebar-left");
+});
+
+integration_test!(custom_templates_invalid, |area| {
+    area.create_config();
+    area.mkdir(Path::new("docs").join("_templates"));
+    area.write_file(Path::new("docs").join("README.md"), b"# Hi");
+    area.write_file(
+        Path::new("docs").join("_templates").join("page.html"),
+        b"<main>{{#if content}}</main>",
+    );
+
+    let result = area.cmd(&["build"]);
+    assert_failed(&result);
+    assert_output(&result, "Could not parse template");
+    assert_output(
+        &result,
+        &format!(
+            "{}:",
+            Path::new("docs")
+                .join("_templates")
+                .join("page.html")
+                .display()
+        ),
+    );
+});
+
+integration_test!(builtin_layouts, |area| {
+    area.create_config();
+    area.mkdir("docs");
+    area.write_file(Path::new("docs").join("README.md"), b"# Hi");
+    area.write_file(
+        Path::new("docs").join("landing.md"),
+        b"---\nlayout: landing\n---\n# Welcome",
+    );
+    area.write_file(
+        Path::new("docs").join("blank.md"),
+        b"---\nlayout: blank\n---\n# Nothing else",
+    );
+
+    let result = area.cmd(&["build"]);
+    assert_success(&result);
+
+    let index = Path::new("site").join("index.html");
+    area.assert_contains(&index, "sidebar-left");
+    area.assert_contains(&index, "sidebar-right");
+
+    let landing = Path::new("site").join("landing.html");
+    area.assert_contains(&landing, "container landing");
+    area.assert_contains(&landing, "search-box");
+    area.refute_contains(&landing, "sidebar-left");
+    area.refute_contains(&landing, "sidebar-right");
+
+    let blank = Path::new("site").join("blank.html");
+    area.assert_contains(&blank, "Nothing else</h1>");
+    area.assert_contains(&blank, "doctave-style.css");
+    area.refute_contains(&blank, "class='header'");
+    area.refute_contains(&blank, "sidebar-left");
+});
+
+integration_test!(custom_layout, |area| {
+    area.create_config();
+    area.mkdir(Path::new("docs").join("_templates"));
+    area.write_file(Path::new("docs").join("README.md"), b"# Hi");
+    area.write_file(
+        Path::new("docs").join("changelog.md"),
+        b"---\nlayout: changelog\n---\n# Changelog",
+    );
+    area.write_file(
+        Path::new("docs").join("_templates").join("changelog.html"),
+        b"<article class='changelog'>{{{ content }}}</article>",
+    );
+
+    let result = area.cmd(&["build"]);
+    assert_success(&result);
+
+    let changelog = Path::new("site").join("changelog.html");
+    area.assert_contains(&changelog, "<article class='changelog'>");
+
+    let index = Path::new("site").join("index.html");
+    area.refute_contains(&index, "<article class='changelog'>");
+});
+
+integration_test!(unknown_layout, |area| {
+    area.create_config();
+    area.mkdir("docs");
+    area.write_file(
+        Path::new("docs").join("README.md"),
+        b"---\nlayout: nonexistent\n---\n# Hi",
+    );
+
+    let result = area.cmd(&["build"]);
+    assert_failed(&result);
+    assert_output(&result, "Unknown layout \"nonexistent\"");
+    assert_output(
+        &result,
+        &format!("{}", Path::new("docs").join("README.md").display()),
+    );
+});
+
+integration_test!(versions_from_directories, |area| {
+    area.mkdir(Path::new("docs"));
+    area.mkdir(Path::new("versions").join("1.0"));
+    area.write_file(
+        Path::new("doctave.yaml"),
+        b"---\ntitle: Versioned\nversions:\n  - name: \"2.0\"\n  - name: \"1.0\"\n    path: versions/1.0\n",
+    );
+    area.write_file(
+        Path::new("docs").join("README.md"),
+        b"# Version two\n\nSee the [tutorial](/tutorial).",
+    );
+    area.write_file(Path::new("docs").join("tutorial.md"), b"# Tutorial two");
+    area.write_file(
+        Path::new("versions").join("1.0").join("README.md"),
+        b"# Version one\n\nSee the [tutorial](/tutorial).",
+    );
+    area.write_file(
+        Path::new("versions").join("1.0").join("tutorial.md"),
+        b"# Tutorial one",
+    );
+
+    let result = area.cmd(&["build"]);
+    assert_success(&result);
+
+    let latest = Path::new("site").join("2.0").join("index.html");
+    area.assert_contains(&latest, "Version two");
+    area.assert_contains(&latest, "See the <a href=\"/2.0/tutorial\">");
+    area.assert_contains(&latest, "<li><a href=\"/2.0/tutorial\">tutorial</a></li>");
+    area.assert_contains(&latest, "src=\"/2.0/assets/doctave-app.js");
+    area.assert_contains(&latest, "<option value='/1.0/'>");
+    area.refute_contains(&latest, "version-banner");
+
+    let old = Path::new("site").join("1.0").join("index.html");
+    area.assert_contains(&old, "Version one");
+    area.assert_contains(&old, "See the <a href=\"/1.0/tutorial\">");
+    area.assert_contains(&old, "version-banner");
+    area.assert_contains(&old, "<a href='/latest/'>");
+
+    area.assert_contains(
+        Path::new("site").join("2.0").join("search_index.json"),
+        "/2.0/tutorial",
+    );
+    area.assert_contains(
+        Path::new("site").join("1.0").join("search_index.json"),
+        "/1.0/tutorial",
+    );
+
+    area.assert_contains(Path::new("site").join("index.html"), "url=/2.0/");
+    area.assert_contains(
+        Path::new("site").join("latest").join("tutorial.html"),
+        "url=/2.0/tutorial",
+    );
+    area.refute_exists(Path::new("site").join("latest").join("assets"));
+});
+
+integration_test!(versions_from_git, |area| {
+    area.create_config();
+    area.mkdir("docs");
+    area.write_file(Path::new("docs").join("README.md"), b"# Old docs");
+    area.write_file(Path::new("docs").join("removed.md"), b"# Removed page");
+
+    area.git(&["init", "-q"]);
+    area.git(&["add", "."]);
+    area.git(&["commit", "-q", "-m", "Old docs"]);
+    area.git(&["tag", "v1"]);
+
+    area.write_file(Path::new("docs").join("README.md"), b"# New docs");
+    fs::remove_file(area.path.join("docs").join("removed.md")).unwrap();
+    area.write_file(
+        Path::new("doctave.yaml"),
+        b"---\ntitle: Versioned\nversions:\n  - name: v2\n  - name: v1\n    git: v1\n",
+    );
+
+    let result = area.cmd(&["build"]);
+    assert_success(&result);
+
+    area.assert_contains(Path::new("site").join("v2").join("index.html"), "New docs");
+    area.refute_exists(Path::new("site").join("v2").join("removed.html"));
+    area.assert_contains(Path::new("site").join("v1").join("index.html"), "Old docs");
+    area.assert_contains(
+        Path::new("site").join("v1").join("removed.html"),
+        "Removed page",
+    );
+});
+
+integration_test!(versions_from_unknown_git_ref, |area| {
+    area.create_config();
+    area.mkdir("docs");
+    area.write_file(Path::new("docs").join("README.md"), b"# Docs");
+
+    area.git(&["init", "-q"]);
+    area.git(&["add", "."]);
+    area.git(&["commit", "-q", "-m", "Docs"]);
+
+    area.write_file(
+        Path::new("doctave.yaml"),
+        b"---\ntitle: Versioned\nversions:\n  - name: v2\n  - name: v1\n    git: does-not-exist\n",
+    );
+
+    let result = area.cmd(&["build"]);
+    assert_failed(&result);
+    assert_output(&result, "Could not read docs at git ref 'does-not-exist'");
+});
+
+integration_test!(languages_from_directories, |area| {
+    area.mkdir(Path::new("docs").join("en"));
+    area.mkdir(Path::new("docs").join("de"));
+    area.mkdir(Path::new("docs").join("_include"));
+    area.write_file(
+        Path::new("doctave.yaml"),
+        b"---\ntitle: Translated\nlanguages:\n  - code: en\n  - code: de\n",
+    );
+    area.write_file(
+        Path::new("docs").join("en").join("README.md"),
+        b"# Welcome\n\nSee the [tutorial](/tutorial).",
+    );
+    area.write_file(
+        Path::new("docs").join("en").join("tutorial.md"),
+        b"# Tutorial",
+    );
+    area.write_file(Path::new("docs").join("en").join("faq.md"), b"# FAQ");
+    area.write_file(
+        Path::new("docs").join("de").join("README.md"),
+        b"# Willkommen",
+    );
+    area.write_file(
+        Path::new("docs").join("de").join("tutorial.md"),
+        b"# Anleitung",
+    );
+    area.write_file(Path::new("docs").join("_include").join("logo.png"), b"");
+
+    let result = area.cmd(&["build"]);
+    assert_success(&result);
+
+    let english = Path::new("site").join("en").join("index.html");
+    area.assert_contains(&english, "<html lang=\"en\">");
+    area.assert_contains(&english, "See the <a href=\"/en/tutorial\">");
+    area.assert_contains(&english, "src=\"/en/assets/doctave-app.js");
+    area.assert_contains(&english, "<option value='/de/'>");
+    area.assert_contains(&english, "On this page");
+
+    let german = Path::new("site").join("de").join("index.html");
+    area.assert_contains(&german, "<html lang=\"de\">");
+    area.assert_contains(&german, "Willkommen");
+    area.assert_contains(&german, "Auf dieser Seite");
+    area.assert_contains(&german, "placeholder=\"Suchen...\"");
+
+    // Pages link to their translation, or to the start page of languages
+    // they haven't been translated to
+    area.assert_contains(
+        Path::new("site").join("en").join("tutorial.html"),
+        "<option value='/de/tutorial'>",
+    );
+    area.assert_contains(
+        Path::new("site").join("en").join("faq.html"),
+        "<option value='/de/'>",
+    );
+    area.refute_exists(Path::new("site").join("de").join("faq.html"));
+
+    area.assert_exists(Path::new("site").join("en").join("logo.png"));
+    area.assert_exists(Path::new("site").join("de").join("logo.png"));
+    area.assert_contains(
+        Path::new("site").join("de").join("search_index.json"),
+        "/de/tutorial",
+    );
+    area.assert_contains(Path::new("site").join("index.html"), "url=/en/");
+});
+
+integration_test!(languages_from_suffixes, |area| {
+    area.mkdir("docs");
+    area.write_file(
+        Path::new("doctave.yaml"),
+        b"---\ntitle: Translated\nlanguages:\n  - code: en\n  - code: fr\n    name: French\n    strings:\n      on_this_page: Sommaire\n",
+    );
+    area.write_file(Path::new("docs").join("README.md"), b"# Welcome");
+    area.write_file(Path::new("docs").join("tutorial.md"), b"# Tutorial");
+    area.write_file(Path::new("docs").join("tutorial.fr.md"), b"# Tutoriel");
+
+    let result = area.cmd(&["build"]);
+    assert_success(&result);
+
+    let english = Path::new("site").join("en").join("tutorial.html");
+    area.assert_contains(&english, "Tutorial");
+    area.assert_contains(&english, "<option value='/fr/tutorial'>");
+    area.assert_contains(&english, "French");
+    area.refute_exists(Path::new("site").join("en").join("tutorial.fr.html"));
+
+    let french = Path::new("site").join("fr").join("tutorial.html");
+    area.assert_contains(&french, "Tutoriel");
+    area.assert_contains(&french, "Sommaire");
+
+    // Untranslated pages fall back to the original
+    area.assert_contains(Path::new("site").join("fr").join("index.html"), "Welcome");
+});
+
+integration_test!(languages_with_versions, |area| {
+    area.mkdir("docs");
+    area.write_file(
+        Path::new("doctave.yaml"),
+        b"---\ntitle: Translated\nversions:\n  - name: v2\n  - name: v1\n    path: docs\nlanguages:\n  - code: en\n  - code: es\n",
+    );
+    area.write_file(Path::new("docs").join("README.md"), b"# Welcome");
+
+    let result = area.cmd(&["build"]);
+    assert_success(&result);
+
+    area.assert_contains(Path::new("site").join("index.html"), "url=/v2/en/");
+    area.assert_contains(
+        Path::new("site").join("v1").join("index.html"),
+        "url=/v1/en/",
+    );
+    area.assert_contains(
+        Path::new("site")
+            .join("latest")
+            .join("es")
+            .join("index.html"),
+        "url=/v2/es/",
+    );
+
+    let old = Path::new("site").join("v1").join("es").join("index.html");
+    area.assert_contains(&old, "Está leyendo la documentación de la versión v1.");
+    area.assert_contains(&old, "<a href='/latest/es/'>");
+    area.assert_contains(&old, "<option value='/v2/es/'>");
+});
+
+integration_test!(build_from_project_directory, |area| {
+    area.mkdir(Path::new("website").join("docs"));
+    area.write_file(
+        Path::new("website").join("doctave.yaml"),
+        b"---\ntitle: Elsewhere\n",
+    );
+    area.write_file(
+        Path::new("website").join("docs").join("README.md"),
+        b"# Hello from elsewhere",
+    );
+
+    let result = area.cmd(&["build", "--project", "website", "--out-dir", "public"]);
+    assert_success(&result);
+
+    area.assert_contains(
+        Path::new("public").join("index.html"),
+        "Hello from elsewhere",
+    );
+    area.refute_exists(Path::new("website").join("site"));
+});
+
+integration_test!(build_refuses_to_delete_project, |area| {
+    area.create_config();
+    area.mkdir("docs");
+    area.write_file(Path::new("docs").join("README.md"), b"# Some content");
+
+    for out_dir in &[".", "docs"] {
+        let result = area.cmd(&["build", "--out-dir", out_dir]);
+        assert_failed(&result);
+        assert_output(&result, "Invalid output directory");
+    }
+
+    area.assert_exists("doctave.yaml");
+    area.assert_contains(Path::new("docs").join("README.md"), "Some content");
+});
+
+integration_test!(build_refuses_to_delete_other_files, |area| {
+    area.create_config();
+    area.mkdir("docs");
+    area.mkdir("notes");
+    area.write_file(Path::new("docs").join("README.md"), b"# Some content");
+    area.write_file(Path::new("notes").join("todo.txt"), b"Buy milk");
+
+    let result = area.cmd(&["build", "--out-dir", "notes"]);
+    assert_failed(&result);
+    assert_output(&result, "Invalid output directory");
+    assert_output(&result, "It has files that Doctave didn't build");
+    area.assert_contains(Path::new("notes").join("todo.txt"), "Buy milk");
+
+    // Sites Doctave built before can be built again
+    let result = area.cmd(&["build", "--out-dir", "public"]);
+    assert_success(&result);
+    area.assert_exists(Path::new("public").join(".doctave"));
+
+    let result = area.cmd(&["build", "--out-dir", "public"]);
+    assert_success(&result);
+    area.assert_exists(Path::new("public").join("index.html"));
+});
+
+integration_test!(build_without_project, |area| {
+    area.mkdir("empty");
+
+    let result = area.cmd(&["build", "--project", "empty"]);
+    assert_failed(&result);
+    assert_output(&result, "Could not find a doctave project in");
+});
+
+integration_test!(docs_dir_from_config, |area| {
+    area.mkdir(Path::new("content").join("_include"));
+    area.write_file(
+        Path::new("doctave.yaml"),
+        b"---\ntitle: Content\ndocs_dir: content\nlogo: logo.png\nnavigation:\n  - path: content/tutorial.md\n",
+    );
+    area.write_file(Path::new("content").join("README.md"), b"# Welcome");
+    area.write_file(Path::new("content").join("tutorial.md"), b"# Tutorial");
+    area.write_file(Path::new("content").join("_include").join("logo.png"), b"");
+
+    let result = area.cmd(&["build"]);
+    assert_success(&result);
+
+    let index = Path::new("site").join("index.html");
+    area.assert_contains(&index, "Welcome");
+    area.assert_contains(&index, "<li><a href=\"/tutorial\">tutorial</a></li>");
+    area.assert_exists(Path::new("site").join("logo.png"));
+});
+
+integration_test!(base_path_from_config, |area| {
+    area.mkdir(Path::new("docs").join("_include"));
+    area.write_file(
+        Path::new("doctave.yaml"),
+        b"---\ntitle: Hosted\nbase_path: /project/\nlogo: logo.png\n",
+    );
+    area.write_file(
+        Path::new("docs").join("README.md"),
+        b"# Hosted\n\nSee the [tutorial](/tutorial).\n\n![Diagram](/diagram.png)",
+    );
+    area.write_file(Path::new("docs").join("tutorial.md"), b"# Tutorial");
+    area.write_file(Path::new("docs").join("_include").join("logo.png"), b"");
+    area.write_file(Path::new("docs").join("_include").join("diagram.png"), b"");
+
+    let result = area.cmd(&["build"]);
+    assert_success(&result);
+
+    let index = Path::new("site").join("index.html");
+    area.assert_contains(&index, "See the <a href=\"/project/tutorial\">");
+    area.assert_contains(&index, "src=\"/project/diagram.png\"");
+    area.assert_contains(
+        &index,
+        "<li><a href=\"/project/tutorial\">tutorial</a></li>",
+    );
+    area.assert_contains(&index, "href=\"/project/assets/doctave-style.css");
+    area.assert_contains(&index, "src=\"/project/logo.png\"");
+    area.assert_contains(&index, "var DOCTAVE_URI_PREFIX = \"/project\";");
+    area.assert_contains(&index, "var DOCTAVE_SEARCH_API = true;");
+    area.assert_contains(
+        Path::new("site").join("search_index.json"),
+        "\"uri\":\"/project/tutorial\"",
+    );
+});
+
+integration_test!(base_path_from_command_line, |area| {
+    area.mkdir("docs");
+    area.write_file(
+        Path::new("doctave.yaml"),
+        b"---\ntitle: Hosted\nbase_path: /project\n",
+    );
+    area.write_file(
+        Path::new("docs").join("README.md"),
+        b"See the [tutorial](/tutorial).",
+    );
+    area.write_file(Path::new("docs").join("tutorial.md"), b"# Tutorial");
+
+    let result = area.cmd(&["build", "--base-path", "/docs/v2"]);
+    assert_success(&result);
+
+    let index = Path::new("site").join("index.html");
+    area.assert_contains(&index, "See the <a href=\"/docs/v2/tutorial\">");
+    area.refute_contains(&index, "/project");
+
+    let result = area.cmd(&["build", "--base-path", "https://example.com"]);
+    assert_failed(&result);
+    assert_output(&result, "Invalid base path 'https://example.com'");
+});
+
+integration_test!(relative_links, |area| {
+    area.mkdir(Path::new("docs").join("features"));
+    area.write_file(
+        Path::new("doctave.yaml"),
+        b"---\ntitle: Offline\nbase_path: /project\n",
+    );
+    area.write_file(
+        Path::new("docs").join("README.md"),
+        b"# Offline\n\nSee the [features](/features), [one](features/one), [one.md](features/one.md) and [the directory](features).",
+    );
+    area.write_file(
+        Path::new("docs").join("features").join("README.md"),
+        b"# Features\n\nBack to the [tutorial](/tutorial#setup), or see [one](./one.md) and [home](../README.md).",
+    );
+    area.write_file(Path::new("docs").join("features").join("one.md"), b"# One");
+    area.write_file(Path::new("docs").join("tutorial.md"), b"# Tutorial");
+
+    let result = area.cmd(&["build", "--relative-links"]);
+    assert_success(&result);
+
+    let index = Path::new("site").join("index.html");
+    area.assert_contains(&index, "See the <a href=\"features/index.html\">");
+    area.assert_contains(&index, "<a href=\"features/one.html\">one</a>");
+    area.assert_contains(&index, "<a href=\"features/one.html\">one.md</a>");
+    area.assert_contains(&index, "<a href=\"features/index.html\">the directory</a>");
+    area.assert_contains(&index, "href=\"assets/doctave-style.css");
+    area.assert_contains(&index, "var DOCTAVE_URI_PREFIX = \".\";");
+    area.assert_contains(&index, "var DOCTAVE_SEARCH_API = false;");
+    area.refute_contains(&index, "/project");
+
+    let features = Path::new("site").join("features").join("index.html");
+    area.assert_contains(&features, "<a href=\"../tutorial.html#setup\">");
+    area.assert_contains(&features, "<a href=\"one.html\">one</a>");
+    area.assert_contains(&features, "<a href=\"../index.html\">home</a>");
+    area.assert_contains(&features, "src=\"../assets/doctave-app.js");
+    area.assert_contains(&features, "src=\"../search_index.js");
+    area.assert_contains(&features, "var DOCTAVE_URI_PREFIX = \"..\";");
+
+    area.assert_contains(
+        Path::new("site").join("search_index.json"),
+        "\"uri\":\"features/index.html\"",
+    );
+    area.assert_contains(
+        Path::new("site").join("search_index.js"),
+        "var DOCTAVE_SEARCH_INDEX = {",
+    );
+});
+
+integration_test!(relative_links_with_versions, |area| {
+    area.mkdir("docs");
+    area.write_file(
+        Path::new("doctave.yaml"),
+        b"---\ntitle: Offline\nversions:\n  - name: v2\n  - name: v1\n    path: docs\n",
+    );
+    area.write_file(Path::new("docs").join("README.md"), b"# Offline");
+    area.write_file(Path::new("docs").join("tutorial.md"), b"# Tutorial");
+
+    let result = area.cmd(&["build", "--relative-links"]);
+    assert_success(&result);
+
+    area.assert_contains(
+        Path::new("site").join("v2").join("tutorial.html"),
+        "<option value='../v1/index.html'>",
+    );
+    area.assert_contains(Path::new("site").join("index.html"), "url=v2/index.html");
+    area.assert_contains(
+        Path::new("site").join("latest").join("tutorial.html"),
+        "url=../v2/tutorial.html",
+    );
+});
+
+integration_test!(sitemap, |area| {
+    area.mkdir("docs");
+    area.write_file(
+        Path::new("doctave.yaml"),
+        b"---\ntitle: Indexed\nurl: https://docs.example.com/\n",
+    );
+    area.write_file(Path::new("docs").join("README.md"), b"# Indexed");
+    area.write_file(Path::new("docs").join("tutorial.md"), b"# Tutorial");
+    area.write_file(
+        Path::new("docs").join("unfinished.md"),
+        b"---\ndraft: true\n---\n# Unfinished",
+    );
+    area.write_file(
+        Path::new("docs").join("hidden.md"),
+        b"---\nnoindex: true\n---\n# Hidden",
+    );
+
+    let result = area.cmd(&["build"]);
+    assert_success(&result);
+
+    area.refute_exists(Path::new("site").join("sitemap.xml"));
+    area.refute_exists(Path::new("site").join("robots.txt"));
+
+    let result = area.cmd(&["build", "--release"]);
+    assert_success(&result);
+
+    let sitemap = Path::new("site").join("sitemap.xml");
+    area.assert_contains(
+        &sitemap,
+        "<loc>https://docs.example.com/</loc>\n    <lastmod>",
+    );
+    area.assert_contains(
+        &sitemap,
+        "<loc>https://docs.example.com/tutorial</loc>\n    <lastmod>",
+    );
+    area.refute_contains(&sitemap, "unfinished");
+    area.refute_contains(&sitemap, "hidden");
+
+    area.assert_contains(
+        Path::new("site").join("hidden.html"),
+        "<meta name=\"robots\" content=\"noindex\">",
+    );
+    area.refute_contains(Path::new("site").join("tutorial.html"), "noindex");
+
+    area.assert_contains(
+        Path::new("site").join("robots.txt"),
+        "User-agent: *\nAllow: /\n\nSitemap: https://docs.example.com/sitemap.xml\n",
+    );
+});
+
+integration_test!(sitemap_without_url, |area| {
+    area.create_config();
+    area.mkdir("docs");
+    area.write_file(Path::new("docs").join("README.md"), b"# Docs");
+
+    let result = area.cmd(&["build", "--release"]);
+    assert_success(&result);
+
+    area.refute_exists(Path::new("site").join("sitemap.xml"));
+    area.refute_exists(Path::new("site").join("robots.txt"));
+});
+
+integration_test!(sitemap_with_versions_from_git, |area| {
+    area.create_config();
+    area.mkdir("docs");
+    area.write_file(Path::new("docs").join("README.md"), b"# Old docs");
+    area.write_file(Path::new("docs").join("removed.md"), b"# Removed page");
+
+    area.git(&["init", "-q"]);
+    area.git(&["add", "."]);
+    area.git(&["commit", "-q", "-m", "Old docs"]);
+    area.git(&["tag", "v1"]);
+
+    fs::remove_file(area.path.join("docs").join("removed.md")).unwrap();
+    area.write_file(
+        Path::new("doctave.yaml"),
+        b"---\ntitle: Versioned\nurl: https://example.com\nbase_path: /project\nversions:\n  - name: v2\n  - name: v1\n    git: v1\n",
+    );
+
+    let result = area.cmd(&["build", "--release"]);
+    assert_success(&result);
+
+    let sitemap = Path::new("site").join("sitemap.xml");
+    area.assert_contains(&sitemap, "<loc>https://example.com/project/v2/</loc>");
+    area.assert_contains(
+        &sitemap,
+        "<loc>https://example.com/project/v1/removed</loc>\n    <lastmod>",
+    );
+    area.refute_contains(&sitemap, "/latest");
+
+    area.assert_contains(
+        Path::new("site").join("robots.txt"),
+        "Sitemap: https://example.com/project/sitemap.xml",
+    );
+});
+
+integration_test!(custom_robots, |area| {
+    area.mkdir("docs");
+    area.write_file(
+        Path::new("doctave.yaml"),
+        b"---\ntitle: Indexed\nurl: https://docs.example.com\nrobots: |\n  User-agent: *\n  Disallow: /internal\n",
+    );
+    area.write_file(Path::new("docs").join("README.md"), b"# Indexed");
+
+    let result = area.cmd(&["build", "--release"]);
+    assert_success(&result);
+
+    area.assert_contains(
+        Path::new("site").join("robots.txt"),
+        "User-agent: *\nDisallow: /internal\n\nSitemap: https://docs.example.com/sitemap.xml\n",
+    );
+
+    area.mkdir(Path::new("docs").join("_include"));
+    area.write_file(
+        Path::new("docs").join("_include").join("robots.txt"),
+        b"User-agent: *\nDisallow: /\n",
+    );
+
+    let result = area.cmd(&["build", "--release"]);
+    assert_success(&result);
+
+    area.refute_contains(Path::new("site").join("robots.txt"), "Sitemap");
+});
+
+integration_test!(seo_metadata, |area| {
+    area.create_config();
+    area.mkdir("docs");
+    area.write_file(
+        Path::new("docs").join("README.md"),
+        b"# Welcome\n\nEverything you need to know about *widgets*.\n\nMore text.",
+    );
+    area.write_file(
+        Path::new("docs").join("tutorial.md"),
+        b"---\ntitle: Tutorial\ndescription: Learn widgets & gadgets\nog_image: previews/tutorial.png\n---\n# Tutorial\n\nFirst steps.",
+    );
+    area.write_file(Path::new("docs").join("empty.md"), b"# Empty");
+
+    let result = area.cmd(&["build"]);
+    assert_success(&result);
+
+    let index = Path::new("site").join("index.html");
+    area.assert_contains(
+        &index,
+        "<meta name=\"description\" content=\"Everything you need to know about widgets.\">",
+    );
+    area.assert_contains(
+        &index,
+        "<meta property=\"og:description\" content=\"Everything you need to know about widgets.\">",
+    );
+    area.assert_contains(&index, "<meta name=\"twitter:card\" content=\"summary\">");
+    area.refute_contains(&index, "rel=\"canonical\"");
+    area.refute_contains(&index, "og:image");
+
+    let tutorial = Path::new("site").join("tutorial.html");
+    area.assert_contains(
+        &tutorial,
+        "<meta name=\"description\" content=\"Learn widgets &amp; gadgets\">",
+    );
+    area.assert_contains(
+        &tutorial,
+        "<meta property=\"og:title\" content=\"Tutorial\">",
+    );
+    area.assert_contains(
+        &tutorial,
+        "<meta property=\"og:image\" content=\"/previews/tutorial.png\">",
+    );
+
+    area.assert_contains(
+        Path::new("site").join("empty.html"),
+        "<meta name=\"description\" content=\"Documentation for Test Project\">",
+    );
+});
+
+integration_test!(seo_metadata_with_url, |area| {
+    area.mkdir(Path::new("docs").join("_include"));
+    area.write_file(
+        Path::new("doctave.yaml"),
+        b"---\ntitle: Widgets\nurl: https://docs.example.com\nbase_path: /widgets\nog_image: preview.png\n",
+    );
+    area.write_file(Path::new("docs").join("_include").join("preview.png"), b"");
+    area.write_file(Path::new("docs").join("README.md"), b"# Widgets");
+    area.write_file(
+        Path::new("docs").join("tutorial.md"),
+        b"---\nog_image: https://cdn.example.com/tutorial.png\n---\n# Tutorial",
+    );
+
+    let result = area.cmd(&["build"]);
+    assert_success(&result);
+
+    let index = Path::new("site").join("index.html");
+    area.assert_contains(
+        &index,
+        "<link rel=\"canonical\" href=\"https://docs.example.com/widgets/\">",
+    );
+    area.assert_contains(
+        &index,
+        "<meta property=\"og:image\" content=\"https://docs.example.com/widgets/preview.png\">",
+    );
+    area.assert_contains(
+        &index,
+        "<meta name=\"twitter:card\" content=\"summary_large_image\">",
+    );
+
+    let tutorial = Path::new("site").join("tutorial.html");
+    area.assert_contains(
+        &tutorial,
+        "<meta property=\"og:url\" content=\"https://docs.example.com/widgets/tutorial\">",
+    );
+    area.assert_contains(
+        &tutorial,
+        "<meta property=\"og:image\" content=\"https://cdn.example.com/tutorial.png\">",
+    );
+});
+
+integration_test!(drafts, |area| {
+    area.create_config();
+    area.mkdir(Path::new("docs").join("upcoming"));
+    area.write_file(
+        Path::new("docs").join("README.md"),
+        b"# Docs\n\nRead about the [new feature](/new-feature).",
+    );
+    area.write_file(
+        Path::new("docs").join("new-feature.md"),
+        b"---\ntitle: New feature\ndraft: true\n---\n# New feature",
+    );
+    area.write_file(
+        Path::new("docs").join("upcoming").join("README.md"),
+        b"---\ndraft: true\n---\n# Upcoming",
+    );
+    area.write_file(
+        Path::new("docs").join("upcoming").join("plans.md"),
+        b"---\ntitle: Plans\n---\n# Plans",
+    );
+
+    let result = area.cmd(&["build"]);
+    assert_success(&result);
+    assert_output(
+        &result,
+        "Warning: docs/README.md:3:16: \"/new-feature\" points to a draft page",
+    );
+
+    area.assert_contains(Path::new("site").join("new-feature.html"), "draft-banner");
+    area.refute_contains(Path::new("site").join("index.html"), "draft-banner");
+    area.assert_contains(Path::new("site").join("index.html"), "New feature");
+
+    let result = area.cmd(&["build", "--release"]);
+    assert_success(&result);
+    assert_output(&result, "points to a draft page");
+
+    area.refute_exists(Path::new("site").join("new-feature.html"));
+    area.refute_contains(Path::new("site").join("index.html"), "New feature");
+    area.refute_contains(Path::new("site").join("search_index.json"), "New feature");
+
+    // The directory's index is a draft, so it gets a generated one instead
+    let upcoming = Path::new("site").join("upcoming").join("index.html");
+    area.assert_contains(&upcoming, "Index of upcoming");
+    area.assert_contains(&upcoming, "Plans");
+    area.refute_contains(&upcoming, "draft-banner");
+});
+
+integration_test!(drafts_in_custom_navigation, |area| {
+    area.mkdir("docs");
+    area.write_file(
+        Path::new("doctave.yaml"),
+        indoc! {"
+            ---
+            title: Drafts
+            navigation:
+              - path: docs/tutorial.md
+              - path: docs/new-feature.md
+        "}
+        .as_bytes(),
+    );
+    area.write_file(Path::new("docs").join("README.md"), b"# Docs");
+    area.write_file(
+        Path::new("docs").join("tutorial.md"),
+        b"---\ntitle: Tutorial\n---\n# Tutorial",
+    );
+    area.write_file(
+        Path::new("docs").join("new-feature.md"),
+        b"---\ntitle: New feature\ndraft: true\n---\n# New feature",
+    );
+
+    let result = area.cmd(&["build", "--release"]);
+    assert_success(&result);
+
+    let index = Path::new("site").join("index.html");
+    area.assert_contains(&index, "Tutorial");
+    area.refute_contains(&index, "New feature");
+});
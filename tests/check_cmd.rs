@@ -0,0 +1,158 @@
+#[macro_use]
+extern crate indoc;
+
+#[allow(dead_code)]
+mod support;
+
+use std::path::Path;
+use support::*;
+
+integration_test!(check_valid_links, |area| {
+    area.create_config();
+    area.mkdir(Path::new("docs").join("nested"));
+    area.mkdir(Path::new("docs").join("_include").join("assets"));
+    area.write_file(
+        Path::new("docs")
+            .join("_include")
+            .join("assets")
+            .join("logo.png"),
+        b"",
+    );
+    area.write_file(
+        Path::new("docs").join("README.md"),
+        indoc! {"
+        # Some content
+
+        * [Nested](/nested)
+        * [Other page](nested/other.md)
+        * [External](https://www.doctave.com)
+        * [Mail](mailto:nik@doctave.com)
+
+        ![Logo](/assets/logo.png)
+    "}
+        .as_bytes(),
+    );
+    area.write_file(
+        Path::new("docs").join("nested").join("README.md"),
+        b"# Nested",
+    );
+    area.write_file(
+        Path::new("docs").join("nested").join("other.md"),
+        b"# Other\n\n[Back home](../README.md)",
+    );
+
+    let result = area.cmd(&["check"]);
+    assert_success(&result);
+    assert_output(&result, "No broken links found");
+});
+
+integration_test!(check_relative_links_from_directory_index, |area| {
+    area.create_config();
+    area.mkdir(Path::new("docs").join("features"));
+    area.write_file(Path::new("docs").join("README.md"), b"# Home");
+    area.write_file(
+        Path::new("docs").join("features").join("README.md"),
+        indoc! {"
+        # Features
+
+        * [One](./one)
+        * [Two](two.md)
+        * [Home](../)
+        * [Missing](./missing)
+    "}
+        .as_bytes(),
+    );
+    area.write_file(Path::new("docs").join("features").join("one.md"), b"# One");
+    area.write_file(Path::new("docs").join("features").join("two.md"), b"# Two");
+
+    let result = area.cmd(&["check"]);
+    assert_failed(&result);
+    assert_output(
+        &result,
+        &format!(
+            "{}:6:3: \"./missing\" points to a page that does not exist",
+            Path::new("docs")
+                .join("features")
+                .join("README.md")
+                .display()
+        ),
+    );
+    assert_output(&result, "Error: Found 1 broken link(s)");
+});
+
+integration_test!(check_broken_link, |area| {
+    area.create_config();
+    area.mkdir("docs");
+    area.write_file(
+        Path::new("docs").join("README.md"),
+        indoc! {"
+        ---
+        title: Home
+        ---
+
+        # Some content
+
+        Go [nowhere](/does-not-exist) please.
+    "}
+        .as_bytes(),
+    );
+
+    let result = area.cmd(&["check"]);
+    assert_failed(&result);
+    assert_output(
+        &result,
+        &format!(
+            "docs{}README.md:7:4: \"/does-not-exist\" points to a page that does not exist",
+            std::path::MAIN_SEPARATOR
+        ),
+    );
+    assert_output(&result, "Error: Found 1 broken link(s)");
+});
+
+integration_test!(check_broken_anchor, |area| {
+    area.create_config();
+    area.mkdir("docs");
+    area.write_file(Path::new("docs").join("README.md"), b"# Home");
+    area.write_file(
+        Path::new("docs").join("other.md"),
+        b"# Other\n\n[Home](/#not-a-heading)",
+    );
+
+    let result = area.cmd(&["check"]);
+    assert_failed(&result);
+    assert_output(&result, "points to a heading that does not exist on /");
+});
+
+integration_test!(check_broken_image, |area| {
+    area.create_config();
+    area.mkdir("docs");
+    area.write_file(
+        Path::new("docs").join("README.md"),
+        b"# Home\n\n![Missing](/assets/missing.png)",
+    );
+
+    let result = area.cmd(&["check"]);
+    assert_failed(&result);
+    assert_output(&result, "points to an image that does not exist");
+});
+
+integration_test!(check_links_to_drafts, |area| {
+    area.create_config();
+    area.mkdir("docs");
+    area.write_file(
+        Path::new("docs").join("README.md"),
+        b"# Docs\n\n[Soon](upcoming.md)",
+    );
+    area.write_file(
+        Path::new("docs").join("upcoming.md"),
+        b"---\ndraft: true\n---\n# Upcoming\n\n[Home](/)",
+    );
+
+    let result = area.cmd(&["check"]);
+    assert_success(&result);
+    assert_output(
+        &result,
+        "Warning: docs/README.md:3:1: \"upcoming.md\" points to a draft page, which is left out of release builds",
+    );
+    assert_output(&result, "No broken links found");
+});
@@ -71,3 +71,12 @@ integration_test!(skips_generating_docs_if_docs_folder_exists, |area| {
     area.refute_exists(Path::new("docs").join("examples.md"));
     area.assert_exists(Path::new("doctave.yaml"));
 });
+
+integration_test!(init_in_project_directory, |area| {
+    let result = area.cmd(&["init", "--project", "website"]);
+    assert_success(&result);
+
+    area.assert_exists(Path::new("website").join("doctave.yaml"));
+    area.assert_exists(Path::new("website").join("docs").join("README.md"));
+    area.refute_exists(Path::new("doctave.yaml"));
+});
@@ -65,3 +65,164 @@ integration_test!(serve_smoke_test, |area| {
 
     assert!(buf.contains("Some content"));
 });
+
+integration_test!(serve_search_api, |area| {
+    area.create_config();
+    area.mkdir("docs");
+    area.write_file(Path::new("docs").join("README.md"), b"# Welcome");
+    area.write_file(
+        Path::new("docs").join("guide.md"),
+        b"# Guide\n\nRead this first.\n\n## Installing\n\nRun `make install` & wait.\n",
+    );
+    area.mkdir(Path::new("docs").join("reference").join("api"));
+    area.write_file(
+        Path::new("docs").join("reference").join("README.md"),
+        b"# Reference",
+    );
+    area.write_file(
+        Path::new("docs")
+            .join("reference")
+            .join("api")
+            .join("search.md"),
+        b"# Search endpoint",
+    );
+
+    let mut handle = Command::new(area.binary())
+        .args(&["serve", "--port", "4012"])
+        .current_dir(&area.path)
+        .stdout(std::process::Stdio::null())
+        .spawn()
+        .expect("Unable to spawn command");
+
+    let response = get(4012, "/api/search?q=instal&limit=5");
+    let empty = get(4012, "/api/search?q=");
+    let missing = get(4012, "/nope/api/search?q=instal");
+    let page = get(4012, "/reference/api/search");
+
+    handle.kill().unwrap();
+
+    assert!(
+        response.contains("Content-Type: application/json"),
+        "{}",
+        response
+    );
+    assert!(response.contains("\"query\":\"instal\""), "{}", response);
+    assert!(
+        response.contains("\"uri\":\"/guide#installing"),
+        "{}",
+        response
+    );
+    assert!(
+        response.contains("\"excerpt\":\"Run make <mark>install</mark> &amp; wait.\""),
+        "{}",
+        response
+    );
+    assert!(
+        response.contains("\"title\":\"<mark>Installing</mark>\""),
+        "{}",
+        response
+    );
+
+    assert!(empty.contains("\"results\":[]"), "{}", empty);
+    assert!(missing.contains(" 404 Not Found"), "{}", missing);
+    assert!(page.contains("Search endpoint"), "{}", page);
+});
+
+integration_test!(serve_keeps_unchanged_pages, |area| {
+    area.create_config();
+    area.mkdir("docs");
+    area.mkdir(Path::new("docs").join("_templates"));
+    area.write_file(Path::new("docs").join("README.md"), b"# Some content");
+    area.write_file(
+        Path::new("docs").join("changelog.md"),
+        b"---\nlayout: changelog\n---\n# Changelog",
+    );
+    area.write_file(
+        Path::new("docs").join("_templates").join("changelog.html"),
+        b"<p>First</p>{{{ content }}}",
+    );
+
+    let mut handle = Command::new(area.binary())
+        .args(&["serve", "--port", "4013"])
+        .current_dir(&area.path)
+        .stdout(std::process::Stdio::null())
+        .spawn()
+        .expect("Unable to spawn command");
+
+    get(4013, "/");
+
+    let index = area.path.join("site").join("index.html");
+    let changelog = area.path.join("site").join("changelog.html");
+    let before = std::fs::read_to_string(&index).unwrap();
+    let modified = std::fs::metadata(&index).unwrap().modified().unwrap();
+
+    // A new build a second later would get a different timestamp
+    std::thread::sleep(std::time::Duration::from_millis(1100));
+    area.write_file(
+        Path::new("docs").join("_templates").join("changelog.html"),
+        b"<p>Second</p>{{{ content }}}",
+    );
+
+    let mut attempts = 0;
+    while !std::fs::read_to_string(&changelog)
+        .unwrap_or_default()
+        .contains("Second")
+        && attempts < 50
+    {
+        attempts += 1;
+        std::thread::sleep(std::time::Duration::from_millis(100));
+    }
+
+    handle.kill().unwrap();
+
+    area.assert_contains(Path::new("site").join("changelog.html"), "Second");
+    assert_eq!(std::fs::read_to_string(&index).unwrap(), before);
+    assert_eq!(
+        std::fs::metadata(&index).unwrap().modified().unwrap(),
+        modified
+    );
+});
+
+integration_test!(serve_refuses_to_build_into_docs, |area| {
+    area.create_config();
+    area.mkdir("docs");
+    area.write_file(Path::new("docs").join("README.md"), b"# Some content");
+
+    let result = area.cmd(&["serve", "--out-dir", "docs/site"]);
+    assert_failed(&result);
+    assert_output(
+        &result,
+        "The serve command can't build the site into the docs directory.",
+    );
+    area.refute_exists(Path::new("docs").join("site"));
+});
+
+/// Makes a request to the preview server, waiting for it to start.
+fn get(port: u32, path: &str) -> String {
+    use std::io::Read;
+    use std::io::Write;
+    use std::net::TcpStream;
+
+    let mut attempts = 0;
+    let mut stream = loop {
+        match TcpStream::connect(("localhost", port as u16)) {
+            Ok(stream) => break stream,
+            Err(_) if attempts < 50 => {
+                attempts += 1;
+                std::thread::sleep(std::time::Duration::from_millis(100));
+            }
+            Err(e) => panic!("Could not connect to the preview server: {}", e),
+        }
+    };
+
+    let request = format!(
+        "GET {} HTTP/1.0\r\nHost: localhost\r\nConnection: close\r\n\r\n",
+        path
+    );
+    stream.write_all(request.as_bytes()).unwrap();
+
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+
+    response
+}
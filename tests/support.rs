@@ -71,6 +71,32 @@ impl TestArea {
             .expect("Unable to spawn command")
     }
 
+    /// Runs git with the given arguments in the test area, panicking if it
+    /// fails.
+    pub fn git<I, S>(&self, args: I)
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<OsStr>,
+    {
+        let output = Command::new("git")
+            .args(&[
+                "-c",
+                "user.name=Doctave",
+                "-c",
+                "user.email=test@doctave.com",
+            ])
+            .args(args)
+            .current_dir(&self.path)
+            .output()
+            .expect("Unable to spawn git");
+
+        assert!(
+            output.status.success(),
+            "git failed:\n{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+
     /// The location of the doctave executable
     pub fn binary(&self) -> PathBuf {
         self.project_root.join("..").join("doctave")